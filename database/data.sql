@@ -1,55 +0,0 @@
-INSERT INTO products
-    (shop_id, category_id, brand_id, model, description, color, strap_material, strap_color, case_material, dial_color, movement_type, water_resistance, warranty_period, dimensions, price, stock_quantity, is_top_model)
-VALUES
-    (2, 10, 15, 'Submariner', 'Diver''s watch known for its robustness and classic design.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '300m', '5 years', '40x13mm', 8000.00, 5, TRUE),
-    (2, 11, 2, 'Speedmaster', 'Known as the Moonwatch, first watch on the lunar surface.', 'Black', 'Leather', 'Black', 'Stainless Steel', 'Black', 'Manual', '50m', '5 years', '42x13.5mm', 6000.00, 3, TRUE),
-    (2, 11, 15, 'Daytona', 'High-performance chronograph tailored for racing enthusiasts.', 'White', 'Stainless Steel', 'Silver', 'Stainless Steel', 'White', 'Automatic', '100m', '5 years', '40x12mm', 13000.00, 4, TRUE),
-    (2, 1, 15, 'GMT Master II', 'Designed to display the time in two different time zones simultaneously.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black/Red', 'Automatic', '100m', '5 years', '40x12mm', 9000.00, 6, FALSE),
-    (2, 10, 2, 'Seamaster', 'Iconic dive watch used by James Bond.', 'Blue', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Blue', 'Automatic', '300m', '5 years', '42x13mm', 5000.00, 7, TRUE),
-    (2, 1, 15, 'Explorer', 'Designed for explorers and mountaineers.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '100m', '5 years', '39x11mm', 7000.00, 8, FALSE),
-    (2, 1, 2, 'Constellation', 'A symbol of luxury since 1952.', 'Silver', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Silver', 'Automatic', '50m', '5 years', '38x10mm', 5500.00, 5, FALSE),
-    (2, 1, 15, 'Yacht-Master', 'The watch of the open seas.', 'Blue', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Blue', 'Automatic', '100m', '5 years', '42x12mm', 11000.00, 4, TRUE),
-    (2, 1, 2, 'De Ville', 'Elegance in simplicity.', 'White', 'Leather', 'Brown', 'Stainless Steel', 'White', 'Automatic', '30m', '5 years', '40x11mm', 6000.00, 6, FALSE),
-    (2, 1, 15, 'Air-King', 'Paying tribute to the pioneers of flight.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '100m', '5 years', '40x12mm', 6500.00, 7, FALSE);
-
-
-insert into product_images
-    (product_id, image_url)
-values
-    (20, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (17, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (13, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (19, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (16, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (15, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (12, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (11, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (18, '/images/temp.png');
-insert into product_images
-    (product_id, image_url)
-values
-    (14, '/images/temp.png'); 
\ No newline at end of file
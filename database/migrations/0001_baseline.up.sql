@@ -1,4 +1,5 @@
-
+-- Baseline schema (formerly database/db.sql), ordered so every table is
+-- created after the tables it references.
 
 -- Users Table
 CREATE TABLE users
@@ -44,11 +45,11 @@ CREATE TABLE user_addresses
 
 -- alter table user_addresses
 -- add constraint user_id_deleted_at_unique unique (user_id, deleted_at);
-CREATE UNIQUE INDEX idx_unique_user_not_deleted 
-ON user_addresses(user_id) 
+CREATE UNIQUE INDEX idx_unique_user_not_deleted
+ON user_addresses(user_id)
 WHERE deleted_at IS NULL;
 
-
+CREATE INDEX idx_user_addresses ON user_addresses(user_id);
 
 CREATE TABLE shops
 (
@@ -73,72 +74,6 @@ CREATE TABLE shops
     deleted_at TIMESTAMP DEFAULT null
 );
 
--- Active: The shop is currently operational and open for business.
--- Inactive: The shop is registered but not currently operational.
--- Closed: The shop has permanently closed down.
--- Suspended: The shop's operations are temporarily halted, possibly due to some regulatory or administrative reasons.
--- Pending Approval: The shop's details are under review, and it hasn't been approved to start operations yet.
-
-INSERT INTO shops
-    (
-    name,
-    description,
-    cover_image,
-    address,
-    city,
-    state,
-    postal_code,
-    country,
-    phone,
-    email,
-    website_url,
-    operating_hours,
-    status
-    )
-VALUES
-    ('Timeless Watches',
-        'A shop offering a variety of timeless and classic watch designs.',
-        '/images/timeless_cover.jpg',
-        '123 Watch Street',
-        'Watch City',
-        'Watch State',
-        '12345',
-        'Watchland',
-        '+1234567890',
-        'info@timelesswatches.com',
-        'http://www.timelesswatches.com',
-        'Mon-Fri: 9am-6pm; Sat: 10am-4pm',
-        'Active'),
-
-    ('Modern Timepieces',
-        'Specializing in modern and innovative watch designs for the contemporary individual.',
-        '/images/modern_cover.jpg',
-        '456 Modern Avenue',
-        'Timepiece City',
-        'Modern State',
-        '67890',
-        'Timepiece Country',
-        '+0987654321',
-        'info@moderntimepieces.com',
-        'http://www.moderntimepieces.com',
-        'Mon-Sun: 10am-8pm',
-        'Active'),
-
-    ('Vintage Horology',
-        'A boutique shop offering a curated selection of vintage and antique watches.',
-        '/images/vintage_cover.jpg',
-        '789 Antique Road',
-        'Vintage Town',
-        'Horology State',
-        '11223',
-        'Horology Country',
-        '+1122334455',
-        'info@vintagehorology.com',
-        'http://www.vintagehorology.com',
-        'Tue-Sat: 11am-5pm',
-        'Active');
-
-
 CREATE TABLE categories
 (
     category_id SERIAL PRIMARY KEY,
@@ -152,34 +87,6 @@ CREATE TABLE categories
     deleted_at TIMESTAMP DEFAULT null
 );
 
-INSERT INTO categories
-    (
-    name,
-    description,
-    cover_image
-    )
-VALUES
-    ('Luxury Watches',
-        'High-end watches from renowned brands, showcasing craftsmanship and elegance.',
-        '/images/luxury_category.jpg'),
-
-    ('Sports Watches',
-        'Durable and functional watches designed for active individuals and sports enthusiasts.',
-        '/images/sports_category.jpg'),
-
-    ('Vintage Watches',
-        'Classic timepieces from past eras, offering a nostalgic touch and timeless beauty.',
-        '/images/vintage_category.jpg'),
-
-    ('Smart Watches',
-        'Modern watches integrated with technology to offer features beyond just timekeeping.',
-        '/images/smart_category.jpg'),
-
-    ('Casual Watches',
-        'Everyday watches that combine style and practicality for daily wear.',
-        '/images/casual_category.jpg');
-
-
 CREATE TABLE brands
 (
     brand_id SERIAL PRIMARY KEY,
@@ -193,48 +100,54 @@ CREATE TABLE brands
     deleted_at TIMESTAMP DEFAULT null
 );
 
-INSERT INTO brands
-    (
-    name,
-    description,
-    logo_url
-    )
-VALUES
-    ('Rolex',
-        'A Swiss luxury watch manufacturer known for its timeless designs and precision.',
-        '/logos/rolex_logo.jpg'),
-
-    ('Omega',
-        'A Swiss luxury watchmaker, renowned for its performance and reliability.',
-        '/logos/omega_logo.jpg'),
-
-    ('Casio',
-        'A Japanese multinational consumer electronics company, famous for its durable and innovative watches.',
-        '/logos/casio_logo.jpg'),
-
-    ('Seiko',
-        'A Japanese company that produces watches, clocks, electronic devices, semiconductors, and optical products.',
-        '/logos/seiko_logo.jpg'),
-
-    ('TAG Heuer',
-        'A Swiss luxury watchmaker known for its sports watches and chronographs.',
-        '/logos/tagheuer_logo.jpg'),
+-- e.g., 4.5
 
-    ('Fossil',
-        'An American fashion designer and manufacturer known for its vintage-inspired watches.',
-        '/logos/fossil_logo.jpg');
+CREATE TABLE currencies
+(
+    currency_id SERIAL PRIMARY KEY,
+    currency_code CHAR(3) NOT NULL UNIQUE,
+    currency_name VARCHAR(255) NOT NULL,
+    symbol VARCHAR(10) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT null
+);
 
+CREATE TABLE warranty_types
+(
+    warranty_type_id SERIAL PRIMARY KEY,
+    description VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
 
+CREATE TABLE dial_glass_types
+(
+    dial_glass_type_id SERIAL PRIMARY KEY,
+    description VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
 
-CREATE TABLE product_images
+CREATE TABLE other_accessories_types
 (
-    image_id SERIAL PRIMARY KEY,
-    product_id INT REFERENCES products(product_id),
-    image_url VARCHAR(255) NOT NULL,
+    other_accessories_type_id SERIAL PRIMARY KEY,
+    description VARCHAR(255) NOT NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT null
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
 );
 
+CREATE TABLE genders
+(
+    gender_id SERIAL PRIMARY KEY,
+    description VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
 
 CREATE TABLE products
 (
@@ -286,73 +199,15 @@ CREATE TABLE products
     deleted_at TIMESTAMP DEFAULT null
 );
 
--- strap_material: Material of the watch strap (e.g., Leather, Stainless Steel, Rubber).
--- strap_color: Color of the strap.
--- case_material: Material of the watch case (e.g., Stainless Steel, Titanium, Ceramic).
--- dial_color: Color of the watch dial.
--- movement_type: Type of watch movement (e.g., Automatic, Quartz, Manual).
--- water_resistance: Water resistance level of the watch (e.g., 30m, 100m).
--- warranty_period: Warranty period offered with the watch (e.g., 1 Year, 2 Years).
--- dimensions: Dimensions of the watch (e.g., Case Diameter, Thickness).
--- stock_quantity: Quantity of the watch available in stock.
--- image_url: URL or path to the image of the watch.
-
--- Inserting sample data into products table
-INSERT INTO products
-    (
-    shop_id,
-    category_id,
-    brand_id,
-    model,
-    description,
-    color,
-    strap_material,
-    strap_color,
-    case_material,
-    dial_color,
-    movement_type,
-    water_resistance,
-    warranty_period,
-    dimensions,
-    price,
-    stock_quantity,
-    is_top_model
-    )
-VALUES
-    (1, 1, 1, 'Submariner', 'Dive watch with automatic movement and date function.', 'Black', 'Steel', 'Silver', 'Steel', 'Black', 'Automatic', '300m', '5 years', '40mm', 9000.00, 10, TRUE),
-    (2, 2, 2, 'Speedmaster', 'Chronograph watch used in space missions.', 'Black', 'Leather', 'Black', 'Steel', 'Black', 'Manual', '50m', '3 years', '42mm', 5000.00, 5, TRUE);
-
--- Inserting sample data into product_images table
-INSERT INTO product_images
-    (
-    product_id,
-    image_url
-    )
-VALUES
-    (1, '/images/products/submariner_1.jpg'),
-    (1, '/images/products/submariner_2.jpg'),
-    (1, '/images/products/submariner_3.jpg'),
-    (2, '/images/products/speedmaster_1.jpg'),
-    (2, '/images/products/speedmaster_2.jpg');
-
-
-CREATE TABLE orders
+CREATE TABLE product_images
 (
-    order_id SERIAL PRIMARY KEY,
-    user_id INT REFERENCES users(user_id),
-    shipping_address_id INT REFERENCES order_addresses(address_id),
-    status VARCHAR(50) DEFAULT 'Pending',
-    order_total DECIMAL(10, 2) DEFAULT 0.0,
-    item_counts INT DEFAULT 0,
-    payment_type VARCHAR(50) DEFAULT 'Cash on Delivery',
-    payslip_screenshot_path VARCHAR(255) DEFAULT '',
-    commission_amount DECIMAL(10, 2) DEFAULT 0.0,
-    currency_id INT REFERENCES currencies(currency_id) DEFAULT 1,
-    invoice_id VARCHAR(255) DEFAULT '',
-    invoice_url VARCHAR(255) DEFAULT '',
+    image_id SERIAL PRIMARY KEY,
+    product_id INT REFERENCES products(product_id),
+    image_url VARCHAR(255) NOT NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT null
 );
+
 -- Preorder || Cash on Delivery
 
 -- Pending: The order has been placed but not yet processed.
@@ -383,7 +238,23 @@ CREATE TABLE order_addresses
     deleted_at TIMESTAMP DEFAULT null
 );
 
-
+CREATE TABLE orders
+(
+    order_id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(user_id),
+    shipping_address_id INT REFERENCES order_addresses(address_id),
+    status VARCHAR(50) DEFAULT 'Pending',
+    order_total DECIMAL(10, 2) DEFAULT 0.0,
+    item_counts INT DEFAULT 0,
+    payment_type VARCHAR(50) DEFAULT 'Cash on Delivery',
+    payslip_screenshot_path VARCHAR(255) DEFAULT '',
+    commission_amount DECIMAL(10, 2) DEFAULT 0.0,
+    currency_id INT REFERENCES currencies(currency_id) DEFAULT 1,
+    invoice_id VARCHAR(255) DEFAULT '',
+    invoice_url VARCHAR(255) DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT null
+);
 
 CREATE TABLE order_items
 (
@@ -407,6 +278,7 @@ CREATE TABLE notifications
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT null
 );
+
 -- Unread: The user hasn't seen or interacted with the notification yet.
 -- Read: The user has seen the notification but hasn't taken any action on it.
 -- Acted: The user has taken some action on the notification, such as clicking on a link or button associated with it.
@@ -422,12 +294,11 @@ CREATE TABLE fcm_tokens
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
-CREATE UNIQUE INDEX idx_unique_user_device_type 
+
+CREATE UNIQUE INDEX idx_unique_user_device_type
 ON fcm_tokens(user_id, device_type);
 -- e.g., 'android', 'ios', 'web'
 
-CREATE INDEX idx_user_addresses ON addresses(user_id);
-
 CREATE TABLE user_activity
 (
     activity_id SERIAL PRIMARY KEY,
@@ -468,14 +339,6 @@ CREATE TABLE commission_rules
     deleted_at TIMESTAMP DEFAULT null
 );
 
-INSERT INTO commission_rules
-    (description, commission_percentage, min_order_amount, max_order_amount, effective_from, effective_to, created_at, updated_at)
-VALUES
-    ('Standard Insurance for Watches', 5.00, 100.00, 10000.00, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00'),
-    ('Premium Insurance for Luxury Watches', 7.00, 10001.00, 50000.00, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00'),
-    ('Basic Insurance for Affordable Watches', 3.00, 1.00, 99.99, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00');
-
-
 CREATE TABLE insurance_options
 (
     option_id SERIAL PRIMARY KEY,
@@ -486,7 +349,6 @@ CREATE TABLE insurance_options
     deleted_at TIMESTAMP DEFAULT null
 );
 
-
 CREATE TABLE seller_reviews
 (
     review_id SERIAL PRIMARY KEY,
@@ -498,32 +360,6 @@ CREATE TABLE seller_reviews
     deleted_at TIMESTAMP DEFAULT null
 );
 
--- e.g., 4.5
-
-CREATE TABLE currencies
-(
-    currency_id SERIAL PRIMARY KEY,
-    currency_code CHAR(3) NOT NULL UNIQUE,
-    currency_name VARCHAR(255) NOT NULL,
-    symbol VARCHAR(10) NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT null
-);
-
--- ISO 4217 currency code, e.g., USD, EUR, MMK
--- e.g., $, €, ကျပ်
-
-INSERT INTO currencies
-    (currency_code, currency_name, symbol)
-VALUES
-    ('USD', 'United States Dollar', '$'),
-    ('SGD', 'Singapore Dollar', 'S$'),
-    ('THB', 'Thai Baht', '฿'),
-    ('CNY', 'China Yuan Renminbi', '¥'),
-    ('MMK', 'Myanmar Kyat', 'Ks');
-
-
 CREATE TABLE bank_accounts
 (
     account_id SERIAL PRIMARY KEY,
@@ -537,60 +373,27 @@ CREATE TABLE bank_accounts
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-insert into bank_accounts
-    (account_holder_name, account_number, bank_logo)
-values
-    ('U La Min Tun', '002211188001232', '');
-insert into bank_accounts
-    (account_holder_name, account_number, bank_logo)
-values
-    ('Daw Yin Yin Myo', '27030127000219801', '');
-insert into bank_accounts
-    (account_holder_name, account_number, bank_logo)
-values
-    ('Daw Yin Yin Myo', '0014600100018578', '');
-
-CREATE TABLE warranty_types
+CREATE TABLE buyer_protections
 (
-    warranty_type_id SERIAL PRIMARY KEY,
+    buyer_protection_id SERIAL PRIMARY KEY,
     description VARCHAR(255) NOT NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-insert into warranty_types
-    (description)
-values
-    ('Local Seller Warranty');
-insert into warranty_types
-    (description)
-values
-    ('International Warranty');
-insert into warranty_types
-    (description)
-values
-    ('Authorized Distributor Warranty');
-
-CREATE TABLE buyer_protections
+CREATE TABLE seller_registration_fees
 (
-    buyer_protection_id SERIAL PRIMARY KEY,
+    fee_id SERIAL PRIMARY KEY,
     description VARCHAR(255) NOT NULL,
+    amount DECIMAL(18, 2) DEFAULT 0.0,
+    is_percent BOOLEAN DEFAULT FALSE,
+    currency_id INT REFERENCES currencies(currency_id) DEFAULT 1,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-insert into buyer_protections
-    (description)
-values
-    ('Authenticity Guarantee');
-insert into buyer_protections
-    (description)
-values
-    ('14-day money-back guarantee');
-
-
 CREATE TABLE seller_informations
 (
     seller_id SERIAL PRIMARY KEY,
@@ -625,32 +428,6 @@ CREATE TABLE seller_informations
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-CREATE TABLE dial_glass_types
-(
-    dial_glass_type_id SERIAL PRIMARY KEY,
-    description VARCHAR(255) NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT NULL
-);
-
-insert into dial_glass_types
-    (description)
-values
-    ('Sapphire Crystal');
-insert into dial_glass_types
-    (description)
-values
-    ('Hardlex Crystal');
-insert into dial_glass_types
-    (description)
-values
-    ('Mineral Glass');
-insert into dial_glass_types
-    (description)
-values
-    ('Acrylic Crystal');
-
 CREATE TABLE conditions
 (
     condition_id SERIAL PRIMARY KEY,
@@ -660,83 +437,6 @@ CREATE TABLE conditions
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-insert into conditions
-    (description)
-values
-    ('Brand New');
-insert into conditions
-    (description)
-values
-    ('Unworn');
-insert into conditions
-    (description)
-values
-    ('Very Good');
-insert into conditions
-    (description)
-values
-    ('Good');
-insert into conditions
-    (description)
-values
-    ('Fair');
-insert into conditions
-    (description)
-values
-    ('Poor');
-insert into conditions
-    (description)
-values
-    ('Incomplete');
-
-CREATE TABLE other_accessories_types
-(
-    other_accessories_type_id SERIAL PRIMARY KEY,
-    description VARCHAR(255) NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT NULL
-);
-
-insert into other_accessories_types
-    (description)
-values
-    ('Original box, warranty card, manual book');
-insert into other_accessories_types
-    (description)
-values
-    ('No original box, warranty card, manual book');
-insert into other_accessories_types
-    (description)
-values
-    ('Only original box');
-insert into other_accessories_types
-    (description)
-values
-    ('Watch only');
-
-CREATE TABLE genders
-(
-    gender_id SERIAL PRIMARY KEY,
-    description VARCHAR(255) NOT NULL,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT NULL
-);
-
-insert into genders
-    (description)
-values
-    ('Men');
-insert into genders
-    (description)
-values
-    ('Women');
-insert into genders
-    (description)
-values
-    ('Unisex');
-
 CREATE TABLE chats
 (
     chat_id SERIAL PRIMARY KEY,
@@ -772,7 +472,6 @@ CREATE TABLE audio_messages
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-
 CREATE TABLE messages
 (
     message_id SERIAL PRIMARY KEY,
@@ -824,66 +523,6 @@ CREATE TABLE movement_types
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Automatic with manual winding');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Automatic with self-winding');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Digital');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Mechanical');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Japanese Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Chinese Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('German Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Russian Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('American Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Swiss Quartz');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Smart');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Chronograph');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Solar');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Analog');
-INSERT INTO movement_types
-    (description)
-VALUES
-    ('Kinetic');
 
 CREATE TABLE strap_materials
 (
@@ -893,71 +532,6 @@ CREATE TABLE strap_materials
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Stainless steel');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Steel');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Brass');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Artificial leather');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Alloy');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Titanium');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Leather');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Metal');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Fabric');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Plastic');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Rubber');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Wood');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Ceramic');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Nylon');
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Silicone');
--- Corrected from 'Silicon'
-INSERT INTO strap_materials
-    (description)
-VALUES
-    ('Carbon fiber');
 
 CREATE TABLE case_materials
 (
@@ -967,54 +541,6 @@ CREATE TABLE case_materials
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-insert into case_materials
-    (description)
-values
-    ('Stainless steel');
-insert into case_materials
-    (description)
-values
-    ('Steel');
-insert into case_materials
-    (description)
-values
-    ('Titanium');
-insert into case_materials
-    (description)
-values
-    ('Carbon');
-insert into case_materials
-    (description)
-values
-    ('Wood');
-insert into case_materials
-    (description)
-values
-    ('Alloy');
-insert into case_materials
-    (description)
-values
-    ('Plastic');
-insert into case_materials
-    (description)
-values
-    ('Bronze');
-insert into case_materials
-    (description)
-values
-    ('Pure gold');
-insert into case_materials
-    (description)
-values
-    ('Ceramic');
-insert into case_materials
-    (description)
-values
-    ('Platinum');
-insert into case_materials
-    (description)
-values
-    ('Diamond');
 
 CREATE TABLE water_resistances
 (
@@ -1024,87 +550,6 @@ CREATE TABLE water_resistances
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('3 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('5 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('10 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('30 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('100 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('300 bar');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('3ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('5ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('10ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('20ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('50ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('100ATM');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('30m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('50m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('100m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('200m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('300m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('500m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('1000m');
-INSERT INTO water_resistances
-    (description)
-VALUES
-    ('2000m');
-
 
 CREATE TABLE movement_countries
 (
@@ -1114,34 +559,6 @@ CREATE TABLE movement_countries
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('Switzerland');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('Japan');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('China');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('USA');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('Russia');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('Germany');
-INSERT INTO movement_countries
-    (description)
-VALUES
-    ('Others');
 
 CREATE TABLE report_subjects
 (
@@ -1151,31 +568,6 @@ CREATE TABLE report_subjects
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('Suspicious vendor');
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('Counterfeit watch');
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('Inaccurate listing');
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('This watch is unavailable');
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('The dealer hasn''t responded');
-INSERT INTO report_subjects
-    (description)
-VALUES
-    ('Other');
-
 
 CREATE TABLE seller_reports
 (
@@ -1198,19 +590,6 @@ CREATE TABLE payment_types
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
 );
-insert into payment_types
-    (description)
-values
-    ('Cash on Delivery');
-insert into payment_types
-    (description)
-values
-    ('Half Prepaid');
-insert into payment_types
-    (description)
-values
-    ('Full Prepaid');
-
 
 CREATE TABLE reason_types
 (
@@ -1221,28 +600,6 @@ CREATE TABLE reason_types
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-INSERT INTO reason_types
-    (description)
-VALUES
-    ('Incorrect Product Specifications');
-INSERT INTO reason_types
-    (description)
-VALUES
-    ('Product is Defective or Damaged');
-INSERT INTO reason_types
-    (description)
-VALUES
-    ('The Watch is Not Working');
-INSERT INTO reason_types
-    (description)
-VALUES
-    ('Watch Not Received');
-INSERT INTO reason_types
-    (description)
-VALUES
-    ('Quality Not as Described in App');
-
-
 CREATE TABLE refund_reasons
 (
     refund_reason_id SERIAL PRIMARY KEY,
@@ -1261,10 +618,6 @@ CREATE TABLE counters
     label VARCHAR(255) NOT NULL,
     n INT default 1
 );
-insert into counters
-    (label)
-values
-    ('777');
 
 CREATE TABLE discount_types
 (
@@ -1275,43 +628,6 @@ CREATE TABLE discount_types
     deleted_at TIMESTAMP DEFAULT NULL
 );
 
-INSERT INTO discount_types
-    (description)
-VALUES
-    ('No Discount');
-
-INSERT INTO discount_types
-    (description)
-VALUES
-    ('Discount by Specific Percentage');
-
-INSERT INTO discount_types
-    (description)
-VALUES
-    ('Discount by Specific Amount');
-
-
-CREATE TABLE seller_registration_fees
-(
-    fee_id SERIAL PRIMARY KEY,
-    description VARCHAR(255) NOT NULL,
-    amount DECIMAL(18, 2) DEFAULT 0.0,
-    is_percent BOOLEAN DEFAULT FALSE,
-    currency_id INT REFERENCES currencies(currency_id) DEFAULT 1,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-    deleted_at TIMESTAMP DEFAULT NULL
-);
-
-insert into seller_registration_fees
-    (description, amount, is_percent)
-values
-    ('Monthly Fee', '50000.00', false);
-insert into seller_registration_fees
-    (description, amount, is_percent)
-values
-    ('Commission Fee', '5', true);
-
 CREATE TABLE seller_agreement_contract
 (
     aggrement_id SERIAL PRIMARY KEY,
@@ -1392,4 +708,4 @@ CREATE TABLE used_coupons
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     deleted_at TIMESTAMP DEFAULT NULL
-);
\ No newline at end of file
+);
@@ -0,0 +1,801 @@
+-- Reference data and demo records the application expects on a fresh database.
+
+-- Active: The shop is currently operational and open for business.
+-- Inactive: The shop is registered but not currently operational.
+-- Closed: The shop has permanently closed down.
+-- Suspended: The shop's operations are temporarily halted, possibly due to some regulatory or administrative reasons.
+-- Pending Approval: The shop's details are under review, and it hasn't been approved to start operations yet.
+
+INSERT INTO shops
+    (
+    name,
+    description,
+    cover_image,
+    address,
+    city,
+    state,
+    postal_code,
+    country,
+    phone,
+    email,
+    website_url,
+    operating_hours,
+    status
+    )
+VALUES
+    ('Timeless Watches',
+        'A shop offering a variety of timeless and classic watch designs.',
+        '/images/timeless_cover.jpg',
+        '123 Watch Street',
+        'Watch City',
+        'Watch State',
+        '12345',
+        'Watchland',
+        '+1234567890',
+        'info@timelesswatches.com',
+        'http://www.timelesswatches.com',
+        'Mon-Fri: 9am-6pm; Sat: 10am-4pm',
+        'Active'),
+
+    ('Modern Timepieces',
+        'Specializing in modern and innovative watch designs for the contemporary individual.',
+        '/images/modern_cover.jpg',
+        '456 Modern Avenue',
+        'Timepiece City',
+        'Modern State',
+        '67890',
+        'Timepiece Country',
+        '+0987654321',
+        'info@moderntimepieces.com',
+        'http://www.moderntimepieces.com',
+        'Mon-Sun: 10am-8pm',
+        'Active'),
+
+    ('Vintage Horology',
+        'A boutique shop offering a curated selection of vintage and antique watches.',
+        '/images/vintage_cover.jpg',
+        '789 Antique Road',
+        'Vintage Town',
+        'Horology State',
+        '11223',
+        'Horology Country',
+        '+1122334455',
+        'info@vintagehorology.com',
+        'http://www.vintagehorology.com',
+        'Tue-Sat: 11am-5pm',
+        'Active');
+
+INSERT INTO categories
+    (
+    name,
+    description,
+    cover_image
+    )
+VALUES
+    ('Luxury Watches',
+        'High-end watches from renowned brands, showcasing craftsmanship and elegance.',
+        '/images/luxury_category.jpg'),
+
+    ('Sports Watches',
+        'Durable and functional watches designed for active individuals and sports enthusiasts.',
+        '/images/sports_category.jpg'),
+
+    ('Vintage Watches',
+        'Classic timepieces from past eras, offering a nostalgic touch and timeless beauty.',
+        '/images/vintage_category.jpg'),
+
+    ('Smart Watches',
+        'Modern watches integrated with technology to offer features beyond just timekeeping.',
+        '/images/smart_category.jpg'),
+
+    ('Casual Watches',
+        'Everyday watches that combine style and practicality for daily wear.',
+        '/images/casual_category.jpg');
+
+INSERT INTO brands
+    (
+    name,
+    description,
+    logo_url
+    )
+VALUES
+    ('Rolex',
+        'A Swiss luxury watch manufacturer known for its timeless designs and precision.',
+        '/logos/rolex_logo.jpg'),
+
+    ('Omega',
+        'A Swiss luxury watchmaker, renowned for its performance and reliability.',
+        '/logos/omega_logo.jpg'),
+
+    ('Casio',
+        'A Japanese multinational consumer electronics company, famous for its durable and innovative watches.',
+        '/logos/casio_logo.jpg'),
+
+    ('Seiko',
+        'A Japanese company that produces watches, clocks, electronic devices, semiconductors, and optical products.',
+        '/logos/seiko_logo.jpg'),
+
+    ('TAG Heuer',
+        'A Swiss luxury watchmaker known for its sports watches and chronographs.',
+        '/logos/tagheuer_logo.jpg'),
+
+    ('Fossil',
+        'An American fashion designer and manufacturer known for its vintage-inspired watches.',
+        '/logos/fossil_logo.jpg');
+
+-- ISO 4217 currency code, e.g., USD, EUR, MMK
+-- e.g., $, €, ကျပ်
+
+INSERT INTO currencies
+    (currency_code, currency_name, symbol)
+VALUES
+    ('USD', 'United States Dollar', '$'),
+    ('SGD', 'Singapore Dollar', 'S$'),
+    ('THB', 'Thai Baht', '฿'),
+    ('CNY', 'China Yuan Renminbi', '¥'),
+    ('MMK', 'Myanmar Kyat', 'Ks');
+
+insert into warranty_types
+    (description)
+values
+    ('Local Seller Warranty');
+
+insert into warranty_types
+    (description)
+values
+    ('International Warranty');
+
+insert into warranty_types
+    (description)
+values
+    ('Authorized Distributor Warranty');
+
+insert into dial_glass_types
+    (description)
+values
+    ('Sapphire Crystal');
+
+insert into dial_glass_types
+    (description)
+values
+    ('Hardlex Crystal');
+
+insert into dial_glass_types
+    (description)
+values
+    ('Mineral Glass');
+
+insert into dial_glass_types
+    (description)
+values
+    ('Acrylic Crystal');
+
+insert into other_accessories_types
+    (description)
+values
+    ('Original box, warranty card, manual book');
+
+insert into other_accessories_types
+    (description)
+values
+    ('No original box, warranty card, manual book');
+
+insert into other_accessories_types
+    (description)
+values
+    ('Only original box');
+
+insert into other_accessories_types
+    (description)
+values
+    ('Watch only');
+
+insert into genders
+    (description)
+values
+    ('Men');
+
+insert into genders
+    (description)
+values
+    ('Women');
+
+insert into genders
+    (description)
+values
+    ('Unisex');
+
+-- strap_material: Material of the watch strap (e.g., Leather, Stainless Steel, Rubber).
+-- strap_color: Color of the strap.
+-- case_material: Material of the watch case (e.g., Stainless Steel, Titanium, Ceramic).
+-- dial_color: Color of the watch dial.
+-- movement_type: Type of watch movement (e.g., Automatic, Quartz, Manual).
+-- water_resistance: Water resistance level of the watch (e.g., 30m, 100m).
+-- warranty_period: Warranty period offered with the watch (e.g., 1 Year, 2 Years).
+-- dimensions: Dimensions of the watch (e.g., Case Diameter, Thickness).
+-- stock_quantity: Quantity of the watch available in stock.
+-- image_url: URL or path to the image of the watch.
+
+-- Inserting sample data into products table
+INSERT INTO products
+    (
+    shop_id,
+    category_id,
+    brand_id,
+    model,
+    description,
+    color,
+    strap_material,
+    strap_color,
+    case_material,
+    dial_color,
+    movement_type,
+    water_resistance,
+    warranty_period,
+    dimensions,
+    price,
+    stock_quantity,
+    is_top_model
+    )
+VALUES
+    (1, 1, 1, 'Submariner', 'Dive watch with automatic movement and date function.', 'Black', 'Steel', 'Silver', 'Steel', 'Black', 'Automatic', '300m', '5 years', '40mm', 9000.00, 10, TRUE),
+    (2, 2, 2, 'Speedmaster', 'Chronograph watch used in space missions.', 'Black', 'Leather', 'Black', 'Steel', 'Black', 'Manual', '50m', '3 years', '42mm', 5000.00, 5, TRUE);
+
+-- Inserting sample data into product_images table
+INSERT INTO product_images
+    (
+    product_id,
+    image_url
+    )
+VALUES
+    (1, '/images/products/submariner_1.jpg'),
+    (1, '/images/products/submariner_2.jpg'),
+    (1, '/images/products/submariner_3.jpg'),
+    (2, '/images/products/speedmaster_1.jpg'),
+    (2, '/images/products/speedmaster_2.jpg');
+
+INSERT INTO commission_rules
+    (description, commission_percentage, min_order_amount, max_order_amount, effective_from, effective_to, created_at, updated_at)
+VALUES
+    ('Standard Insurance for Watches', 5.00, 100.00, 10000.00, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00'),
+    ('Premium Insurance for Luxury Watches', 7.00, 10001.00, 50000.00, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00'),
+    ('Basic Insurance for Affordable Watches', 3.00, 1.00, 99.99, '2023-01-01 00:00:00', '2033-01-01 00:00:00', '2023-01-01 00:00:00', '2023-01-01 00:00:00');
+
+insert into bank_accounts
+    (account_holder_name, account_number, bank_logo)
+values
+    ('U La Min Tun', '002211188001232', '');
+
+insert into bank_accounts
+    (account_holder_name, account_number, bank_logo)
+values
+    ('Daw Yin Yin Myo', '27030127000219801', '');
+
+insert into bank_accounts
+    (account_holder_name, account_number, bank_logo)
+values
+    ('Daw Yin Yin Myo', '0014600100018578', '');
+
+insert into buyer_protections
+    (description)
+values
+    ('Authenticity Guarantee');
+
+insert into buyer_protections
+    (description)
+values
+    ('14-day money-back guarantee');
+
+insert into seller_registration_fees
+    (description, amount, is_percent)
+values
+    ('Monthly Fee', '50000.00', false);
+
+insert into seller_registration_fees
+    (description, amount, is_percent)
+values
+    ('Commission Fee', '5', true);
+
+insert into conditions
+    (description)
+values
+    ('Brand New');
+
+insert into conditions
+    (description)
+values
+    ('Unworn');
+
+insert into conditions
+    (description)
+values
+    ('Very Good');
+
+insert into conditions
+    (description)
+values
+    ('Good');
+
+insert into conditions
+    (description)
+values
+    ('Fair');
+
+insert into conditions
+    (description)
+values
+    ('Poor');
+
+insert into conditions
+    (description)
+values
+    ('Incomplete');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Automatic with manual winding');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Automatic with self-winding');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Digital');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Mechanical');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Japanese Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Chinese Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('German Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Russian Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('American Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Swiss Quartz');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Smart');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Chronograph');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Solar');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Analog');
+
+INSERT INTO movement_types
+    (description)
+VALUES
+    ('Kinetic');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Stainless steel');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Steel');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Brass');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Artificial leather');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Alloy');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Titanium');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Leather');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Metal');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Fabric');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Plastic');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Rubber');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Wood');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Ceramic');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Nylon');
+
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Silicone');
+
+-- Corrected from 'Silicon'
+INSERT INTO strap_materials
+    (description)
+VALUES
+    ('Carbon fiber');
+
+insert into case_materials
+    (description)
+values
+    ('Stainless steel');
+
+insert into case_materials
+    (description)
+values
+    ('Steel');
+
+insert into case_materials
+    (description)
+values
+    ('Titanium');
+
+insert into case_materials
+    (description)
+values
+    ('Carbon');
+
+insert into case_materials
+    (description)
+values
+    ('Wood');
+
+insert into case_materials
+    (description)
+values
+    ('Alloy');
+
+insert into case_materials
+    (description)
+values
+    ('Plastic');
+
+insert into case_materials
+    (description)
+values
+    ('Bronze');
+
+insert into case_materials
+    (description)
+values
+    ('Pure gold');
+
+insert into case_materials
+    (description)
+values
+    ('Ceramic');
+
+insert into case_materials
+    (description)
+values
+    ('Platinum');
+
+insert into case_materials
+    (description)
+values
+    ('Diamond');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('3 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('5 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('10 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('30 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('100 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('300 bar');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('3ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('5ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('10ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('20ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('50ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('100ATM');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('30m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('50m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('100m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('200m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('300m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('500m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('1000m');
+
+INSERT INTO water_resistances
+    (description)
+VALUES
+    ('2000m');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('Switzerland');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('Japan');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('China');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('USA');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('Russia');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('Germany');
+
+INSERT INTO movement_countries
+    (description)
+VALUES
+    ('Others');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('Suspicious vendor');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('Counterfeit watch');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('Inaccurate listing');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('This watch is unavailable');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('The dealer hasn''t responded');
+
+INSERT INTO report_subjects
+    (description)
+VALUES
+    ('Other');
+
+insert into payment_types
+    (description)
+values
+    ('Cash on Delivery');
+
+insert into payment_types
+    (description)
+values
+    ('Half Prepaid');
+
+insert into payment_types
+    (description)
+values
+    ('Full Prepaid');
+
+INSERT INTO reason_types
+    (description)
+VALUES
+    ('Incorrect Product Specifications');
+
+INSERT INTO reason_types
+    (description)
+VALUES
+    ('Product is Defective or Damaged');
+
+INSERT INTO reason_types
+    (description)
+VALUES
+    ('The Watch is Not Working');
+
+INSERT INTO reason_types
+    (description)
+VALUES
+    ('Watch Not Received');
+
+INSERT INTO reason_types
+    (description)
+VALUES
+    ('Quality Not as Described in App');
+
+insert into counters
+    (label)
+values
+    ('777');
+
+INSERT INTO discount_types
+    (description)
+VALUES
+    ('No Discount');
+
+INSERT INTO discount_types
+    (description)
+VALUES
+    ('Discount by Specific Percentage');
+
+INSERT INTO discount_types
+    (description)
+VALUES
+    ('Discount by Specific Amount');
+
+-- Demo catalogue (formerly database/data.sql). Rows whose shop, category or
+-- brand is missing are skipped so a fresh database can still be seeded.
+INSERT INTO products
+    (shop_id, category_id, brand_id, model, description, color, strap_material, strap_color, case_material, dial_color, movement_type, water_resistance, warranty_period, dimensions, price, stock_quantity, is_top_model)
+SELECT v.*
+FROM (
+VALUES
+    (2, 10, 15, 'Submariner', 'Diver''s watch known for its robustness and classic design.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '300m', '5 years', '40x13mm', 8000.00, 5, TRUE),
+    (2, 11, 2, 'Speedmaster', 'Known as the Moonwatch, first watch on the lunar surface.', 'Black', 'Leather', 'Black', 'Stainless Steel', 'Black', 'Manual', '50m', '5 years', '42x13.5mm', 6000.00, 3, TRUE),
+    (2, 11, 15, 'Daytona', 'High-performance chronograph tailored for racing enthusiasts.', 'White', 'Stainless Steel', 'Silver', 'Stainless Steel', 'White', 'Automatic', '100m', '5 years', '40x12mm', 13000.00, 4, TRUE),
+    (2, 1, 15, 'GMT Master II', 'Designed to display the time in two different time zones simultaneously.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black/Red', 'Automatic', '100m', '5 years', '40x12mm', 9000.00, 6, FALSE),
+    (2, 10, 2, 'Seamaster', 'Iconic dive watch used by James Bond.', 'Blue', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Blue', 'Automatic', '300m', '5 years', '42x13mm', 5000.00, 7, TRUE),
+    (2, 1, 15, 'Explorer', 'Designed for explorers and mountaineers.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '100m', '5 years', '39x11mm', 7000.00, 8, FALSE),
+    (2, 1, 2, 'Constellation', 'A symbol of luxury since 1952.', 'Silver', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Silver', 'Automatic', '50m', '5 years', '38x10mm', 5500.00, 5, FALSE),
+    (2, 1, 15, 'Yacht-Master', 'The watch of the open seas.', 'Blue', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Blue', 'Automatic', '100m', '5 years', '42x12mm', 11000.00, 4, TRUE),
+    (2, 1, 2, 'De Ville', 'Elegance in simplicity.', 'White', 'Leather', 'Brown', 'Stainless Steel', 'White', 'Automatic', '30m', '5 years', '40x11mm', 6000.00, 6, FALSE),
+    (2, 1, 15, 'Air-King', 'Paying tribute to the pioneers of flight.', 'Black', 'Stainless Steel', 'Silver', 'Stainless Steel', 'Black', 'Automatic', '100m', '5 years', '40x12mm', 6500.00, 7, FALSE)
+) AS v(shop_id, category_id, brand_id, model, description, color, strap_material, strap_color, case_material, dial_color, movement_type, water_resistance, warranty_period, dimensions, price, stock_quantity, is_top_model)
+WHERE EXISTS (SELECT 1 FROM shops WHERE shop_id = v.shop_id)
+    AND EXISTS (SELECT 1 FROM categories WHERE category_id = v.category_id)
+    AND EXISTS (SELECT 1 FROM brands WHERE brand_id = v.brand_id);
+
+INSERT INTO product_images
+    (product_id, image_url)
+SELECT product_id, '/images/temp.png'
+FROM products
+WHERE product_id IN (20, 17, 13, 19, 16, 15, 12, 11, 18, 14);
@@ -0,0 +1,12 @@
+DROP TABLE IF EXISTS proxy_bids;
+
+ALTER TABLE auctions DROP COLUMN IF EXISTS closing_soon_notified_at;
+ALTER TABLE auctions DROP COLUMN IF EXISTS order_id;
+ALTER TABLE auctions DROP COLUMN IF EXISTS proxy_bidding_enabled;
+ALTER TABLE auctions DROP COLUMN IF EXISTS soft_close_minutes;
+ALTER TABLE auctions DROP COLUMN IF EXISTS buy_it_now_price;
+
+DROP TABLE IF EXISTS order_status_history;
+DROP TABLE IF EXISTS inventory_ledger;
+
+ALTER TABLE orders DROP COLUMN IF EXISTS stock_restored_at;
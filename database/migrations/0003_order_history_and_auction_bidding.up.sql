@@ -0,0 +1,52 @@
+-- Catches databases created from the old db.sql up with the schema the
+-- application has relied on since: stock restoration, the inventory ledger,
+-- order status history, soft-close and proxy bidding for auctions.
+
+CREATE INDEX IF NOT EXISTS idx_user_addresses ON user_addresses(user_id);
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS stock_restored_at TIMESTAMP DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS inventory_ledger
+(
+    ledger_id SERIAL PRIMARY KEY,
+    product_id INT REFERENCES products(product_id),
+    order_id INT REFERENCES orders(order_id),
+    quantity_change INT NOT NULL,
+    reason VARCHAR(50) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+-- initial_stock, adjustment, order_placed, order_cancelled, order_refunded, order_returned, order_failed
+CREATE INDEX IF NOT EXISTS idx_inventory_ledger_product_id ON inventory_ledger(product_id);
+
+CREATE TABLE IF NOT EXISTS order_status_history
+(
+    history_id SERIAL PRIMARY KEY,
+    order_id INT REFERENCES orders(order_id),
+    from_status VARCHAR(50) DEFAULT NULL, -- null for the initial status of a new order
+    to_status VARCHAR(50) NOT NULL,
+    actor_id INT REFERENCES users(user_id),
+    note TEXT DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_order_status_history_order_id ON order_status_history(order_id);
+
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS buy_it_now_price DECIMAL(10, 2) DEFAULT NULL;
+-- a bid in the last N minutes pushes end_time to now + N, 0 disables it
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS soft_close_minutes INT NOT NULL DEFAULT 0;
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS proxy_bidding_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS order_id INTEGER REFERENCES orders(order_id);
+ALTER TABLE auctions ADD COLUMN IF NOT EXISTS closing_soon_notified_at TIMESTAMP DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS proxy_bids
+(
+    proxy_bid_id SERIAL PRIMARY KEY,
+    auction_id INTEGER REFERENCES auctions(auction_id),
+    bidder_id INTEGER REFERENCES users(user_id),
+    max_amount DECIMAL(10, 2) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_unique_proxy_bid_not_deleted
+ON proxy_bids(auction_id, bidder_id)
+WHERE deleted_at IS NULL;
@@ -0,0 +1,12 @@
+CREATE TABLE refresh_tokens
+(
+    refresh_token_id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(user_id),
+    session_id VARCHAR(36) NOT NULL, -- shared by every token rotated from the same login
+    token_hash VARCHAR(64) NOT NULL UNIQUE, -- sha256 of the token, the token itself is never stored
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_session_id ON refresh_tokens(session_id);
@@ -0,0 +1,3 @@
+-- Bumped whenever a user's role, permissions, status or password changes, access
+-- tokens signed with an older version are rejected.
+ALTER TABLE users ADD COLUMN token_version INT NOT NULL DEFAULT 1;
@@ -0,0 +1,11 @@
+CREATE TABLE password_reset_codes
+(
+    reset_code_id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(user_id),
+    code_hash TEXT NOT NULL, -- bcrypt hash, the code itself is only sent to the user
+    attempts INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_password_reset_codes_user_id ON password_reset_codes(user_id);
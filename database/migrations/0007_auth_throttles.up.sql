@@ -0,0 +1,11 @@
+-- Failed attempt counters for login and password reset, one row per client IP or
+-- account and scope
+CREATE TABLE auth_throttles
+(
+    scope VARCHAR(50) NOT NULL,
+    throttle_key VARCHAR(255) NOT NULL,
+    failures INT NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP DEFAULT NULL,
+    last_failure_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (scope, throttle_key)
+);
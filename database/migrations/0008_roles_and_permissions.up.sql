@@ -0,0 +1,63 @@
+CREATE TABLE permissions
+(
+    permission_id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT ''
+);
+
+-- users.role holds the role name. base_role decides which records a role sees in
+-- listings (admin, agent or user), its permissions decide what it may do.
+CREATE TABLE roles
+(
+    role_id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    base_role VARCHAR(50) NOT NULL,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE, -- the built in admin, agent and user roles
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE role_permissions
+(
+    role_id INT REFERENCES roles(role_id) ON DELETE CASCADE,
+    permission_id INT REFERENCES permissions(permission_id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+INSERT INTO permissions
+    (name, description)
+VALUES
+    ('catalog.view', 'View brands, categories and product attributes in the seller tools'),
+    ('catalog.manage', 'Create, update and delete brands and categories'),
+    ('content.manage', 'Manage advertisements, buyer protection, insurance rules, terms and seller fees'),
+    ('products.manage_own', 'Create, update and delete own products'),
+    ('products.manage_any', 'Update and delete products of any seller'),
+    ('auctions.manage', 'Create, update, close and delete auctions'),
+    ('shops.manage_own', 'Create and update own shops while pending approval'),
+    ('shops.manage_any', 'Update, approve and delete shops of any seller'),
+    ('discounts.manage', 'Manage discount rules'),
+    ('orders.manage_own', 'Cancel, return or complete own orders'),
+    ('orders.update_status', 'Change the status of orders'),
+    ('orders.view_any', 'View the history of any order'),
+    ('users.manage', 'View, create, update and delete users'),
+    ('users.view_phone', 'See buyer phone numbers on orders'),
+    ('users.view_address', 'See buyer addresses on orders'),
+    ('reports.view', 'View seller reports'),
+    ('notifications.broadcast', 'Send push notifications to all users'),
+    ('chat.moderate', 'Delete any chat message'),
+    ('roles.manage', 'Create, update and delete roles and assign them to users');
+
+INSERT INTO roles
+    (name, description, base_role, is_system)
+VALUES
+    ('admin', 'Full access', 'admin', TRUE),
+    ('agent', 'Sellers managing their own shops and products', 'agent', TRUE),
+    ('user', 'Buyers', 'user', TRUE);
+
+INSERT INTO role_permissions
+    (role_id, permission_id)
+SELECT r.role_id, p.permission_id
+FROM roles r, permissions p
+WHERE r.name = 'admin'
+    OR (r.name = 'agent' AND p.name IN ('catalog.view', 'products.manage_own', 'auctions.manage', 'shops.manage_own', 'discounts.manage'))
+    OR (r.name = 'user' AND p.name IN ('orders.manage_own'));
@@ -0,0 +1,2 @@
+DELETE FROM permissions WHERE name = 'sellers.approve';
+DROP TABLE IF EXISTS seller_application_decisions;
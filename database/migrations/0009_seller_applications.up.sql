@@ -0,0 +1,25 @@
+-- Every admin decision on a seller application (a user with request_to_agent set).
+-- The latest row is the application's current state.
+CREATE TABLE seller_application_decisions
+(
+    decision_id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(user_id),
+    decision VARCHAR(50) NOT NULL, -- Approved, Rejected or Documents Requested
+    reason TEXT NOT NULL DEFAULT '',
+    decided_by INT NOT NULL REFERENCES users(user_id),
+    shop_id INT REFERENCES shops(shop_id), -- the starter shop created on approval
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX seller_application_decisions_user_id_idx ON seller_application_decisions (user_id);
+
+INSERT INTO permissions
+    (name, description)
+VALUES
+    ('sellers.approve', 'Review seller applications and approve, reject or request documents');
+
+INSERT INTO role_permissions
+    (role_id, permission_id)
+SELECT r.role_id, p.permission_id
+FROM roles r, permissions p
+WHERE r.name = 'admin' AND p.name = 'sellers.approve';
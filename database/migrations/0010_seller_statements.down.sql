@@ -0,0 +1,4 @@
+DELETE FROM permissions WHERE name = 'billing.manage';
+UPDATE shops SET status = 'Active' WHERE billing_suspended_at IS NOT NULL;
+ALTER TABLE shops DROP COLUMN IF EXISTS billing_suspended_at;
+DROP TABLE IF EXISTS seller_statements;
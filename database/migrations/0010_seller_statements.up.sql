@@ -0,0 +1,39 @@
+-- One statement per seller and month, billed on the seller's plan (seller_informations.fee_id).
+-- A flat plan bills its amount, a percent plan bills that share of the seller's completed sales.
+CREATE TABLE seller_statements
+(
+    statement_id SERIAL PRIMARY KEY,
+    seller_id INT NOT NULL REFERENCES users(user_id),
+    fee_id INT REFERENCES seller_registration_fees(fee_id),
+    period_start DATE NOT NULL, -- first day of the billed month
+    period_end DATE NOT NULL, -- first day of the next month
+    fee_amount DECIMAL(18, 2) NOT NULL DEFAULT 0.0,
+    is_percent BOOLEAN NOT NULL DEFAULT FALSE,
+    sales_total DECIMAL(18, 2) NOT NULL DEFAULT 0.0,
+    amount_due DECIMAL(18, 2) NOT NULL DEFAULT 0.0,
+    currency_id INT REFERENCES currencies(currency_id) DEFAULT 1,
+    status VARCHAR(50) NOT NULL DEFAULT 'Unpaid', -- Unpaid, Payment Submitted, Paid
+    due_at TIMESTAMP NOT NULL,
+    payslip_screenshot_path VARCHAR(255) DEFAULT '',
+    payment_submitted_at TIMESTAMP DEFAULT NULL,
+    paid_at TIMESTAMP DEFAULT NULL,
+    paid_by INT REFERENCES users(user_id),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (seller_id, period_start)
+);
+
+CREATE INDEX seller_statements_status_due_at_idx ON seller_statements (status, due_at);
+
+-- Set when billing suspended the shop, so paying up only reopens shops billing closed
+ALTER TABLE shops ADD COLUMN billing_suspended_at TIMESTAMP DEFAULT NULL;
+
+INSERT INTO permissions
+    (name, description)
+VALUES
+    ('billing.manage', 'View all seller statements and mark them paid');
+
+INSERT INTO role_permissions
+    (role_id, permission_id)
+SELECT r.role_id, p.permission_id
+FROM roles r, permissions p
+WHERE r.name = 'admin' AND p.name = 'billing.manage';
@@ -0,0 +1,4 @@
+-- What the item cost before its discount and why it was discounted, kept for invoices.
+-- Items ordered before this have no original price recorded.
+ALTER TABLE order_items ADD COLUMN original_price DECIMAL(10, 2);
+ALTER TABLE order_items ADD COLUMN discount_reason TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,3 @@
+ALTER TABLE orders DROP COLUMN IF EXISTS coupon_discount;
+DROP TABLE IF EXISTS coupon_redemptions;
+DROP TABLE IF EXISTS coupons;
@@ -0,0 +1,41 @@
+-- Order coupons. A coupon without a shop works in any shop, one with a product only
+-- discounts that product's items.
+CREATE TABLE coupons
+(
+    coupon_id SERIAL PRIMARY KEY,
+    code VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    discount_type VARCHAR(255) NOT NULL, -- Discount by Specific Percentage or Discount by Specific Amount
+    discount_percent DECIMAL(10, 2) NOT NULL DEFAULT 0,
+    discount_amount DECIMAL(18, 2) NOT NULL DEFAULT 0,
+    shop_id INT REFERENCES shops(shop_id),
+    product_id INT REFERENCES products(product_id),
+    min_order_amount DECIMAL(18, 2) NOT NULL DEFAULT 0,
+    max_redemptions INT, -- null for no limit
+    max_redemptions_per_user INT,
+    starts_at TIMESTAMP,
+    ends_at TIMESTAMP,
+    creator_id INT REFERENCES users(user_id),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMP DEFAULT NULL
+);
+
+CREATE UNIQUE INDEX coupons_code_key ON coupons (upper(code)) WHERE deleted_at IS NULL;
+
+-- A coupon used on an order. Cancelling the order releases it so it counts against
+-- the limits no more.
+CREATE TABLE coupon_redemptions
+(
+    redemption_id SERIAL PRIMARY KEY,
+    coupon_id INT NOT NULL REFERENCES coupons(coupon_id),
+    user_id INT NOT NULL REFERENCES users(user_id),
+    order_id INT NOT NULL REFERENCES orders(order_id),
+    discount_amount DECIMAL(18, 2) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    released_at TIMESTAMP DEFAULT NULL
+);
+
+CREATE INDEX coupon_redemptions_coupon_id_idx ON coupon_redemptions (coupon_id, user_id);
+CREATE INDEX coupon_redemptions_order_id_idx ON coupon_redemptions (order_id);
+
+ALTER TABLE orders ADD COLUMN coupon_discount DECIMAL(18, 2) NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Rule discounts cleared from products are not copied back
+ALTER TABLE order_items DROP COLUMN IF EXISTS discount_rule_id;
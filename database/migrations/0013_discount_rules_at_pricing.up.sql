@@ -0,0 +1,10 @@
+-- Discount rules used to be copied into the products they cover. They are now looked up
+-- when a product is priced, so the copies are cleared and products keep only the
+-- discounts set on them directly.
+UPDATE products
+SET discount_percent = 0.0, discount_expiration = NULL, discount_reason = '', discounted_price = 0.0,
+    discount_type = 'No Discount', coupon_code = NULL, discount_updated_by = 'none'
+WHERE discount_updated_by IN ('brand', 'category', 'all');
+
+-- The rule an ordered item was discounted by, if any
+ALTER TABLE order_items ADD COLUMN discount_rule_id INT REFERENCES discount_rules(rule_id);
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS product_favorites;
+DROP INDEX IF EXISTS discount_rules_starts_at_idx;
+ALTER TABLE discount_rules DROP COLUMN IF EXISTS deactivated_at;
+ALTER TABLE discount_rules DROP COLUMN IF EXISTS activated_at;
+ALTER TABLE discount_rules DROP COLUMN IF EXISTS ends_at;
+ALTER TABLE discount_rules DROP COLUMN IF EXISTS starts_at;
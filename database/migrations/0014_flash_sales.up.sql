@@ -0,0 +1,19 @@
+-- A rule with a start time is a sale set up ahead of time. `ends_at` takes over from
+-- `discount_expiration`, which is still accepted from older clients.
+ALTER TABLE discount_rules ADD COLUMN starts_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE discount_rules ADD COLUMN ends_at TIMESTAMP DEFAULT NULL;
+-- Set by the scheduler when it announces a sale starting and sees it end
+ALTER TABLE discount_rules ADD COLUMN activated_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE discount_rules ADD COLUMN deactivated_at TIMESTAMP DEFAULT NULL;
+UPDATE discount_rules SET ends_at = discount_expiration;
+CREATE INDEX discount_rules_starts_at_idx ON discount_rules (starts_at) WHERE deleted_at IS NULL;
+
+CREATE TABLE product_favorites
+(
+    favorite_id SERIAL PRIMARY KEY,
+    user_id INT NOT NULL REFERENCES users(user_id),
+    product_id INT NOT NULL REFERENCES products(product_id),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, product_id)
+);
+CREATE INDEX product_favorites_product_id_idx ON product_favorites (product_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_unique_user_facebook_id;
+DROP INDEX IF EXISTS idx_unique_user_apple_id;
+DROP INDEX IF EXISTS idx_unique_user_google_id;
@@ -0,0 +1,12 @@
+-- A provider account belongs to one user, so linking it to two users at once fails
+CREATE UNIQUE INDEX idx_unique_user_google_id
+ON users(google_id)
+WHERE google_id IS NOT NULL AND deleted_at IS NULL;
+
+CREATE UNIQUE INDEX idx_unique_user_apple_id
+ON users(apple_id)
+WHERE apple_id IS NOT NULL AND deleted_at IS NULL;
+
+CREATE UNIQUE INDEX idx_unique_user_facebook_id
+ON users(facebook_id)
+WHERE facebook_id IS NOT NULL AND deleted_at IS NULL;
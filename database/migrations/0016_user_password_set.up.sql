@@ -0,0 +1,7 @@
+-- Whether the user chose the password, accounts signed up through a provider only get
+-- one by changing or resetting it. Those accounts were signed up with the email as the
+-- username, which is the best guess for the ones that already exist.
+ALTER TABLE users ADD COLUMN password_set BOOLEAN NOT NULL DEFAULT TRUE;
+UPDATE users SET password_set = FALSE
+WHERE username = email
+  AND (google_id IS NOT NULL OR apple_id IS NOT NULL OR facebook_id IS NOT NULL);
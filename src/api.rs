@@ -13,10 +13,12 @@ mod category;
 mod chat;
 mod condition;
 mod counter;
+mod coupon;
 mod currency;
 mod dial_glass_type;
 mod discount_rule;
 mod discount_type;
+mod favorite;
 mod fcm;
 mod gender;
 mod google;
@@ -31,11 +33,14 @@ mod payment_type;
 mod product;
 mod reason_type;
 mod refund_reason;
+mod role;
 mod seller_agreement_contract;
+mod seller_application;
 mod seller_information;
 mod seller_registration_fee;
 mod seller_report;
 mod seller_review;
+mod seller_statement;
 mod setting;
 mod shop;
 mod stock_quantity;
@@ -50,6 +55,8 @@ use actix_web::web;
 
 pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(auth::login);
+    cfg.service(auth::refresh);
+    cfg.service(auth::logout);
     cfg.service(auth::hash_password);
     cfg.service(auth::register);
     cfg.service(image::upload);
@@ -71,6 +78,7 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(user::delete_user);
     cfg.service(order::get_order_items);
     cfg.service(order::update_order);
+    cfg.service(order::get_order_status_history);
     cfg.service(product::add_product);
     cfg.service(product::get_product_by_id);
     cfg.service(product::update_product);
@@ -85,6 +93,9 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(category::update_category);
     cfg.service(category::delete_category);
     cfg.service(auth::change_password);
+    cfg.service(auth::link_provider);
+    cfg.service(auth::unlink_provider);
+    cfg.service(auth::get_linked_providers);
     cfg.service(user::get_user_profile);
     cfg.service(user::update_user_profile);
     cfg.service(fcm::add_fcm);
@@ -109,6 +120,7 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(currency::get_currencies);
     cfg.service(image::remove_dangling_images);
     cfg.service(auth::forgot_password);
+    cfg.service(auth::reset_password);
     cfg.service(bank_account::get_bank_accounts);
     cfg.service(bank_account::add_bank_account);
     cfg.service(bank_account::get_bank_account_by_id);
@@ -175,6 +187,10 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(discount_rule::update_discount_rule);
     cfg.service(discount_rule::delete_discount_rule);
     cfg.service(discount_rule::get_discount_fors);
+    cfg.service(discount_rule::get_upcoming_sales);
+    cfg.service(favorite::get_favorites);
+    cfg.service(favorite::add_favorite);
+    cfg.service(favorite::delete_favorite);
     cfg.service(advertisement::add_advertisement);
     cfg.service(advertisement::get_advertisements);
     cfg.service(advertisement::get_advertisement_by_id);
@@ -186,7 +202,33 @@ pub fn init(cfg: &mut web::ServiceConfig) {
     cfg.service(auction::get_auction_by_id);
     cfg.service(auction::update_auction);
     cfg.service(auction::delete_auction);
+    cfg.service(auction::place_bid);
+    cfg.service(auction::get_bids);
+    cfg.service(auction::close_auction);
+    cfg.service(auction::buy_it_now);
     cfg.service(discount_rule::add_used_coupon);
     cfg.service(discount_rule::delete_used_coupon);
     cfg.service(discount_rule::get_used_coupons);
+    cfg.service(coupon::validate_coupon);
+    cfg.service(coupon::get_coupons);
+    cfg.service(coupon::add_coupon);
+    cfg.service(coupon::get_coupon_by_id);
+    cfg.service(coupon::update_coupon);
+    cfg.service(coupon::delete_coupon);
+    cfg.service(role::get_permissions);
+    cfg.service(role::get_roles);
+    cfg.service(role::add_role);
+    cfg.service(role::get_role_by_id);
+    cfg.service(role::update_role);
+    cfg.service(role::delete_role);
+    cfg.service(role::assign_role);
+    cfg.service(seller_application::get_seller_applications);
+    cfg.service(seller_application::get_seller_application);
+    cfg.service(seller_application::get_decisions);
+    cfg.service(seller_application::add_decision);
+    cfg.service(seller_statement::get_seller_statements);
+    cfg.service(seller_statement::get_seller_statement_by_id);
+    cfg.service(seller_statement::submit_payment);
+    cfg.service(seller_statement::mark_paid);
+    cfg.service(seller_statement::reject_payment);
 }
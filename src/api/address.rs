@@ -1,60 +1,23 @@
-use std::sync::Arc;
-
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
-use tokio_postgres::Client;
+use actix_web::{get, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 
 use crate::{
     models::address::{self, Address},
     utils::{
+        auth::AuthUser,
         common_struct::{BaseResponse, DataResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
     },
 };
 
 #[get("/api/address")]
-pub async fn get_address(req: HttpRequest, client: web::Data<Arc<Client>>) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+pub async fn get_address(auth: AuthUser, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
+    let user_id = auth.user_id;
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
     match address::get_address(user_id, &client).await {
         Ok(address) => {
             if let Some(a) = address {
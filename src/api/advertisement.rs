@@ -1,18 +1,20 @@
 use actix_web::Error;
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
+use std::fs::File;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::path::PathBuf;
-use std::{fs::File, sync::Arc};
-use tokio_postgres::Client;
 
 use crate::{
     models::advertisement::{self, AdvertisementRequest},
     utils::{
+        auth::{Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::ContentManage,
     },
 };
 
@@ -25,31 +27,14 @@ pub struct GetAdvertisementsQuery {
 
 #[get("/api/advertisements")]
 pub async fn get_advertisements(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    _auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetAdvertisementsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if !token.is_empty() {
-        if verify_token_and_get_sub(&token).is_none() {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            });
-        }
-    }
     match advertisement::get_advertisements(&query.search, query.page, query.per_page, &client)
         .await
     {
@@ -75,60 +60,15 @@ pub async fn get_advertisements(
 
 #[post("/api/advertisements")]
 pub async fn add_advertisement(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     body: web::Json<AdvertisementRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
     if body.media_url.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
             code: 400,
@@ -161,59 +101,15 @@ pub async fn add_advertisement(
 
 #[get("/api/advertisements/{advertisement_id}")]
 pub async fn get_advertisement_by_id(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let advertisement_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let advertisement_id = path.into_inner();
 
     match advertisement::get_advertisement_by_id(advertisement_id, &client).await {
         Some(a) => HttpResponse::Ok().json(DataResponse {
@@ -230,60 +126,16 @@ pub async fn get_advertisement_by_id(
 
 #[put("/api/advertisements/{advertisement_id}")]
 pub async fn update_advertisement(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
     body: web::Json<AdvertisementRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let advertisement_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let advertisement_id = path.into_inner();
 
     if body.media_url.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -332,59 +184,15 @@ pub async fn update_advertisement(
 
 #[delete("/api/advertisements/{advertisement_id}")]
 pub async fn delete_advertisement(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let advertisement_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let advertisement_id = path.into_inner();
 
     match advertisement::get_advertisement_by_id(advertisement_id, &client).await {
         Some(a) => {
@@ -414,8 +222,12 @@ pub async fn delete_advertisement(
 async fn stream_video(
     advertisement_id: web::Path<i32>,
     req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> Result<HttpResponse, Error> {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return Ok(res),
+    };
     let file_path = match advertisement::get_advertisement_by_id(*advertisement_id, &client).await {
         Some(a) => a.media_url,
         None => {
@@ -1,12 +1,16 @@
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use std::sync::Arc;
-use tokio_postgres::Client;
 
-use crate::models::auction::{self, AuctionRequest};
+use crate::models::{
+    auction::{self, AuctionRequest, BidRequest, BidResult, BuyItNowResult},
+    product,
+};
 use crate::utils::{
+    auth::{forbidden, AuthUser, Authorized, OptionalAuthUser},
     common_struct::{BaseResponse, DataResponse, PaginationResponse},
-    jwt::verify_token_and_get_sub,
+    db::get_client,
+    permission::{AuctionsManage, PRODUCTS_MANAGE_ANY},
 };
 
 #[derive(Deserialize)]
@@ -18,31 +22,14 @@ pub struct GetAuctionsQuery {
 
 #[get("/api/auctions")]
 pub async fn get_auctions(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    _auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetAuctionsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if !token.is_empty() {
-        if verify_token_and_get_sub(&token).is_none() {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            });
-        }
-    }
     match auction::get_auctions(&query.search, query.page, query.per_page, &client).await {
         Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
             code: 200,
@@ -66,60 +53,15 @@ pub async fn get_auctions(
 
 #[post("/api/auctions")]
 pub async fn add_auction(
-    req: HttpRequest,
+    _auth: Authorized<AuctionsManage>,
     body: web::Json<AuctionRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
     if body.start_time.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
             code: 400,
@@ -151,59 +93,15 @@ pub async fn add_auction(
 
 #[get("/api/auctions/{auction_id}")]
 pub async fn get_auction_by_id(
-    req: HttpRequest,
+    _auth: Authorized<AuctionsManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let auction_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let auction_id = path.into_inner();
 
     match auction::get_auction_by_id(auction_id, &client).await {
         Some(a) => HttpResponse::Ok().json(DataResponse {
@@ -220,60 +118,16 @@ pub async fn get_auction_by_id(
 
 #[put("/api/auctions/{auction_id}")]
 pub async fn update_auction(
-    req: HttpRequest,
+    _auth: Authorized<AuctionsManage>,
     path: web::Path<i32>,
     body: web::Json<AuctionRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let auction_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let auction_id = path.into_inner();
 
     if body.start_time.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -312,59 +166,15 @@ pub async fn update_auction(
 
 #[delete("/api/auctions/{auction_id}")]
 pub async fn delete_auction(
-    req: HttpRequest,
+    _auth: Authorized<AuctionsManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let auction_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let auction_id = path.into_inner();
 
     match auction::get_auction_by_id(auction_id, &client).await {
         Some(_) => match auction::delete_auction(auction_id, &client).await {
@@ -386,3 +196,256 @@ pub async fn delete_auction(
         }),
     }
 }
+
+#[derive(Deserialize)]
+pub struct GetBidsQuery {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+#[post("/api/auctions/{auction_id}/bids")]
+pub async fn place_bid(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    body: web::Json<BidRequest>,
+    pool: web::Data<Pool>,
+) -> HttpResponse {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let auction_id = path.into_inner();
+    let user_id = auth.user_id;
+
+    if body.bid_amount <= 0.0 {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Bid amount must be greater than zero!"),
+        });
+    }
+
+    match auction::place_bid(
+        auction_id,
+        user_id,
+        body.bid_amount,
+        body.max_amount,
+        &mut client,
+    )
+    .await
+    {
+        Ok(BidResult::Accepted(bid)) => {
+            let bid_id = bid.bid_id;
+            tokio::spawn(async move {
+                if let Err(err) = auction::broadcast_bid(&bid, &client).await {
+                    println!("Error broadcasting bid: {:?}", err);
+                }
+            });
+            HttpResponse::Created().json(DataResponse {
+                code: 201,
+                message: String::from("Bid placed successfully"),
+                data: Some(bid_id),
+            })
+        }
+        Ok(BidResult::AuctionNotFound) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Auction not found!"),
+        }),
+        Ok(BidResult::NotActive) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Auction is no longer active!"),
+        }),
+        Ok(BidResult::NotStarted) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Auction has not started yet!"),
+        }),
+        Ok(BidResult::Ended) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Auction has already ended!"),
+        }),
+        Ok(BidResult::OwnAuction) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("You cannot bid on your own auction!"),
+        }),
+        Ok(BidResult::ProxyBiddingDisabled) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Proxy bidding is not enabled for this auction!"),
+        }),
+        Ok(BidResult::MaxAmountTooLow) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Maximum bid must not be lower than your bid amount!"),
+        }),
+        Ok(BidResult::TooLow(minimum_bid)) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: format!("Bid amount must be at least {minimum_bid}!"),
+        }),
+        Err(e) => {
+            eprintln!("Bid placing error: {}", e);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error placing bid!"),
+            })
+        }
+    }
+}
+
+#[get("/api/auctions/{auction_id}/bids")]
+pub async fn get_bids(
+    _auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+    query: web::Query<GetBidsQuery>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let auction_id = path.into_inner();
+    match auction::get_bids(auction_id, query.page, query.per_page, &client).await {
+        Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: item_result.data,
+            total: item_result.total,
+            page: item_result.page,
+            per_page: item_result.per_page,
+            page_counts: item_result.page_counts,
+        }),
+        Err(err) => {
+            // Log the error message here
+            println!("Error retrieving bids: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read all bids from database"),
+            })
+        }
+    }
+}
+
+#[post("/api/auctions/{auction_id}/close")]
+pub async fn close_auction(
+    auth: Authorized<AuctionsManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> HttpResponse {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let auction_id = path.into_inner();
+
+    let auction = match auction::get_auction_by_id(auction_id, &client).await {
+        Some(a) => a,
+        None => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Auction not found!"),
+            })
+        }
+    };
+
+    // Sellers close their own auctions once they have ended, only staff who manage every
+    // seller's products can end an auction early
+    let manages_any = auth.has_permission(PRODUCTS_MANAGE_ANY);
+    if !manages_any
+        && product::get_product_creator_id(auction.product_id, &client).await != Some(auth.user_id)
+    {
+        return forbidden();
+    }
+    if !manages_any && !auction::has_auction_ended(auction_id, &client).await {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Auction has not ended yet!"),
+        });
+    }
+
+    match auction::finalize_auction(auction_id, &mut client).await {
+        Ok(Some(result)) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Auction closed successfully"),
+            data: Some(result),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Active auction not found!"),
+        }),
+        Err(e) => {
+            eprintln!("Auction closing error: {}", e);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error closing auction!"),
+            })
+        }
+    }
+}
+
+#[post("/api/auctions/{auction_id}/buy-now")]
+pub async fn buy_it_now(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> HttpResponse {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let auction_id = path.into_inner();
+    let user_id = auth.user_id;
+
+    let result = match auction::buy_it_now(auction_id, user_id, &mut client).await {
+        Ok(BuyItNowResult::Purchased(result)) => result,
+        Ok(BuyItNowResult::AuctionNotFound) => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Auction not found!"),
+            })
+        }
+        Ok(BuyItNowResult::NotActive) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Auction is no longer active!"),
+            })
+        }
+        Ok(BuyItNowResult::NotStarted) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Auction has not started yet!"),
+            })
+        }
+        Ok(BuyItNowResult::Ended) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Auction has already ended!"),
+            })
+        }
+        Ok(BuyItNowResult::OwnAuction) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("You cannot buy your own auction!"),
+            })
+        }
+        Ok(BuyItNowResult::Unavailable) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Buy It Now is not available for this auction!"),
+            })
+        }
+        Err(e) => {
+            eprintln!("Buy it now error: {}", e);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error buying auction!"),
+            });
+        }
+    };
+
+    // The order was created with the purchase, settling only sends the invoice and notifications
+    if let Err(e) = auction::settle_auction(&result, &client).await {
+        eprintln!("Auction settling error: {}", e);
+    }
+
+    HttpResponse::Ok().json(DataResponse {
+        code: 200,
+        message: String::from("Auction purchased successfully"),
+        data: Some(result),
+    })
+}
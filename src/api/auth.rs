@@ -1,19 +1,28 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::models::auth_throttle::{self, ThrottleKey};
 use crate::models::notification;
+use crate::models::password_reset::{self, PasswordReset};
+use crate::models::refresh_token::{self, RefreshTokenRotation};
 use crate::models::seller_information::SellerInformationRequest;
-use crate::models::user::{self, get_user, get_user_by_google_id, is_phone_existed, user_exists};
+use crate::models::user::{self, get_user, get_user_by_provider_id, is_phone_existed, user_exists};
+use crate::utils::auth::AuthUser;
+use crate::utils::code_delivery::{CodeDelivery, DeliveryChannel};
 use crate::utils::common_struct::{BaseResponse, DataResponse};
-use crate::utils::google;
-use crate::utils::jwt::{self, verify_token_and_get_sub};
+use crate::utils::db::get_client;
+use crate::utils::identity_provider::{IdentityProviders, Provider};
+use crate::utils::jwt;
+use crate::utils::setting::{
+    get_access_token_expires_in, get_login_lockout_minutes,
+    get_password_reset_code_expires_in_minutes, get_trust_proxy_headers,
+};
 use crate::utils::validator::{validate_email, validate_mobile};
-use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
 use bcrypt::{hash, verify, DEFAULT_COST};
+use deadpool_postgres::{GenericClient, Pool};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_postgres::Client;
+use tokio_postgres::error::SqlState;
 
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -31,9 +40,14 @@ pub struct RegisterRequest {
 
 #[post("/api/auth/register")]
 pub async fn register(
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
+    identity_providers: web::Data<IdentityProviders>,
     body: web::Json<RegisterRequest>,
 ) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let mut method = "password";
     if let Some(m) = &body.method {
         method = m;
@@ -70,17 +84,36 @@ pub async fn register(
 
     let request_to_agent = if role == "agent" { true } else { false };
 
-    if method == "google" {
+    if let Some(provider) = Provider::from_method(method) {
         if let Some(t) = &body.token {
-            match google::verify_id_token_with_google_api(t).await {
-                Ok(res) => match user_exists(&res.email, &client).await {
+            match identity_providers.get(provider).verify(t).await {
+                Ok(res) => match user_exists(
+                    // Apple and Facebook can keep the email to themselves, the one typed
+                    // in by the user is used then
+                    res.email.as_ref().unwrap_or(&body.email),
+                    &client,
+                )
+                .await
+                {
                     Ok(exists) => {
-                        if exists {
+                        if exists
+                            || get_user_by_provider_id(provider, &res.subject, &client)
+                                .await
+                                .is_some()
+                        {
                             return HttpResponse::BadRequest().json(BaseResponse {
                                 code: 400,
                                 message: String::from("User already exists!"),
                             });
                         }
+                        let email = res.email.as_ref().unwrap_or(&body.email);
+                        if !validate_email(email) {
+                            return HttpResponse::BadRequest().json(BaseResponse {
+                                code: 400,
+                                message: String::from("Invalid email!"),
+                            });
+                        }
+                        let name = res.name.as_ref().unwrap_or(&body.name);
 
                         let profile_image = if let Some(picture) = &res.picture {
                             picture
@@ -89,10 +122,10 @@ pub async fn register(
                         };
 
                         match user::add_user(
-                            &res.name,
-                            &res.email,
+                            name,
+                            email,
                             &body.password,
-                            &res.email,
+                            email,
                             &body.phone,
                             profile_image,
                             role,
@@ -101,7 +134,7 @@ pub async fn register(
                             false,
                             false,
                             &body.seller_information,
-                            &Some(res.sub),
+                            &Some((provider, res.subject.clone())),
                             request_to_agent,
                             &client,
                         )
@@ -279,106 +312,332 @@ pub struct LoginRequest {
 #[derive(Serialize)]
 pub struct LoginData {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
     pub name: String,
     pub profile_image: String,
     pub role: String,
     pub can_modify_order_status: bool,
 }
 
+// Signs with the role, permissions and token version currently stored for the user
+async fn sign_user_access_token(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<String, HttpResponse> {
+    let token_user = match user::get_token_user(user_id, client).await {
+        Ok(Some(token_user)) => token_user,
+        Ok(None) => {
+            return Err(HttpResponse::Unauthorized().json(BaseResponse {
+                code: 401,
+                message: String::from("Unauthorized!"),
+            }))
+        }
+        Err(err) => {
+            println!("Error reading token user: {:?}", err);
+            return Err(HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            }));
+        }
+    };
+    jwt::sign_access_token(&token_user).map_err(|err| {
+        println!("Error signing access token: {:?}", err);
+        HttpResponse::InternalServerError().json(BaseResponse {
+            code: 500,
+            message: String::from("Something went wrong!"),
+        })
+    })
+}
+
+const LOGIN_SCOPE: &str = "login";
+const FORGOT_PASSWORD_SCOPE: &str = "forgot-password";
+const RESET_PASSWORD_SCOPE: &str = "reset-password";
+
+// bcrypt hash of a throwaway password, checked against when the username does not exist
+const DUMMY_PASSWORD_HASH: &str = "$2b$12$qCq8yz8c.sZUmyA/hYDcm.cmbvqPZSi7oCQLp5fyBtoVCecn6kzjq";
+
+fn get_client_ip(req: &HttpRequest) -> String {
+    if get_trust_proxy_headers() {
+        if let Some(ip) = req.connection_info().realip_remote_addr() {
+            return ip.to_string();
+        }
+    }
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_default()
+}
+
+// Rejects the request with 429 while the client IP or the account is locked
+async fn check_throttle(
+    scope: &str,
+    keys: &[ThrottleKey],
+    client: &impl GenericClient,
+) -> Result<(), HttpResponse> {
+    match auth_throttle::get_locked_seconds(scope, keys, client).await {
+        Ok(0) => Ok(()),
+        Ok(seconds) => Err(HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", seconds.to_string()))
+            .json(BaseResponse {
+                code: 429,
+                message: format!(
+                    "Too many attempts. Please try again in {} seconds.",
+                    seconds
+                ),
+            })),
+        Err(err) => {
+            println!("Error reading auth throttle: {:?}", err);
+            Err(HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            }))
+        }
+    }
+}
+
+async fn record_failures(scope: &str, keys: &[ThrottleKey], client: &impl GenericClient) {
+    for key in keys {
+        let attempt = match auth_throttle::record_failure(scope, key, client).await {
+            Ok(attempt) => attempt,
+            Err(err) => {
+                println!("Error recording auth failure: {:?}", err);
+                continue;
+            }
+        };
+        if !attempt.locked_out {
+            continue;
+        }
+        let message = match key {
+            ThrottleKey::Ip(ip) => format!(
+                "IP {} was blocked from {} for {} minutes after {} failed attempts.",
+                ip,
+                scope,
+                get_login_lockout_minutes(),
+                attempt.failures
+            ),
+            ThrottleKey::Account(username) => format!(
+                "Account {} was locked from {} for {} minutes after {} failed attempts.",
+                username,
+                scope,
+                get_login_lockout_minutes(),
+                attempt.failures
+            ),
+        };
+        match notification::add_notification_to_admins(
+            "Account Lockout Alert",
+            &message,
+            &None,
+            client,
+        )
+        .await
+        {
+            Ok(()) => {
+                println!("Notification added successfully.");
+            }
+            Err(err) => {
+                println!("Error adding notification: {:?}", err);
+            }
+        };
+    }
+}
+
+async fn clear_failures(scope: &str, key: &ThrottleKey, client: &impl GenericClient) {
+    if let Err(err) = auth_throttle::clear_failures(scope, key, client).await {
+        println!("Error clearing auth failures: {:?}", err);
+    }
+}
+
 #[post("/api/auth/login")]
 pub async fn login(
-    client: web::Data<Arc<Client>>,
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    identity_providers: web::Data<IdentityProviders>,
     credentials: web::Json<LoginRequest>,
 ) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let method = if let Some(m) = &credentials.method {
         m
     } else {
         "password"
     };
 
-    let mut username_or_google_id = credentials.username.clone();
-    if method == "google" {
-        if let Some(t) = &credentials.token {
-            username_or_google_id = match google::verify_id_token_with_google_api(t).await {
-                Ok(res) => res.sub,
+    let provider = Provider::from_method(method);
+    let mut throttle_keys = vec![ThrottleKey::Ip(get_client_ip(&req))];
+    if provider.is_none() {
+        throttle_keys.push(ThrottleKey::Account(credentials.username.clone()));
+    }
+    if let Err(res) = check_throttle(LOGIN_SCOPE, &throttle_keys, &client).await {
+        return res;
+    }
+
+    let user = match provider {
+        Some(provider) => {
+            let identity = match &credentials.token {
+                Some(t) => identity_providers.get(provider).verify(t).await,
+                None => Err("Token is required".into()),
+            };
+            // A rejected token and an unknown account both get the failed login answer below
+            match identity {
+                Ok(identity) => get_user_by_provider_id(provider, &identity.subject, &client).await,
                 Err(err) => {
                     println!("{:?}", err);
-                    return HttpResponse::Unauthorized().json(BaseResponse {
-                        code: 401,
-                        message: String::from("Unauthorized!"),
-                    });
+                    None
                 }
             }
         }
-    }
+        // Fetch user from the database based on the username
+        None => get_user(&credentials.username, &client).await,
+    };
 
-    // Fetch user from the database based on the username
-    let user = if method == "google" {
-        get_user_by_google_id(&username_or_google_id, &client).await
-    } else {
-        get_user(&username_or_google_id, &client).await
+    // Unknown usernames still pay for a bcrypt check so response times do not give them away
+    let password_hash = user
+        .as_ref()
+        .map(|user| user.password.as_str())
+        .unwrap_or(DUMMY_PASSWORD_HASH);
+    let verified = provider.is_some()
+        || (verify(&credentials.password, password_hash).unwrap_or(false) && user.is_some());
+    let user = match user {
+        Some(user) if verified => user,
+        _ => {
+            record_failures(LOGIN_SCOPE, &throttle_keys, &client).await;
+            return HttpResponse::Unauthorized().json(BaseResponse {
+                code: 401,
+                message: String::from("Invalid username or password!"),
+            });
+        }
     };
-    if method == "google" && user.is_none() {
-        return HttpResponse::NotFound().json(BaseResponse {
+
+    if &user.account_status != "active" {
+        return HttpResponse::Unauthorized().json(BaseResponse {
             code: 401,
-            message: String::from("User not found!"),
+            message: String::from("Your account has not been activated yet. Please wait for an admin to approve your account or contact support for further assistance!")
         });
     }
+    if provider.is_none() {
+        clear_failures(LOGIN_SCOPE, &throttle_keys[1], &client).await;
+    }
 
-    match user {
-        Some(user) => {
-            if &user.account_status != "active" {
+    let token = match sign_user_access_token(user.user_id, &client).await {
+        Ok(token) => token,
+        Err(res) => return res,
+    };
+    let refresh_token = match refresh_token::add_refresh_token(user.user_id, &client).await {
+        Ok(refresh_token) => refresh_token,
+        Err(err) => {
+            println!("Error adding refresh token: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            });
+        }
+    };
+    HttpResponse::Ok().json(DataResponse {
+        code: 200,
+        message: String::from("Token generated successfully."),
+        data: Some(LoginData {
+            token,
+            refresh_token,
+            expires_in: get_access_token_expires_in(),
+            name: user.name,
+            profile_image: user.profile_image,
+            role: user.role,
+            can_modify_order_status: user.can_modify_order_status,
+        }),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[post("/api/auth/refresh")]
+pub async fn refresh(pool: web::Data<Pool>, body: web::Json<RefreshTokenRequest>) -> HttpResponse {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    let (user_id, refresh_token) =
+        match refresh_token::rotate_refresh_token(&body.refresh_token, &mut client).await {
+            Ok(RefreshTokenRotation::Rotated {
+                user_id,
+                refresh_token,
+            }) => (user_id, refresh_token),
+            Ok(RefreshTokenRotation::Invalid) => {
                 return HttpResponse::Unauthorized().json(BaseResponse {
                     code: 401,
-                    message: String::from("Your account has not been activated yet. Please wait for an admin to approve your account or contact support for further assistance!")
+                    message: String::from("Invalid refresh token"),
+                })
+            }
+            Err(err) => {
+                println!("Error rotating refresh token: {:?}", err);
+                return HttpResponse::InternalServerError().json(BaseResponse {
+                    code: 500,
+                    message: String::from("Something went wrong!"),
                 });
             }
+        };
 
-            if method == "google" || verify(&credentials.password, &user.password).unwrap() {
-                // let now = SystemTime::now()
-                //     .duration_since(UNIX_EPOCH)
-                //     .expect("Time went backwards")
-                //     .as_secs() as usize;
-                // let token = jwt::sign_token(&jwt::Claims {
-                //     sub: format!("{},{}", &user.user_id, &user.role),
-                //     exp: now + (3600 * 24),
-                // })
-                // .unwrap();
-
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Time went backwards")
-                    .as_secs() as usize;
-                // Setting a far future expiration time
-                let far_future_exp = now + (3600 * 24 * 365 * 100); // 100 years into the future
-                let token = jwt::sign_token(&jwt::Claims {
-                    sub: format!("{},{}", &user.user_id, &user.role),
-                    exp: far_future_exp,
-                })
-                .unwrap();
-                // let token = create_token(&user.username).unwrap();
-                HttpResponse::Ok().json(DataResponse {
-                    code: 200,
-                    message: String::from("Token generated successfully."),
-                    data: Some(LoginData {
-                        token,
-                        name: user.name,
-                        profile_image: user.profile_image,
-                        role: user.role,
-                        can_modify_order_status: user.can_modify_order_status,
-                    }),
-                })
-            } else {
-                HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Invalid password!"),
-                })
+    // Role and account status are read again so changes apply on the next refresh
+    let user = match user::get_user_by_id(user_id, &client).await {
+        Some(user) if &user.account_status == "active" => user,
+        _ => {
+            if let Err(err) = refresh_token::revoke_refresh_token(&refresh_token, &client).await {
+                println!("Error revoking refresh token: {:?}", err);
             }
+            return HttpResponse::Unauthorized().json(BaseResponse {
+                code: 401,
+                message: String::from("Invalid refresh token"),
+            });
         }
-        None => HttpResponse::Unauthorized().json(BaseResponse {
+    };
+
+    let token = match sign_user_access_token(user.user_id, &client).await {
+        Ok(token) => token,
+        Err(res) => return res,
+    };
+    HttpResponse::Ok().json(DataResponse {
+        code: 200,
+        message: String::from("Token refreshed successfully."),
+        data: Some(LoginData {
+            token,
+            refresh_token,
+            expires_in: get_access_token_expires_in(),
+            name: user.name,
+            profile_image: user.profile_image,
+            role: user.role,
+            can_modify_order_status: user.can_modify_order_status,
+        }),
+    })
+}
+
+#[post("/api/auth/logout")]
+pub async fn logout(pool: web::Data<Pool>, body: web::Json<RefreshTokenRequest>) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match refresh_token::revoke_refresh_token(&body.refresh_token, &client).await {
+        Ok(true) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Logged out successfully."),
+        }),
+        Ok(false) => HttpResponse::Unauthorized().json(BaseResponse {
             code: 401,
-            message: String::from("Invalid username!"),
+            message: String::from("Invalid refresh token"),
         }),
+        Err(err) => {
+            println!("Error revoking refresh token: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            })
+        }
     }
 }
 
@@ -410,52 +669,15 @@ pub struct ChangePasswordRequest {
 
 #[post("/api/auth/change-password")]
 pub async fn change_password(
-    req: HttpRequest,
+    auth: AuthUser,
     body: web::Json<ChangePasswordRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
+    let user_id = auth.user_id;
 
     if &body.old_password == &body.new_password {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -495,42 +717,168 @@ pub async fn change_password(
 }
 
 #[derive(Deserialize)]
-pub struct VerifyTokenRequest {
+pub struct LinkProviderRequest {
+    pub method: String,
     pub token: String,
 }
 
-#[derive(Serialize)]
-pub struct VerifyTokenData {
-    pub room: i32,
+fn unsupported_method() -> HttpResponse {
+    HttpResponse::BadRequest().json(BaseResponse {
+        code: 400,
+        message: String::from("Method must be google, apple or facebook!"),
+    })
 }
 
-#[post("/api/auth/verify-token")]
-pub async fn verify_token(body: web::Json<VerifyTokenRequest>) -> impl Responder {
-    let sub = match verify_token_and_get_sub(&body.token) {
-        Some(s) => s,
-        None => {
+fn already_linked() -> HttpResponse {
+    HttpResponse::BadRequest().json(BaseResponse {
+        code: 400,
+        message: String::from("This account is already linked to another user!"),
+    })
+}
+
+#[post("/api/auth/link-provider")]
+pub async fn link_provider(
+    auth: AuthUser,
+    body: web::Json<LinkProviderRequest>,
+    pool: web::Data<Pool>,
+    identity_providers: web::Data<IdentityProviders>,
+) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let user_id = auth.user_id;
+    let provider = match Provider::from_method(&body.method) {
+        Some(provider) => provider,
+        None => return unsupported_method(),
+    };
+
+    let identity = match identity_providers.get(provider).verify(&body.token).await {
+        Ok(identity) => identity,
+        Err(err) => {
+            println!("{:?}", err);
             return HttpResponse::Unauthorized().json(BaseResponse {
                 code: 401,
-                message: String::from("Invalid token"),
+                message: String::from("Unauthorized!"),
+            });
+        }
+    };
+    if let Some(user) = None::<user::User> {
+        if user.user_id != user_id {
+            return already_linked();
+        }
+    }
+
+    match user::set_provider_id(user_id, provider, &identity.subject, &client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Account linked successfully."),
+        }),
+        // Another user linked the same account since the check above
+        Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => already_linked(),
+        Err(err) => {
+            println!("Error linking provider: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error linking account!"),
             })
         }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UnlinkProviderRequest {
+    pub method: String,
+}
+
+#[post("/api/auth/unlink-provider")]
+pub async fn unlink_provider(
+    auth: AuthUser,
+    body: web::Json<UnlinkProviderRequest>,
+    pool: web::Data<Pool>,
+) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let provider = match Provider::from_method(&body.method) {
+        Some(provider) => provider,
+        None => return unsupported_method(),
     };
 
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
+    match user::unset_provider_id(auth.user_id, provider, &client).await {
+        Ok(true) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Account unlinked successfully."),
+        }),
+        Ok(false) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from(
+                "Set a password or link another account before unlinking your only sign-in method!",
+            ),
+        }),
+        Err(err) => {
+            println!("Error unlinking provider: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error unlinking account!"),
+            })
+        }
     }
+}
 
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    return HttpResponse::Ok().json(DataResponse {
-        code: 200,
-        message: String::from("Token is valid."),
-        data: Some(VerifyTokenData { room: user_id }),
-    });
+#[get("/api/auth/linked-providers")]
+pub async fn get_linked_providers(auth: AuthUser, pool: web::Data<Pool>) -> HttpResponse {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match user::get_linked_providers(auth.user_id, &client).await {
+        Ok(Some(linked_providers)) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Linked accounts fetched successfully."),
+            data: Some(linked_providers),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("User not found!"),
+        }),
+        Err(err) => {
+            println!("Error fetching linked providers: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct VerifyTokenRequest {
+    pub token: String,
+}
+
+#[derive(Serialize)]
+pub struct VerifyTokenData {
+    pub room: i32,
+}
+
+#[post("/api/auth/verify-token")]
+pub async fn verify_token(body: web::Json<VerifyTokenRequest>) -> impl Responder {
+    match jwt::verify_token_and_get_claims(&body.token) {
+        Some(claims) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Token is valid."),
+            data: Some(VerifyTokenData {
+                room: claims.user_id,
+            }),
+        }),
+        None => HttpResponse::Unauthorized().json(BaseResponse {
+            code: 401,
+            message: String::from("Invalid token"),
+        }),
+    }
 }
 
 #[derive(Deserialize)]
@@ -542,58 +890,162 @@ pub struct ForgotPasswordRequest {
 
 #[post("/api/auth/forgot-password")]
 pub async fn forgot_password(
+    req: HttpRequest,
     body: web::Json<ForgotPasswordRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
+    code_delivery: web::Data<dyn CodeDelivery>,
 ) -> impl Responder {
-    let user = get_user(&body.email, &client).await;
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let username = body.username.as_ref().unwrap_or(&body.email);
+    let throttle_keys = [
+        ThrottleKey::Ip(get_client_ip(&req)),
+        ThrottleKey::Account(username.clone()),
+    ];
+    if let Err(res) = check_throttle(FORGOT_PASSWORD_SCOPE, &throttle_keys, &client).await {
+        return res;
+    }
+    // Every request counts, matching ones too, since each of them sends a message
+    record_failures(FORGOT_PASSWORD_SCOPE, &throttle_keys, &client).await;
 
-    match user {
-        Some(user) => {
-            if &user.account_status != "active" {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Your account has not been activated yet. Please wait for an admin to approve your account or contact support for further assistance!")
-                });
-            }
+    // The same answer whether or not the details match, so it cannot be used to find accounts
+    let submitted = HttpResponse::Ok().json(BaseResponse {
+        code: 200,
+        message: String::from(
+            "If the details match an active account, a password reset code has been sent.",
+        ),
+    });
+    let user = match get_user(username, &client).await {
+        Some(user)
+            if user.account_status == "active"
+                && user.role != "admin"
+                && user.email == body.email
+                && user.phone == body.phone =>
+        {
+            user
+        }
+        _ => return submitted,
+    };
 
-            if &user.role == "admin" || &user.email != &body.email || &user.phone != &user.phone {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Unauthorized!"),
-                });
-            }
-            let message = format!("A password reset request was made for user {} ({}). Please verify legitimacy and monitor for any suspicious activity.",&user.name, &body.email);
-            let mut map = HashMap::new();
-            map.insert(
-                "redirect".to_string(),
-                Value::String("user-detail".to_string()),
-            );
-            map.insert("id".to_string(), Value::Number(user.user_id.into()));
-            match notification::add_notification_to_admins(
-                "Password Reset Alert",
-                &message,
-                &Some(map),
-                &client,
+    let code = match password_reset::add_reset_code(user.user_id, &client).await {
+        Ok(code) => code,
+        Err(err) => {
+            // The generic answer too, a 500 here would only ever show for real accounts
+            println!("Error adding password reset code: {:?}", err);
+            return submitted;
+        }
+    };
+    let (channel, destination) = if user.phone.is_empty() {
+        (DeliveryChannel::Email, &user.email)
+    } else {
+        (DeliveryChannel::Sms, &user.phone)
+    };
+    let message = format!(
+        "Your Watch Wonder password reset code is {}. It expires in {} minutes.",
+        code,
+        get_password_reset_code_expires_in_minutes()
+    );
+    if let Err(err) = code_delivery
+        .deliver(&channel, destination, "Password Reset Code", &message)
+        .await
+    {
+        // Still the generic answer, an error here would only ever show for real accounts
+        println!("Error sending password reset code: {:?}", err);
+        return submitted;
+    }
+
+    let message = format!("A password reset request was made for user {} ({}). Please verify legitimacy and monitor for any suspicious activity.",&user.name, &body.email);
+    let mut map = HashMap::new();
+    map.insert(
+        "redirect".to_string(),
+        Value::String("user-detail".to_string()),
+    );
+    map.insert("id".to_string(), Value::Number(user.user_id.into()));
+    match notification::add_notification_to_admins(
+        "Password Reset Alert",
+        &message,
+        &Some(map),
+        &client,
+    )
+    .await
+    {
+        Ok(()) => {
+            println!("Notification added successfully.");
+        }
+        Err(err) => {
+            println!("Error adding notification: {:?}", err);
+        }
+    };
+    submitted
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub username: Option<String>,
+    pub email: String,
+    pub code: String,
+    pub new_password: String,
+}
+
+#[post("/api/auth/reset-password")]
+pub async fn reset_password(
+    req: HttpRequest,
+    body: web::Json<ResetPasswordRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let username = body.username.as_ref().unwrap_or(&body.email);
+    let throttle_keys = [
+        ThrottleKey::Ip(get_client_ip(&req)),
+        ThrottleKey::Account(username.clone()),
+    ];
+    if let Err(res) = check_throttle(RESET_PASSWORD_SCOPE, &throttle_keys, &client).await {
+        return res;
+    }
+
+    let user = match get_user(username, &client).await {
+        Some(user) if user.email == body.email && user.account_status == "active" => Some(user),
+        _ => None,
+    };
+    let result = match &user {
+        Some(user) => {
+            password_reset::reset_password(
+                user.user_id,
+                &body.code,
+                &body.new_password,
+                &mut client,
             )
             .await
-            {
-                Ok(()) => {
-                    println!("Notification added successfully.");
-                }
-                Err(err) => {
-                    println!("Error adding notification: {:?}", err);
-                }
-            };
+        }
+        None => Ok(PasswordReset::InvalidCode),
+    };
+
+    match result {
+        Ok(PasswordReset::Reset) => {
+            clear_failures(RESET_PASSWORD_SCOPE, &throttle_keys[1], &client).await;
             HttpResponse::Ok().json(BaseResponse {
                 code: 200,
-                message: String::from(
-                    "Your request to reset your password has been successfully submitted.",
-                ),
+                message: String::from("Password reset successfully."),
+            })
+        }
+        Ok(PasswordReset::InvalidCode) => {
+            record_failures(RESET_PASSWORD_SCOPE, &throttle_keys, &client).await;
+            HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Invalid or expired reset code!"),
+            })
+        }
+        Err(err) => {
+            println!("Error resetting password: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error resetting password!"),
             })
         }
-        None => HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid username!"),
-        }),
     }
 }
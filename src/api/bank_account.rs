@@ -1,14 +1,13 @@
-use std::sync::Arc;
-
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::bank_account::{self, BankAccountRequest},
     utils::{
+        auth::{AuthUser, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
     },
 };
 
@@ -23,48 +22,17 @@ pub struct GetBanksQuery {
 
 #[get("/api/bank-accounts")]
 pub async fn get_bank_accounts(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetBanksQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
+    let user_id = auth.user_id();
+    let role = auth.role();
 
-    let mut role = "user".to_string();
-    let mut user_id: i32 = 0;
-    if !token.is_empty() {
-        let sub = match verify_token_and_get_sub(&token) {
-            Some(s) => s,
-            None => {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Invalid token"),
-                })
-            }
-        };
-
-        // Parse the `sub` value
-        let parsed_values: Vec<String> = sub.split(',').map(|s| s.to_string()).collect();
-        if parsed_values.len() != 2 {
-            return HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Invalid sub format in token"),
-            });
-        }
-
-        user_id = parsed_values[0].parse().unwrap();
-        role = parsed_values[1].clone();
-    }
     match bank_account::get_bank_accounts(
         &query.search,
         query.page,
@@ -99,53 +67,14 @@ pub async fn get_bank_accounts(
 
 #[post("/api/bank-accounts")]
 pub async fn add_bank_account(
-    req: HttpRequest,
+    _auth: AuthUser,
     body: web::Json<BankAccountRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let user_id = parsed_values[0].parse().unwrap();
-    // let role: &str = parsed_values[1];
-
     // if role == "admin" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
     //         code: 401,
@@ -191,53 +120,15 @@ pub async fn add_bank_account(
 
 #[get("/api/bank-accounts/{account_id}")]
 pub async fn get_bank_account_by_id(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let account_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let role: &str = parsed_values[1];
-
+    let account_id = path.into_inner();
     // if role != "admin" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
     //         code: 401,
@@ -260,54 +151,16 @@ pub async fn get_bank_account_by_id(
 
 #[put("/api/bank-accounts/{account_id}")]
 pub async fn update_bank_account(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
     body: web::Json<BankAccountRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let account_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let role: &str = parsed_values[1];
-
+    let account_id = path.into_inner();
     // if role != "admin" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
     //         code: 401,
@@ -362,53 +215,15 @@ pub async fn update_bank_account(
 
 #[delete("/api/bank-accounts/{account_id}")]
 pub async fn delete_bank_account(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let account_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let role: &str = parsed_values[1];
-
+    let account_id = path.into_inner();
     // if role != "admin" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
     //         code: 401,
@@ -1,8 +1,6 @@
-use std::sync::Arc;
-
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
@@ -10,8 +8,10 @@ use crate::{
         product,
     },
     utils::{
+        auth::{Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::{CatalogManage, CatalogView},
     },
 };
 
@@ -35,46 +35,16 @@ pub struct GetBrandsQuery {
 
 #[get("/api/brands")]
 pub async fn get_brands(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetBrandsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
+    let user_id = auth.user_id();
 
-    // let mut role = "user".to_string();
-    let mut user_id = 0;
-    if !token.is_empty() {
-        let sub = match verify_token_and_get_sub(&token) {
-            Some(s) => s,
-            None => {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Invalid token"),
-                })
-            }
-        };
-        // Parse the `sub` value
-        let parsed_values: Vec<String> = sub.split(',').map(|s| s.to_string()).collect();
-        if parsed_values.len() != 2 {
-            return HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Invalid sub format in token"),
-            });
-        }
-        user_id = parsed_values[0].parse().unwrap();
-        // role = parsed_values[1].clone();
-    }
     let platform = match &query.platform {
         Some(p) => p.as_str(),
         None => "",
@@ -117,58 +87,15 @@ pub async fn get_brands(
 
 #[post("/api/brands")]
 pub async fn add_brand(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: Authorized<CatalogManage>,
+    pool: web::Data<Pool>,
     body: web::Json<BrandRequest>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let user_id = auth.user_id;
 
     if body.name.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -176,15 +103,7 @@ pub async fn add_brand(
             message: String::from("Brand name cannot be empty!"),
         });
     }
-    let level = if let Some(l) = body.level {
-        if role == "admin" {
-            l
-        } else {
-            0
-        }
-    } else {
-        0
-    };
+    let level = body.level.unwrap_or(0);
     match brand::add_brand(
         &body.name,
         &body.description,
@@ -211,59 +130,15 @@ pub async fn add_brand(
 
 #[get("/api/brands/{brand_id}")]
 pub async fn get_brand_by_id(
-    req: HttpRequest,
+    _auth: Authorized<CatalogView>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let brand_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let brand_id = path.into_inner();
 
     match brand::get_brand_by_id(brand_id, &client).await {
         Some(b) => HttpResponse::Ok().json(DataResponse {
@@ -280,59 +155,16 @@ pub async fn get_brand_by_id(
 
 #[put("/api/brands/{brand_id}")]
 pub async fn update_brand(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    _auth: Authorized<CatalogManage>,
+    pool: web::Data<Pool>,
     path: web::Path<i32>,
     body: web::Json<BrandRequest>,
 ) -> HttpResponse {
-    let brand_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let brand_id = path.into_inner();
 
     if body.name.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -342,15 +174,7 @@ pub async fn update_brand(
     }
     match brand::get_brand_by_id(brand_id, &client).await {
         Some(b) => {
-            let level = if let Some(l) = body.level {
-                if role == "admin" {
-                    l
-                } else {
-                    b.level
-                }
-            } else {
-                b.level
-            };
+            let level = body.level.unwrap_or(b.level);
             match brand::update_brand(
                 brand_id,
                 &body.name,
@@ -384,59 +208,15 @@ pub async fn update_brand(
 
 #[delete("/api/brands/{brand_id}")]
 pub async fn delete_brand(
-    req: HttpRequest,
+    _auth: Authorized<CatalogManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let brand_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let brand_id = path.into_inner();
 
     match product::is_products_exist("brand_id", brand_id, &client).await {
         Ok(is_exist) => {
@@ -1,14 +1,14 @@
-use std::sync::Arc;
-
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::buyer_protection::{self, BuyerProtectionRequest},
     utils::{
+        auth::{Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::ContentManage,
     },
 };
 
@@ -21,32 +21,14 @@ pub struct GetBuyerProtectionsQuery {
 
 #[get("/api/buyer-protections")]
 pub async fn get_buyer_protections(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    _auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetBuyerProtectionsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if !token.is_empty() {
-        if verify_token_and_get_sub(&token).is_none() {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            });
-        }
-    }
-
     match buyer_protection::get_buyer_protections(
         &query.search,
         query.page,
@@ -77,60 +59,15 @@ pub async fn get_buyer_protections(
 
 #[post("/api/buyer-protections")]
 pub async fn add_buyer_protection(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     body: web::Json<BuyerProtectionRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let user_id: i32 = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
     if body.description.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
             code: 400,
@@ -155,59 +92,15 @@ pub async fn add_buyer_protection(
 
 #[get("/api/buyer-protections/{buyer_protection_id}")]
 pub async fn get_buyer_protection_by_id(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let buyer_protection_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let buyer_protection_id = path.into_inner();
 
     match buyer_protection::get_buyer_protection_by_id(buyer_protection_id, &client).await {
         Some(s) => HttpResponse::Ok().json(DataResponse {
@@ -224,60 +117,16 @@ pub async fn get_buyer_protection_by_id(
 
 #[put("/api/buyer-protections/{buyer_protection_id}")]
 pub async fn update_buyer_protection(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
     body: web::Json<BuyerProtectionRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let buyer_protection_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let buyer_protection_id = path.into_inner();
 
     if body.description.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -313,59 +162,15 @@ pub async fn update_buyer_protection(
 
 #[delete("/api/buyer-protections/{buyer_protection_id}")]
 pub async fn delete_buyer_protection(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let buyer_protection_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let buyer_protection_id = path.into_inner();
 
     match buyer_protection::get_buyer_protection_by_id(buyer_protection_id, &client).await {
         Some(_) => {
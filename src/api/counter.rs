@@ -1,15 +1,20 @@
-use std::sync::Arc;
-
 use actix_web::{get, web, HttpResponse, Responder};
-use tokio_postgres::Client;
+use deadpool_postgres::Pool;
 
 use crate::{
     models::counter,
-    utils::common_struct::{BaseResponse, DataResponse},
+    utils::{
+        common_struct::{BaseResponse, DataResponse},
+        db::get_client,
+    },
 };
 
 #[get("/api/counters/generate-invoice-id")]
-pub async fn generate_invoice_id(client: web::Data<Arc<Client>>) -> impl Responder {
+pub async fn generate_invoice_id(pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     match counter::generate_invoice_id(&client).await {
         Ok(invoice_id) => HttpResponse::Ok().json(DataResponse {
             code: 200,
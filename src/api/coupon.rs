@@ -0,0 +1,380 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::{GenericClient, Pool};
+use serde::Deserialize;
+
+use crate::{
+    models::{
+        coupon::{self, Coupon, CouponCheck, CouponRequest},
+        order::NewOrderItem,
+        pricing::{DISCOUNT_BY_AMOUNT, DISCOUNT_BY_PERCENTAGE},
+        product, shop,
+    },
+    utils::{
+        auth::{forbidden, AuthUser, Authorized},
+        common_struct::{BaseResponse, DataResponse, PaginationResponse},
+        db::get_client,
+        permission::{DiscountsManage, SHOPS_MANAGE_ANY},
+    },
+};
+
+// Sellers may only give out coupons for their own shops, staff who manage every shop
+// may also leave the shop out for a coupon that works everywhere
+async fn validate_coupon_request(
+    body: &CouponRequest,
+    coupon_id: Option<i32>,
+    auth: &AuthUser,
+    client: &impl GenericClient,
+) -> Result<(), HttpResponse> {
+    if body.code.trim().is_empty() {
+        return Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Coupon code must not be empty!"),
+        }));
+    }
+    match body.discount_type.as_str() {
+        DISCOUNT_BY_PERCENTAGE => {
+            let percent = body.discount_percent.unwrap_or(0.0);
+            if percent <= 0.0 || percent > 100.0 {
+                return Err(HttpResponse::BadRequest().json(BaseResponse { code: 400, message: String::from("Discount percent must be more than 0 and at most 100!") }));
+            }
+        }
+        DISCOUNT_BY_AMOUNT => {
+            if body.discount_amount.unwrap_or(0.0) <= 0.0 {
+                return Err(HttpResponse::BadRequest().json(BaseResponse { code: 400, message: String::from("Discount amount must be more than 0!") }));
+            }
+        }
+        _ => {
+            return Err(HttpResponse::BadRequest().json(BaseResponse { code: 400, message: String::from("Please select a valid discount type: Discount by Specific Percentage or Discount by Specific Amount.") }))
+        }
+    }
+    if body.min_order_amount.unwrap_or(0.0) < 0.0 {
+        return Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Minimum order amount must not be negative!"),
+        }));
+    }
+    if body.max_redemptions.is_some_and(|max| max < 1)
+        || body.max_redemptions_per_user.is_some_and(|max| max < 1)
+    {
+        return Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Redemption limits must be at least 1!"),
+        }));
+    }
+    if let (Some(starts_at), Some(ends_at)) = (body.starts_at, body.ends_at) {
+        if starts_at >= ends_at {
+            return Err(HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Coupon must end after it starts!"),
+            }));
+        }
+    }
+
+    match body.shop_id {
+        Some(shop_id) => {
+            if !auth.has_permission(SHOPS_MANAGE_ANY)
+                && shop::get_creator_id_from_shop(shop_id, client).await != Some(auth.user_id)
+            {
+                return Err(HttpResponse::BadRequest().json(BaseResponse {
+                    code: 400,
+                    message: String::from("Shop not found!"),
+                }));
+            }
+            if let Some(product_id) = body.product_id {
+                match product::is_product_in_shop(product_id, shop_id, client).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return Err(HttpResponse::BadRequest().json(BaseResponse {
+                            code: 400,
+                            message: String::from("Product not found in this shop!"),
+                        }))
+                    }
+                    Err(err) => {
+                        println!("Error checking coupon product: {:?}", err);
+                        return Err(HttpResponse::InternalServerError().json(BaseResponse {
+                            code: 500,
+                            message: String::from("Something went wrong!"),
+                        }));
+                    }
+                }
+            }
+        }
+        None => {
+            if !auth.has_permission(SHOPS_MANAGE_ANY) {
+                return Err(HttpResponse::BadRequest().json(BaseResponse {
+                    code: 400,
+                    message: String::from("Please select a shop!"),
+                }));
+            }
+            if body.product_id.is_some() {
+                return Err(HttpResponse::BadRequest().json(BaseResponse {
+                    code: 400,
+                    message: String::from("Please select the shop of the product!"),
+                }));
+            }
+        }
+    }
+
+    match coupon::is_code_taken(&body.code, coupon_id, client).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Coupon code is already in use!"),
+        })),
+        Err(err) => {
+            println!("Error checking coupon code: {:?}", err);
+            Err(HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            }))
+        }
+    }
+}
+
+// Coupons are managed by whoever created them, or by staff who manage every shop
+async fn get_own_coupon(
+    coupon_id: i32,
+    auth: &AuthUser,
+    client: &impl GenericClient,
+) -> Result<Coupon, HttpResponse> {
+    match coupon::get_coupon_by_id(coupon_id, client).await {
+        Ok(Some(c)) => {
+            if !auth.has_permission(SHOPS_MANAGE_ANY) && c.creator_id != Some(auth.user_id) {
+                return Err(forbidden());
+            }
+            Ok(c)
+        }
+        Ok(None) => Err(HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Coupon not found!"),
+        })),
+        Err(err) => {
+            println!("Error fetching coupon: {:?}", err);
+            Err(HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching coupon!"),
+            }))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GetCouponsQuery {
+    pub search: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    pub shop_id: Option<i32>,
+}
+
+#[get("/api/coupons")]
+pub async fn get_coupons(
+    auth: Authorized<DiscountsManage>,
+    query: web::Query<GetCouponsQuery>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match coupon::get_coupons(
+        &query.search,
+        query.page,
+        query.per_page,
+        query.shop_id,
+        auth.user_id,
+        auth.has_permission(SHOPS_MANAGE_ANY),
+        &client,
+    )
+    .await
+    {
+        Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: item_result.data,
+            total: item_result.total,
+            page: item_result.page,
+            per_page: item_result.per_page,
+            page_counts: item_result.page_counts,
+        }),
+        Err(err) => {
+            println!("Error retrieving coupons: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read all coupons from database"),
+            })
+        }
+    }
+}
+
+#[post("/api/coupons")]
+pub async fn add_coupon(
+    auth: Authorized<DiscountsManage>,
+    body: web::Json<CouponRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    if let Err(res) = validate_coupon_request(&body, None, &auth, &client).await {
+        return res;
+    }
+
+    match coupon::add_coupon(&body, auth.user_id, &client).await {
+        Ok(coupon_id) => HttpResponse::Created().json(DataResponse {
+            code: 201,
+            message: String::from("Coupon added successfully."),
+            data: Some(coupon_id),
+        }),
+        Err(err) => {
+            println!("Error adding coupon: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error adding coupon!"),
+            })
+        }
+    }
+}
+
+#[get("/api/coupons/{coupon_id}")]
+pub async fn get_coupon_by_id(
+    auth: Authorized<DiscountsManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match get_own_coupon(path.into_inner(), &auth, &client).await {
+        Ok(c) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Coupon fetched successfully."),
+            data: Some(c),
+        }),
+        Err(res) => res,
+    }
+}
+
+#[put("/api/coupons/{coupon_id}")]
+pub async fn update_coupon(
+    auth: Authorized<DiscountsManage>,
+    path: web::Path<i32>,
+    body: web::Json<CouponRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let coupon_id = path.into_inner();
+
+    if let Err(res) = get_own_coupon(coupon_id, &auth, &client).await {
+        return res;
+    }
+    if let Err(res) = validate_coupon_request(&body, Some(coupon_id), &auth, &client).await {
+        return res;
+    }
+
+    match coupon::update_coupon(coupon_id, &body, &client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Coupon updated successfully."),
+        }),
+        Err(err) => {
+            println!("Error updating coupon: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error updating coupon!"),
+            })
+        }
+    }
+}
+
+#[delete("/api/coupons/{coupon_id}")]
+pub async fn delete_coupon(
+    auth: Authorized<DiscountsManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let coupon_id = path.into_inner();
+
+    if let Err(res) = get_own_coupon(coupon_id, &auth, &client).await {
+        return res;
+    }
+
+    match coupon::delete_coupon(coupon_id, &client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 204,
+            message: String::from("Coupon deleted successfully"),
+        }),
+        Err(err) => {
+            println!("Error deleting coupon: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error deleting coupon!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ValidateCouponRequest {
+    pub coupon_code: String,
+    pub order_items: Vec<NewOrderItem>,
+}
+
+// Checks a coupon against a cart without redeeming it, the order is only charged
+// what this returns if the coupon is still valid when it is placed
+#[post("/api/coupons/validate")]
+pub async fn validate_coupon(
+    auth: AuthUser,
+    body: web::Json<ValidateCouponRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    if body.order_items.is_empty() {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Order items must not be empty!"),
+        });
+    }
+
+    let lines = match coupon::get_coupon_lines(&body.order_items, auth.user_id, &client).await {
+        Ok(lines) => lines,
+        Err(err) => {
+            println!("Error pricing coupon items: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error validating coupon!"),
+            });
+        }
+    };
+    match coupon::check_coupon(&body.coupon_code, auth.user_id, &lines, false, &client).await {
+        Ok(CouponCheck::Valid(discount)) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Coupon is valid."),
+            data: Some(discount),
+        }),
+        Ok(CouponCheck::Invalid(message)) => {
+            HttpResponse::BadRequest().json(BaseResponse { code: 400, message })
+        }
+        Err(err) => {
+            println!("Error validating coupon: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error validating coupon!"),
+            })
+        }
+    }
+}
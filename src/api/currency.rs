@@ -1,46 +1,21 @@
-use std::sync::Arc;
-
-use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
-use tokio_postgres::Client;
+use actix_web::{get, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 
 use crate::{
     models::currency,
     utils::{
+        auth::AuthUser,
         common_struct::{BaseResponse, DataResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
     },
 };
 
 #[get("/api/currencies")]
-pub async fn get_currencies(req: HttpRequest, client: web::Data<Arc<Client>>) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+pub async fn get_currencies(_auth: AuthUser, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if verify_token_and_get_sub(token).is_none() {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid token"),
-        });
-    }
-
     match currency::get_currencies(&client).await {
         Ok(currencies) => HttpResponse::Ok().json(DataResponse {
             code: 200,
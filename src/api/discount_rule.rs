@@ -1,14 +1,14 @@
-use std::sync::Arc;
-
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::discount_rule::{self, DiscountRuleRequest},
     utils::{
+        auth::{AuthUser, Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::DiscountsManage,
     },
 };
 
@@ -22,59 +22,16 @@ pub struct GetDiscountRulesQuery {
 
 #[get("/api/discount-rules")]
 pub async fn get_discount_rules(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: Authorized<DiscountsManage>,
+    pool: web::Data<Pool>,
     query: web::Query<GetDiscountRulesQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     match discount_rule::get_discount_rules(
         &query.search,
@@ -109,59 +66,15 @@ pub async fn get_discount_rules(
 
 #[post("/api/discount-rules")]
 pub async fn add_discount_rule(
-    req: HttpRequest,
+    auth: Authorized<DiscountsManage>,
     body: web::Json<DiscountRuleRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let user_id = auth.user_id;
 
     let discount_fors: Vec<&str> = vec!["all", "product", "brand", "category"];
     if !discount_fors.contains(&body.discount_for.as_str()) {
@@ -172,6 +85,14 @@ pub async fn add_discount_rule(
             ),
         });
     }
+    if let (Some(starts_at), Some(ends_at)) = (body.starts_at, body.ends_at) {
+        if starts_at >= ends_at {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Sale must end after it starts!"),
+            });
+        }
+    }
 
     match discount_rule::add_discount_rule(&body, user_id, &client).await {
         Ok(()) => HttpResponse::Created().json(BaseResponse {
@@ -190,59 +111,15 @@ pub async fn add_discount_rule(
 
 #[get("/api/discount-rules/{rule_id}")]
 pub async fn get_discount_rule_by_id(
-    req: HttpRequest,
+    _auth: Authorized<DiscountsManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let rule_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let rule_id = path.into_inner();
 
     match discount_rule::get_discount_rule_by_id(rule_id, &client).await {
         Some(dr) => HttpResponse::Ok().json(DataResponse {
@@ -259,60 +136,16 @@ pub async fn get_discount_rule_by_id(
 
 #[put("/api/discount-rules/{rule_id}")]
 pub async fn update_discount_rule(
-    req: HttpRequest,
+    _auth: Authorized<DiscountsManage>,
     path: web::Path<i32>,
     body: web::Json<DiscountRuleRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let rule_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let rule_id = path.into_inner();
 
     let discount_fors: Vec<&str> = vec!["all", "product", "brand", "category"];
     if !discount_fors.contains(&body.discount_for.as_str()) {
@@ -323,6 +156,14 @@ pub async fn update_discount_rule(
             ),
         });
     }
+    if let (Some(starts_at), Some(ends_at)) = (body.starts_at, body.ends_at) {
+        if starts_at >= ends_at {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Sale must end after it starts!"),
+            });
+        }
+    }
 
     match discount_rule::get_discount_rule_by_id(rule_id, &client).await {
         Some(_) => match discount_rule::update_discount_rule(rule_id, &body, &client).await {
@@ -347,62 +188,18 @@ pub async fn update_discount_rule(
 
 #[delete("/api/discount-rules/{rule_id}")]
 pub async fn delete_discount_rule(
-    req: HttpRequest,
+    _auth: Authorized<DiscountsManage>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let rule_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let rule_id = path.into_inner();
 
     match discount_rule::get_discount_rule_by_id(rule_id, &client).await {
-        Some(dr) => match discount_rule::delete_discount_rule(rule_id, &dr, &client).await {
+        Some(_) => match discount_rule::delete_discount_rule(rule_id, &client).await {
             Ok(()) => HttpResponse::Ok().json(BaseResponse {
                 code: 204,
                 message: String::from("Discount rule deleted successfully"),
@@ -423,56 +220,7 @@ pub async fn delete_discount_rule(
 }
 
 #[get("/api/discount-fors")]
-pub async fn get_discount_fors(req: HttpRequest) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role == "user" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
+pub async fn get_discount_fors(_auth: Authorized<DiscountsManage>) -> impl Responder {
     let discount_fors = vec!["all", "brand", "category"];
     HttpResponse::Ok().json(DataResponse {
         code: 200,
@@ -488,52 +236,15 @@ pub struct UsedCouponRequest {
 
 #[post("/api/used-coupons")]
 pub async fn add_used_coupon(
-    req: HttpRequest,
+    auth: AuthUser,
     body: web::Json<UsedCouponRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id = parsed_values[0].parse().unwrap();
-    // let role: &str = parsed_values[1];
+    let user_id = auth.user_id;
 
     // if role == "user" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
@@ -549,6 +260,23 @@ pub async fn add_used_coupon(
         });
     }
 
+    match discount_rule::has_used_coupon(&body.coupon_code, user_id, &client).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("You have already applied this coupon!"),
+            });
+        }
+        Err(e) => {
+            eprintln!("Coupon code checking error: {}", e);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error applying coupon code!"),
+            });
+        }
+    }
+
     match discount_rule::add_used_coupon(&body.coupon_code, user_id, &client).await {
         Ok(()) => HttpResponse::Created().json(BaseResponse {
             code: 201,
@@ -566,53 +294,15 @@ pub async fn add_used_coupon(
 
 #[delete("/api/used-coupons/{used_coupon_id}")]
 pub async fn delete_used_coupon(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let used_coupon_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let role: &str = parsed_values[1];
-
+    let used_coupon_id = path.into_inner();
     // if role == "user" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
     //         code: 401,
@@ -650,52 +340,16 @@ pub struct GetUsedCouponsQuery {
 
 #[get("/api/used-coupons")]
 pub async fn get_used_coupons(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: AuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetUsedCouponsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     // if role == "user" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
@@ -733,3 +387,43 @@ pub async fn get_used_coupons(
         }
     }
 }
+
+#[derive(Deserialize)]
+pub struct GetUpcomingSalesQuery {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    pub shop_id: Option<i32>,
+}
+
+#[get("/api/flash-sales/upcoming")]
+pub async fn get_upcoming_sales(
+    _auth: OptionalAuthUser,
+    query: web::Query<GetUpcomingSalesQuery>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match discount_rule::get_upcoming_sales(query.page, query.per_page, query.shop_id, &client)
+        .await
+    {
+        Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: item_result.data,
+            total: item_result.total,
+            page: item_result.page,
+            per_page: item_result.per_page,
+            page_counts: item_result.page_counts,
+        }),
+        Err(err) => {
+            println!("Error retrieving upcoming sales: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read upcoming sales from database"),
+            })
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
+
+use crate::{
+    models::favorite,
+    utils::{
+        auth::AuthUser,
+        common_struct::{BaseResponse, DataResponse},
+        db::get_client,
+    },
+};
+
+#[get("/api/favorites")]
+pub async fn get_favorites(auth: AuthUser, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match favorite::get_favorite_product_ids(auth.user_id, &client).await {
+        Ok(product_ids) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Favorites fetched successfully."),
+            data: Some(product_ids),
+        }),
+        Err(err) => {
+            println!("Error retrieving favorites: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read favorites from database"),
+            })
+        }
+    }
+}
+
+#[post("/api/favorites/{product_id}")]
+pub async fn add_favorite(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match favorite::add_favorite(auth.user_id, path.into_inner(), &client).await {
+        Ok(true) => HttpResponse::Created().json(BaseResponse {
+            code: 201,
+            message: String::from("Product added to favorites."),
+        }),
+        Ok(false) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Product not found!"),
+        }),
+        Err(err) => {
+            println!("Error adding favorite: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error adding favorite!"),
+            })
+        }
+    }
+}
+
+#[delete("/api/favorites/{product_id}")]
+pub async fn delete_favorite(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match favorite::delete_favorite(auth.user_id, path.into_inner(), &client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 204,
+            message: String::from("Product removed from favorites."),
+        }),
+        Err(err) => {
+            println!("Error deleting favorite: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error deleting favorite!"),
+            })
+        }
+    }
+}
@@ -1,65 +1,32 @@
-use std::sync::Arc;
-
-use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
         fcm::{self, Fcm},
         notification,
     },
-    utils::{common_struct::BaseResponse, fcm::send_notification, jwt::verify_token_and_get_sub},
+    utils::{
+        auth::{AuthUser, Authorized},
+        common_struct::BaseResponse,
+        db::get_client,
+        fcm::send_notification,
+        permission::NotificationsBroadcast,
+    },
 };
 
 #[post("/api/fcm/token")]
 pub async fn add_fcm(
-    req: HttpRequest,
+    auth: AuthUser,
     body: web::Json<Fcm>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
+    let user_id = auth.user_id;
 
     let device_types: Vec<&str> = vec!["android", "ios", "web"];
     if !device_types.contains(&body.device_type.as_str()) {
@@ -92,59 +59,15 @@ pub struct NotifyAllRequest {
 
 #[post("/api/fcm/notify-all")]
 pub async fn notify_all(
-    req: HttpRequest,
+    _auth: Authorized<NotificationsBroadcast>,
     body: web::Json<NotifyAllRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
     match send_notification(&body.title, &body.message, "/topics/all", None).await {
         Ok(_) => {
             println!("notification message sent successfully.");
@@ -3,7 +3,7 @@ use serde::Deserialize;
 
 use crate::utils::{
     common_struct::{BaseResponse, DataResponse},
-    google,
+    identity_provider::{IdentityProviders, Provider},
 };
 
 #[derive(Deserialize)]
@@ -12,8 +12,15 @@ pub struct VerifyGoogleTokenRequest {
 }
 
 #[post("/api/verify-google-token")]
-pub async fn verify_google_token(body: web::Json<VerifyGoogleTokenRequest>) -> impl Responder {
-    match google::verify_id_token_with_google_api_original(&body.id_token).await {
+pub async fn verify_google_token(
+    body: web::Json<VerifyGoogleTokenRequest>,
+    identity_providers: web::Data<IdentityProviders>,
+) -> impl Responder {
+    match identity_providers
+        .get(Provider::Google)
+        .verify(&body.id_token)
+        .await
+    {
         Ok(res) => HttpResponse::Ok().json(DataResponse {
             code: 200,
             message: String::from("Successful"),
@@ -21,9 +28,9 @@ pub async fn verify_google_token(body: web::Json<VerifyGoogleTokenRequest>) -> i
         }),
         Err(e) => {
             println!("{:?}", e);
-            HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Internal Server Error"),
+            HttpResponse::Unauthorized().json(BaseResponse {
+                code: 401,
+                message: String::from("Unauthorized!"),
             })
         }
     }
@@ -1,19 +1,18 @@
 use crate::{
     models::{bank_account, brand, category, product, shop, user},
-    utils::{common_struct::BaseResponse, image::get_image_format_from_path},
+    utils::{common_struct::BaseResponse, db::get_client, image::get_image_format_from_path},
 };
 use actix_multipart::Multipart;
 use actix_web::{get, post, web, HttpResponse, Responder, Result};
+use deadpool_postgres::Pool;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::{
     fs::{self, read_dir, remove_file},
     io::Write,
     path::{Path, PathBuf},
-    sync::Arc,
     vec,
 };
-use tokio_postgres::Client;
 use uuid::Uuid;
 
 #[derive(Serialize)]
@@ -194,7 +193,11 @@ pub async fn resize_image(
 }
 
 #[get("/api/remove-dangling-images")]
-pub async fn remove_dangling_images(client: web::Data<Arc<Client>>) -> impl Responder {
+pub async fn remove_dangling_images(pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let mut images: Vec<PathBuf> = vec![PathBuf::from("./images/policy.html")];
     let user_profile_images = user::get_profile_images(&client).await;
     for profile_image in user_profile_images {
@@ -1,22 +1,28 @@
-use std::{collections::HashMap, sync::Arc};
+use std::collections::HashMap;
 
-use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, put, web, HttpResponse, Responder};
 use chrono::NaiveDate;
+use deadpool_postgres::Pool;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
         invoice, notification,
         order::{
-            self, are_items_from_single_shop, are_items_same_currency_and_get_currency_id, NewOrder,
+            self, are_items_from_single_shop, are_items_same_currency_and_get_currency_id,
+            has_items_in_active_auction, NewOrder, OrderPlacement, OrderStatusUpdate,
         },
         product, refund_reason, seller_review, user,
     },
     utils::{
+        auth::{forbidden, AuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::{
+            ORDERS_MANAGE_OWN, ORDERS_UPDATE_STATUS, ORDERS_VIEW_ANY, USERS_VIEW_ADDRESS,
+            USERS_VIEW_PHONE,
+        },
     },
 };
 
@@ -30,52 +36,15 @@ pub struct AddOrderResponse<T> {
 
 #[post("/api/orders")]
 pub async fn add_order(
-    req: HttpRequest,
+    auth: AuthUser,
     order: web::Json<NewOrder>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
+    let user_id = auth.user_id;
 
     if order.order_items.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -91,6 +60,13 @@ pub async fn add_order(
         });
     }
 
+    if has_items_in_active_auction(&order.order_items, &client).await {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("This item is currently up for auction. Please place a bid or use Buy It Now instead!"),
+        });
+    }
+
     let currency_id =
         match are_items_same_currency_and_get_currency_id(&order.order_items, &client).await {
             Some(cur_id) => cur_id,
@@ -121,29 +97,22 @@ pub async fn add_order(
         });
     }
 
-    match order::update_stocks(&order.order_items, &client).await {
-        Ok(success) => {
-            if !success {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Insufficient stock!"),
-                });
-            }
-        }
-        Err(err) => {
-            println!("{:?}", err);
-            return HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Error updating stocks!"),
-            });
+    match order::place_order(&order, user_id, currency_id, &mut client).await {
+        Ok(OrderPlacement::InsufficientStock) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Insufficient stock!"),
+        }),
+        Ok(OrderPlacement::CouponRejected(message)) => {
+            HttpResponse::BadRequest().json(BaseResponse { code: 400, message })
         }
-    };
-
-    match order::add_order(&order, user_id, currency_id, &client).await {
-        Ok(order_id) => {
-            let clone_client = client.clone();
+        Ok(OrderPlacement::Placed(order_id)) => {
+            let invoice_pool = pool.clone();
             tokio::spawn(async move {
-                match invoice::export_invoice(order_id, user_id, &clone_client).await {
+                let client = match get_client(&invoice_pool).await {
+                    Ok(client) => client,
+                    Err(_) => return,
+                };
+                match invoice::export_invoice(order_id, user_id, &client).await {
                     Ok(_) => {
                         println!("Invoice exported successfully.")
                     }
@@ -262,53 +231,16 @@ pub struct GetOrdersQuery {
 
 #[get("/api/orders")]
 pub async fn get_orders(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: AuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetOrdersQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
-    let role: &str = parsed_values[1];
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     match order::get_orders(
         &query.search,
@@ -322,6 +254,8 @@ pub async fn get_orders(
         &query.status,
         user_id,
         role,
+        auth.has_permission(USERS_VIEW_PHONE),
+        auth.has_permission(USERS_VIEW_ADDRESS),
         &client,
     )
     .await
@@ -358,53 +292,16 @@ pub struct GetOrderItemsQuery {
 
 #[get("/api/order-items")]
 pub async fn get_order_items(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: AuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetOrderItemsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
-    let role: &str = parsed_values[1];
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     match order::get_order_items(
         &query.search,
@@ -442,59 +339,23 @@ pub async fn get_order_items(
 #[derive(Deserialize)]
 pub struct UpdateOrderRequest {
     pub status: String,
+    #[serde(default)]
+    pub note: String,
 }
 
 #[put("/api/orders/{order_id}")]
 pub async fn update_order(
-    req: HttpRequest,
+    auth: AuthUser,
     path: web::Path<i32>,
     body: web::Json<UpdateOrderRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let order_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-    let clone_role = role.to_string().clone();
+    let order_id = path.into_inner();
+    let user_id = auth.user_id;
 
     // if role != "admin" && role != "agent" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
@@ -502,69 +363,110 @@ pub async fn update_order(
     //         message: String::from("Unauthorized!"),
     //     });
     // }
-    if role == "user"
-        && body.status.as_str() != "Cancelled"
-        && body.status.as_str() != "Returned"
-        && body.status.as_str() != "Completed"
-    {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    } else if role == "agent" && !user::can_modify_order_status(user_id, &client).await {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
+    // Buyers may only cancel, return or complete, any other status needs the seller side permission
+    let buyer_status = matches!(body.status.as_str(), "Cancelled" | "Returned" | "Completed");
+    let update_status = auth.has_permission(ORDERS_UPDATE_STATUS);
+    let can_update = update_status || (buyer_status && auth.has_permission(ORDERS_MANAGE_OWN));
+    if !can_update {
+        return forbidden();
     }
 
-    let status_list: Vec<&str> = vec![
-        "Pending",
-        "Processing",
-        "Shipped",
-        "Delivered",
-        "Completed",
-        "Cancelled",
-        "Refunded",
-        "Failed",
-        "On Hold",
-        "Backordered",
-        "Returned",
-    ];
-    if !status_list.contains(&body.status.as_str()) {
+    if !order::is_valid_order_status(&body.status) {
         return HttpResponse::BadRequest().json(BaseResponse {
             code: 400,
             message: String::from("Please select a valid order status: Pending, Processing, Shipped, Delivered, Completed, Cancelled, Refunded, Failed, On Hold, Backordered, or Returned."),
         });
     }
 
-    match order::get_user_id_by_order_id(order_id, &client).await {
-        Some(client_id) => match order::update_order(order_id, &body.status, &client).await {
-            Ok(()) => {
-                tokio::spawn(async move {
-                    let title = format!("Order {}", &body.status);
-                    let status: &str = &body.status;
-                    let message = match status {
-                        "Processing" => {
-                            format!("Your order #{order_id} is {}.", &body.status.to_lowercase())
-                        }
-                        _ => format!(
-                            "Your order #{order_id} has been {}.",
-                            &body.status.to_lowercase()
-                        ),
-                    };
-                    let mut map = HashMap::new();
-                    map.insert(
-                        "redirect".to_string(),
-                        Value::String("order-detail".to_string()),
+    let client_id = match order::get_user_id_by_order_id(order_id, &client).await {
+        Some(id) => id,
+        None => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Order not found!"),
+            })
+        }
+    };
+    if !auth.has_permission(ORDERS_VIEW_ANY)
+        && client_id != user_id
+        && product::get_product_creator_id_from_order_id(order_id, &client).await != user_id
+    {
+        return forbidden();
+    }
+
+    match order::update_order(
+        order_id,
+        &body.status,
+        user_id,
+        update_status,
+        &body.note,
+        &mut client,
+    )
+    .await
+    {
+        Ok(OrderStatusUpdate::NotFound) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Order not found!"),
+        }),
+        Ok(OrderStatusUpdate::InvalidTransition(old_status)) => {
+            HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: format!(
+                    "Order status cannot be changed from {old_status} to {}.",
+                    &body.status
+                ),
+            })
+        }
+        Ok(OrderStatusUpdate::Updated) => {
+            tokio::spawn(async move {
+                let title = format!("Order {}", &body.status);
+                let status: &str = &body.status;
+                let message = match status {
+                    "Processing" => {
+                        format!("Your order #{order_id} is {}.", &body.status.to_lowercase())
+                    }
+                    _ => format!(
+                        "Your order #{order_id} has been {}.",
+                        &body.status.to_lowercase()
+                    ),
+                };
+                let mut map = HashMap::new();
+                map.insert(
+                    "redirect".to_string(),
+                    Value::String("order-detail".to_string()),
+                );
+                map.insert("id".to_string(), Value::Number(order_id.into()));
+                let clone_map = map.clone();
+                match notification::add_notification(
+                    client_id,
+                    &title,
+                    &message,
+                    &Some(map),
+                    &client,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        println!("Notification added successfully.");
+                    }
+                    Err(err) => {
+                        println!("Error adding notification: {:?}", err);
+                    }
+                };
+
+                if !update_status
+                    && (body.status.as_str() == "Cancelled"
+                        || body.status.as_str() == "Returned"
+                        || body.status.as_str() == "Completed")
+                {
+                    let message = format!(
+                        "Order #{order_id} has been {}.",
+                        &body.status.to_lowercase()
                     );
-                    map.insert("id".to_string(), Value::Number(order_id.into()));
-                    let clone_map = map.clone();
-                    match notification::add_notification(
-                        client_id,
+                    match notification::add_notification_to_admins(
                         &title,
                         &message,
-                        &Some(map),
+                        &Some(clone_map),
                         &client,
                     )
                     .await
@@ -576,88 +478,79 @@ pub async fn update_order(
                             println!("Error adding notification: {:?}", err);
                         }
                     };
-
-                    if &clone_role != "admin"
-                        && (body.status.as_str() == "Cancelled"
-                            || body.status.as_str() == "Returned"
-                            || body.status.as_str() == "Completed")
-                    {
-                        let message = format!(
-                            "Order #{order_id} has been {}.",
-                            &body.status.to_lowercase()
-                        );
-                        match notification::add_notification_to_admins(
-                            &title,
-                            &message,
-                            &Some(clone_map),
-                            &client,
-                        )
-                        .await
-                        {
-                            Ok(()) => {
-                                println!("Notification added successfully.");
-                            }
-                            Err(err) => {
-                                println!("Error adding notification: {:?}", err);
-                            }
-                        };
-                    }
-                });
-                return HttpResponse::Ok().json(BaseResponse {
-                    code: 200,
-                    message: String::from("Order updated successfully"),
-                });
-            }
-            Err(e) => {
-                eprintln!("User updating error: {}", e);
-                return HttpResponse::InternalServerError().json(BaseResponse {
-                    code: 500,
-                    message: String::from("Error updating order!"),
-                });
-            }
-        },
-        None => HttpResponse::NotFound().json(BaseResponse {
-            code: 404,
-            message: String::from("Order not found!"),
-        }),
+                }
+            });
+            HttpResponse::Ok().json(BaseResponse {
+                code: 200,
+                message: String::from("Order updated successfully"),
+            })
+        }
+        Err(e) => {
+            eprintln!("User updating error: {}", e);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error updating order!"),
+            })
+        }
     }
 }
 
-#[get("/api/orders/{order_id}/shop-name")]
-pub async fn get_order_shop_name(
-    req: HttpRequest,
+#[get("/api/orders/{order_id}/status-history")]
+pub async fn get_order_status_history(
+    auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let order_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
+    let user_id = auth.user_id;
+
+    let client_id = match order::get_user_id_by_order_id(order_id, &client).await {
+        Some(id) => id,
         None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Order not found!"),
             })
         }
     };
+    if !auth.has_permission(ORDERS_VIEW_ANY)
+        && client_id != user_id
+        && product::get_product_creator_id_from_order_id(order_id, &client).await != user_id
+    {
+        return forbidden();
+    }
 
-    if verify_token_and_get_sub(token).is_none() {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid token"),
-        });
+    match order::get_order_status_history(order_id, &client).await {
+        Ok(history) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: Some(history),
+        }),
+        Err(err) => {
+            println!("{:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching order status history!"),
+            })
+        }
     }
+}
 
+#[get("/api/orders/{order_id}/shop-name")]
+pub async fn get_order_shop_name(
+    _auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let order_id = path.into_inner();
     let shop_name = order::get_order_shop_name(order_id, &client).await;
     HttpResponse::Ok().json(DataResponse {
         code: 200,
@@ -668,39 +561,15 @@ pub async fn get_order_shop_name(
 
 #[get("/api/orders/{order_id}/refund-reason")]
 pub async fn get_order_refund_reason(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    let order_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if verify_token_and_get_sub(token).is_none() {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid token"),
-        });
-    }
-
+    let order_id = path.into_inner();
     match refund_reason::get_refund_reason_by_order_id(order_id, &client).await {
         Some(r) => HttpResponse::Ok().json(DataResponse {
             code: 200,
@@ -716,52 +585,16 @@ pub async fn get_order_refund_reason(
 
 #[get("/api/orders/{order_id}/remind-seller")]
 pub async fn remind_seller(
-    req: HttpRequest,
+    auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    let order_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
+    let order_id = path.into_inner();
+    let user_id = auth.user_id;
 
     match order::get_status_by_order_id(order_id, &client).await {
         Some(status) => {
@@ -1,8 +1,6 @@
-use std::sync::Arc;
-
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
@@ -11,8 +9,10 @@ use crate::{
         shop,
     },
     utils::{
+        auth::{AuthUser, Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::{ProductsManageOwn, PRODUCTS_MANAGE_ANY},
     },
 };
 
@@ -36,46 +36,16 @@ pub struct GetProductsRequestBody {
 
 #[post("/api/get-products")]
 pub async fn get_products(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     body: web::Json<GetProductsRequestBody>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let mut role = "user".to_string();
-    let mut user_id = 0;
-    if !token.is_empty() {
-        let sub = match verify_token_and_get_sub(&token) {
-            Some(s) => s,
-            None => {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Invalid token"),
-                })
-            }
-        };
-        // Parse the `sub` value
-        let parsed_values: Vec<String> = sub.split(',').map(|s| s.to_string()).collect();
-        if parsed_values.len() != 2 {
-            return HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Invalid sub format in token"),
-            });
-        }
-        user_id = parsed_values[0].parse().unwrap();
-        role = parsed_values[1].clone();
-    }
+    let user_id = auth.user_id();
+    let role = auth.role();
 
     let platform = match &body.platform {
         Some(p) => p.as_str(),
@@ -136,8 +106,12 @@ pub struct GetModelsQuery {
 #[get("/api/models")]
 pub async fn get_models(
     query: web::Query<GetModelsQuery>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     match product::get_models(&query.search, query.page, query.per_page, &client).await {
         Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
             code: 200,
@@ -161,59 +135,16 @@ pub async fn get_models(
 
 #[post("/api/products")]
 pub async fn add_product(
-    req: HttpRequest,
+    auth: Authorized<ProductsManageOwn>,
     body: web::Json<ProductRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     if body.model.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -317,7 +248,7 @@ pub async fn add_product(
     }
 
     let mut creator_id = user_id;
-    if role == "admin" {
+    if auth.has_permission(PRODUCTS_MANAGE_ANY) {
         if let Some(id) = shop::get_creator_id_from_shop(body.shop_id, &client).await {
             creator_id = id;
         }
@@ -362,39 +293,15 @@ pub async fn add_product(
 
 #[get("/api/products/{product_id}")]
 pub async fn get_product_by_id(
-    req: HttpRequest,
+    _auth: AuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let product_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if verify_token_and_get_sub(token).is_none() {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid token"),
-        });
-    }
-
+    let product_id = path.into_inner();
     match product::get_product_by_id(product_id, 0, &client).await {
         Some(p) => HttpResponse::Ok().json(DataResponse {
             code: 200,
@@ -410,61 +317,17 @@ pub async fn get_product_by_id(
 
 #[put("/api/products/{product_id}")]
 pub async fn update_product(
-    req: HttpRequest,
+    auth: Authorized<ProductsManageOwn>,
     path: web::Path<i32>,
     body: web::Json<ProductRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let product_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    // let user_id = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let product_id = path.into_inner();
+    let role = auth.role.as_str();
 
     if body.model.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -599,59 +462,15 @@ pub async fn update_product(
 
 #[delete("/api/products/{product_id}")]
 pub async fn delete_product(
-    req: HttpRequest,
+    _auth: Authorized<ProductsManageOwn>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let product_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let product_id = path.into_inner();
 
     match product::get_product_by_id(product_id, 0, &client).await {
         Some(p) => match product::delete_product(product_id, &p.product_images, &client).await {
@@ -689,8 +508,12 @@ pub async fn delete_product(
 #[get("/api/products/{product_id}/recommended")]
 pub async fn get_recommended_products_for_product(
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let product_id = path.into_inner();
     match product::get_recommended_products_for_product(product_id, &client).await {
         Ok(products) => HttpResponse::Ok().json(DataResponse {
@@ -710,51 +533,14 @@ pub async fn get_recommended_products_for_product(
 
 #[get("/api/recommended-products")]
 pub async fn get_recommended_products_for_user(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: AuthUser,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: &str = parsed_values[0];
-    let user_id: i32 = user_id.parse().unwrap();
+    let user_id = auth.user_id;
 
     match product::get_recommended_products_for_user(user_id, &client).await {
         Ok(products) => HttpResponse::Ok().json(DataResponse {
@@ -773,7 +559,11 @@ pub async fn get_recommended_products_for_user(
 }
 
 #[get("/api/generate-product-htmls")]
-pub async fn generate_product_htmls(client: web::Data<Arc<Client>>) -> impl Responder {
+pub async fn generate_product_htmls(pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     match product::get_products_for_html(0, &client).await {
         Ok(products) => {
             for product in &products {
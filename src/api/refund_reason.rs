@@ -1,68 +1,45 @@
-use std::{collections::HashMap, sync::Arc};
+use std::collections::HashMap;
 
-use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde_json::Value;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
-        notification, product,
+        notification,
+        order::OrderStatusUpdate,
+        product,
         refund_reason::{self, RefundReasonRequest},
         user,
     },
-    utils::{common_struct::BaseResponse, jwt::verify_token_and_get_sub},
+    utils::{auth::AuthUser, common_struct::BaseResponse, db::get_client},
 };
 
 #[post("/api/refund-reasons")]
 pub async fn add_refund_reason(
-    req: HttpRequest,
+    auth: AuthUser,
     body: web::Json<RefundReasonRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
+    let user_id = auth.user_id;
 
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
+    let order_id = body.order_id;
+    match refund_reason::add_refund_reason(&body, user_id, &mut client).await {
+        Ok(OrderStatusUpdate::NotFound) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Order not found!"),
+        }),
+        Ok(OrderStatusUpdate::InvalidTransition(status)) => {
+            HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: format!("A {status} order cannot be returned."),
             })
         }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    let order_id = body.order_id;
-    match refund_reason::add_refund_reason(&body, user_id, &client).await {
-        Ok(()) => {
+        Ok(OrderStatusUpdate::Updated) => {
             tokio::spawn(async move {
                 let user_name = match user::get_user_name(user_id, &client).await {
                     Some(name) => name,
@@ -0,0 +1,353 @@
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::{GenericClient, Pool};
+use serde::Deserialize;
+
+use crate::{
+    models::{
+        role::{self, RoleRequest, BASE_ROLES},
+        user,
+    },
+    utils::{
+        auth::Authorized,
+        common_struct::{BaseResponse, DataResponse},
+        db::get_client,
+        permission::RolesManage,
+    },
+};
+
+// `current_name` is the role's name when updating, a role may keep its own name
+async fn validate_role_request(
+    body: &RoleRequest,
+    current_name: Option<&str>,
+    client: &impl GenericClient,
+) -> Result<(), HttpResponse> {
+    if body.name.trim().is_empty() {
+        return Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Role name must not be empty!"),
+        }));
+    }
+    if !BASE_ROLES.contains(&body.base_role.as_str()) {
+        return Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Base role must be admin, agent or user!"),
+        }));
+    }
+    if current_name != Some(body.name.as_str()) {
+        match role::role_exists(&body.name, client).await {
+            Ok(false) => {}
+            Ok(true) => {
+                return Err(HttpResponse::BadRequest().json(BaseResponse {
+                    code: 400,
+                    message: String::from("Role name is already in use!"),
+                }))
+            }
+            Err(err) => {
+                println!("Error checking role name: {:?}", err);
+                return Err(HttpResponse::InternalServerError().json(BaseResponse {
+                    code: 500,
+                    message: String::from("Something went wrong!"),
+                }));
+            }
+        }
+    }
+    match role::get_unknown_permissions(&body.permissions, client).await {
+        Ok(unknown) if unknown.is_empty() => Ok(()),
+        Ok(unknown) => Err(HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: format!("Unknown permissions: {}!", unknown.join(", ")),
+        })),
+        Err(err) => {
+            println!("Error checking permissions: {:?}", err);
+            Err(HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            }))
+        }
+    }
+}
+
+#[get("/api/permissions")]
+pub async fn get_permissions(
+    _auth: Authorized<RolesManage>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match role::get_permissions(&client).await {
+        Ok(permissions) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Permissions fetched successfully."),
+            data: Some(permissions),
+        }),
+        Err(err) => {
+            println!("Error fetching permissions: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching permissions!"),
+            })
+        }
+    }
+}
+
+#[get("/api/roles")]
+pub async fn get_roles(_auth: Authorized<RolesManage>, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match role::get_roles(&client).await {
+        Ok(roles) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Roles fetched successfully."),
+            data: Some(roles),
+        }),
+        Err(err) => {
+            println!("Error fetching roles: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching roles!"),
+            })
+        }
+    }
+}
+
+#[post("/api/roles")]
+pub async fn add_role(
+    _auth: Authorized<RolesManage>,
+    body: web::Json<RoleRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    if let Err(res) = validate_role_request(&body, None, &client).await {
+        return res;
+    }
+
+    match role::add_role(&body, &mut client).await {
+        Ok(role_id) => HttpResponse::Created().json(DataResponse {
+            code: 201,
+            message: String::from("Role added successfully."),
+            data: Some(role_id),
+        }),
+        Err(err) => {
+            println!("Error adding role: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error adding role!"),
+            })
+        }
+    }
+}
+
+#[get("/api/roles/{role_id}")]
+pub async fn get_role_by_id(
+    _auth: Authorized<RolesManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let role_id = path.into_inner();
+
+    match role::get_role_by_id(role_id, &client).await {
+        Ok(Some(r)) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Role fetched successfully."),
+            data: Some(r),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Role not found!"),
+        }),
+        Err(err) => {
+            println!("Error fetching role: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching role!"),
+            })
+        }
+    }
+}
+
+#[put("/api/roles/{role_id}")]
+pub async fn update_role(
+    _auth: Authorized<RolesManage>,
+    path: web::Path<i32>,
+    body: web::Json<RoleRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let role_id = path.into_inner();
+
+    let r = match role::get_role_by_id(role_id, &client).await {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Role not found!"),
+            })
+        }
+        Err(err) => {
+            println!("Error fetching role: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching role!"),
+            });
+        }
+    };
+    // The built in roles are what registration and the seller flow hand out
+    if r.is_system {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Built-in roles cannot be changed!"),
+        });
+    }
+    if let Err(res) = validate_role_request(&body, Some(&r.name), &client).await {
+        return res;
+    }
+
+    match role::update_role(role_id, &r.name, &body, &mut client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Role updated successfully."),
+        }),
+        Err(err) => {
+            println!("Error updating role: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error updating role!"),
+            })
+        }
+    }
+}
+
+#[delete("/api/roles/{role_id}")]
+pub async fn delete_role(
+    _auth: Authorized<RolesManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let role_id = path.into_inner();
+
+    let r = match role::get_role_by_id(role_id, &client).await {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Role not found!"),
+            })
+        }
+        Err(err) => {
+            println!("Error fetching role: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching role!"),
+            });
+        }
+    };
+    if r.is_system {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Built-in roles cannot be deleted!"),
+        });
+    }
+    if r.user_counts > 0 {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from(
+                "Please assign the users of this role another role before deleting it!",
+            ),
+        });
+    }
+
+    match role::delete_role(role_id, &client).await {
+        Ok(()) => HttpResponse::Ok().json(BaseResponse {
+            code: 204,
+            message: String::from("Role deleted successfully"),
+        }),
+        Err(err) => {
+            println!("Error deleting role: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error deleting role!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AssignRoleRequest {
+    pub role: String,
+}
+
+#[put("/api/users/{user_id}/role")]
+pub async fn assign_role(
+    auth: Authorized<RolesManage>,
+    path: web::Path<i32>,
+    body: web::Json<AssignRoleRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let user_id = path.into_inner();
+
+    // Keeps an admin from taking away their own access to roles by accident
+    if user_id == auth.user_id {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("You cannot change your own role!"),
+        });
+    }
+    match role::role_exists(&body.role, &client).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::BadRequest().json(BaseResponse {
+                code: 400,
+                message: String::from("Role not found!"),
+            })
+        }
+        Err(err) => {
+            println!("Error checking role: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Something went wrong!"),
+            });
+        }
+    }
+
+    match user::set_user_role(user_id, &body.role, &client).await {
+        Ok(true) => HttpResponse::Ok().json(BaseResponse {
+            code: 200,
+            message: String::from("Role assigned successfully."),
+        }),
+        Ok(false) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("User not found!"),
+        }),
+        Err(err) => {
+            println!("Error assigning role: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error assigning role!"),
+            })
+        }
+    }
+}
@@ -1,14 +1,14 @@
-use std::sync::Arc;
-
-use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
-use tokio_postgres::Client;
 
 use crate::{
     models::seller_agreement_contract,
     utils::{
+        auth::Authorized,
         common_struct::{BaseResponse, DataResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::ContentManage,
     },
 };
 
@@ -19,59 +19,15 @@ pub struct SellerAgreementRequest {
 
 #[post("/api/seller-agreement-contract")]
 pub async fn save_seller_agreement_contract(
-    req: HttpRequest,
+    _auth: Authorized<ContentManage>,
     body: web::Json<SellerAgreementRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
 
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
-
     match seller_agreement_contract::save_seller_agreement_contract(&body.file_path, &client).await
     {
         Ok(()) => HttpResponse::Ok().json(BaseResponse {
@@ -89,7 +45,11 @@ pub async fn save_seller_agreement_contract(
 }
 
 #[get("/api/seller-agreement-contract")]
-pub async fn get_seller_agreement_contract(client: web::Data<Arc<Client>>) -> impl Responder {
+pub async fn get_seller_agreement_contract(pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     match seller_agreement_contract::get_seller_agreement_contract(&client).await {
         Some(file_path) => HttpResponse::Ok().json(DataResponse {
             code: 200,
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use actix_web::{get, post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    models::{
+        notification,
+        seller_application::{
+            self, is_valid_decision, Decided, APPROVED, DOCUMENTS_REQUESTED, REJECTED,
+        },
+    },
+    utils::{
+        auth::Authorized,
+        code_delivery::{CodeDelivery, DeliveryChannel},
+        common_struct::{BaseResponse, DataResponse, PaginationResponse},
+        db::get_client,
+        permission::SellersApprove,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct GetSellerApplicationsQuery {
+    pub search: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+#[get("/api/seller-applications")]
+pub async fn get_seller_applications(
+    _auth: Authorized<SellersApprove>,
+    query: web::Query<GetSellerApplicationsQuery>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    match seller_application::get_seller_applications(
+        &query.search,
+        query.page,
+        query.per_page,
+        &client,
+    )
+    .await
+    {
+        Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: item_result.data,
+            total: item_result.total,
+            page: item_result.page,
+            per_page: item_result.per_page,
+            page_counts: item_result.page_counts,
+        }),
+        Err(err) => {
+            println!("Error retrieving seller applications: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read all seller applications from database"),
+            })
+        }
+    }
+}
+
+#[get("/api/seller-applications/{user_id}")]
+pub async fn get_seller_application(
+    _auth: Authorized<SellersApprove>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let user_id = path.into_inner();
+
+    match seller_application::get_seller_application(user_id, &client).await {
+        Ok(Some(application)) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Seller application fetched successfully."),
+            data: Some(application),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Seller application not found!"),
+        }),
+        Err(err) => {
+            println!("Error fetching seller application: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching seller application!"),
+            })
+        }
+    }
+}
+
+#[get("/api/seller-applications/{user_id}/decisions")]
+pub async fn get_decisions(
+    _auth: Authorized<SellersApprove>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let user_id = path.into_inner();
+
+    match seller_application::get_decisions(user_id, &client).await {
+        Ok(decisions) => HttpResponse::Ok().json(DataResponse {
+            code: 200,
+            message: String::from("Decisions fetched successfully."),
+            data: Some(decisions),
+        }),
+        Err(err) => {
+            println!("Error fetching decisions: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching decisions!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DecisionRequest {
+    pub decision: String,
+    pub reason: Option<String>,
+}
+
+#[post("/api/seller-applications/{user_id}/decisions")]
+pub async fn add_decision(
+    auth: Authorized<SellersApprove>,
+    path: web::Path<i32>,
+    body: web::Json<DecisionRequest>,
+    pool: web::Data<Pool>,
+    code_delivery: web::Data<dyn CodeDelivery>,
+) -> impl Responder {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let user_id = path.into_inner();
+
+    if !is_valid_decision(&body.decision) {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from(
+                "Please select a valid decision: Approved, Rejected or Documents Requested.",
+            ),
+        });
+    }
+    let reason = body.reason.as_deref().unwrap_or("").trim();
+    if body.decision != APPROVED && reason.is_empty() {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Please tell the applicant the reason!"),
+        });
+    }
+
+    let application = match seller_application::get_seller_application(user_id, &client).await {
+        Ok(Some(application)) => application,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Seller application not found!"),
+            })
+        }
+        Err(err) => {
+            println!("Error fetching seller application: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching seller application!"),
+            });
+        }
+    };
+
+    let shop_id = match seller_application::decide(
+        user_id,
+        &body.decision,
+        reason,
+        auth.user_id,
+        &mut client,
+    )
+    .await
+    {
+        Ok(Decided::Recorded { shop_id }) => shop_id,
+        Ok(Decided::NotPending) => {
+            return HttpResponse::Conflict().json(BaseResponse {
+                code: 409,
+                message: String::from("This application has already been decided!"),
+            })
+        }
+        Err(err) => {
+            println!("Error deciding seller application: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error deciding seller application!"),
+            });
+        }
+    };
+
+    let (title, message) = match body.decision.as_str() {
+        APPROVED => (
+            "Seller Application Approved",
+            String::from("Your seller application has been approved and your shop is ready. You can now sign in and start selling."),
+        ),
+        REJECTED => (
+            "Seller Application Rejected",
+            format!("Your seller application was not approved. Reason: {}", reason),
+        ),
+        _ => (
+            "More Documents Needed",
+            format!(
+                "We need more documents to review your seller application: {}",
+                reason
+            ),
+        ),
+    };
+
+    // New registrations cannot sign in until approved, so the decision also goes to
+    // their email or phone
+    let (channel, destination) = if application.email.is_empty() {
+        (DeliveryChannel::Sms, &application.phone)
+    } else {
+        (DeliveryChannel::Email, &application.email)
+    };
+    if !destination.is_empty() {
+        if let Err(err) = code_delivery
+            .deliver(&channel, destination, title, &message)
+            .await
+        {
+            println!("Error sending seller application decision: {:?}", err);
+        }
+    }
+
+    let mut map = HashMap::new();
+    map.insert(
+        "redirect".to_string(),
+        Value::String("seller-application".to_string()),
+    );
+    if let Some(shop_id) = shop_id {
+        map.insert("id".to_string(), Value::Number(shop_id.into()));
+    }
+    tokio::spawn(async move {
+        match notification::add_notification(user_id, title, &message, &Some(map), &client).await {
+            Ok(()) => {
+                println!("Notification added successfully.");
+            }
+            Err(err) => {
+                println!("Error adding notification: {:?}", err);
+            }
+        };
+    });
+
+    let message = match body.decision.as_str() {
+        APPROVED => "Seller application approved.",
+        DOCUMENTS_REQUESTED => "Documents requested from the applicant.",
+        _ => "Seller application rejected.",
+    };
+    HttpResponse::Ok().json(DataResponse {
+        code: 200,
+        message: String::from(message),
+        data: shop_id,
+    })
+}
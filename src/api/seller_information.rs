@@ -1,18 +1,20 @@
-use std::sync::Arc;
-
 use actix_web::{get, web, HttpResponse, Responder};
-use tokio_postgres::Client;
+use deadpool_postgres::Pool;
 
 use crate::{
     models::seller_information,
-    utils::common_struct::{BaseResponse, DataResponse},
+    utils::{
+        common_struct::{BaseResponse, DataResponse},
+        db::get_client,
+    },
 };
 
 #[get("/api/users/{user_id}/seller-information")]
-pub async fn get_seller_information(
-    path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
-) -> impl Responder {
+pub async fn get_seller_information(path: web::Path<i32>, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let user_id: i32 = path.into_inner();
     match seller_information::get_seller_information(user_id, &client).await {
         Some(seller_information) => HttpResponse::Ok().json(DataResponse {
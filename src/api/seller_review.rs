@@ -1,64 +1,26 @@
-use std::sync::Arc;
-
-use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
-use tokio_postgres::Client;
+use actix_web::{get, post, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 
 use crate::{
     models::seller_review::{self, SellerReviewRequest},
     utils::{
+        auth::AuthUser,
         common_struct::{BaseResponse, DataResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
     },
 };
 
 #[post("/api/seller-reviews")]
 pub async fn add_seller_review(
-    req: HttpRequest,
+    auth: AuthUser,
     body: web::Json<SellerReviewRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    // let role: &str = parsed_values[1];
+    let user_id = auth.user_id;
 
     // if role != "user" && role != "admin" {
     //     return HttpResponse::Unauthorized().json(BaseResponse {
@@ -83,10 +45,11 @@ pub async fn add_seller_review(
 }
 
 #[get("/api/shops/{shop_id}/reviews")]
-pub async fn get_seller_reviews(
-    path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
-) -> impl Responder {
+pub async fn get_seller_reviews(path: web::Path<i32>, pool: web::Data<Pool>) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
     let shop_id = path.into_inner();
     match seller_review::get_seller_reviews(shop_id, &client).await {
         Ok(reviews) => HttpResponse::Ok().json(DataResponse {
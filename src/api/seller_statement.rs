@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+
+use actix_web::{get, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    models::{
+        notification,
+        seller_statement::{self, MarkedPaid},
+    },
+    utils::{
+        auth::{forbidden, AuthUser, Authorized},
+        common_struct::{BaseResponse, DataResponse, PaginationResponse},
+        db::get_client,
+        permission::{BillingManage, BILLING_MANAGE},
+    },
+};
+
+#[derive(Deserialize)]
+pub struct GetSellerStatementsQuery {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    pub seller_id: Option<i32>,
+    pub status: Option<String>,
+    pub overdue: Option<bool>,
+}
+
+#[get("/api/seller-statements")]
+pub async fn get_seller_statements(
+    auth: AuthUser,
+    query: web::Query<GetSellerStatementsQuery>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+
+    let seller_id = if auth.has_permission(BILLING_MANAGE) {
+        query.seller_id
+    } else {
+        Some(auth.user_id)
+    };
+
+    match seller_statement::get_seller_statements(
+        query.page,
+        query.per_page,
+        seller_id,
+        &query.status,
+        query.overdue,
+        &client,
+    )
+    .await
+    {
+        Ok(item_result) => HttpResponse::Ok().json(PaginationResponse {
+            code: 200,
+            message: String::from("Successful."),
+            data: item_result.data,
+            total: item_result.total,
+            page: item_result.page,
+            per_page: item_result.per_page,
+            page_counts: item_result.page_counts,
+        }),
+        Err(err) => {
+            println!("Error retrieving seller statements: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error trying to read all seller statements from database"),
+            })
+        }
+    }
+}
+
+#[get("/api/seller-statements/{statement_id}")]
+pub async fn get_seller_statement_by_id(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let statement_id = path.into_inner();
+
+    match seller_statement::get_seller_statement_by_id(statement_id, &client).await {
+        Ok(Some(statement)) => {
+            if statement.seller_id != auth.user_id && !auth.has_permission(BILLING_MANAGE) {
+                return forbidden();
+            }
+            HttpResponse::Ok().json(DataResponse {
+                code: 200,
+                message: String::from("Seller statement fetched successfully."),
+                data: Some(statement),
+            })
+        }
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Seller statement not found!"),
+        }),
+        Err(err) => {
+            println!("Error fetching seller statement: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching seller statement!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SubmitPaymentRequest {
+    pub payslip_screenshot_path: String,
+}
+
+#[put("/api/seller-statements/{statement_id}/payslip")]
+pub async fn submit_payment(
+    auth: AuthUser,
+    path: web::Path<i32>,
+    body: web::Json<SubmitPaymentRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let statement_id = path.into_inner();
+
+    if body.payslip_screenshot_path.is_empty() {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Please upload your payment screenshot!"),
+        });
+    }
+
+    match seller_statement::submit_payment(
+        statement_id,
+        auth.user_id,
+        &body.payslip_screenshot_path,
+        &client,
+    )
+    .await
+    {
+        Ok(true) => {
+            let message = format!(
+                "Seller #{} uploaded a payment screenshot for statement #{}.",
+                auth.user_id, statement_id
+            );
+            let mut map = HashMap::new();
+            map.insert(
+                "redirect".to_string(),
+                Value::String("seller-statement".to_string()),
+            );
+            map.insert("id".to_string(), Value::Number(statement_id.into()));
+            tokio::spawn(async move {
+                match notification::add_notification_to_admins(
+                    "Statement Payment Submitted",
+                    &message,
+                    &Some(map),
+                    &client,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        println!("Notification added successfully.");
+                    }
+                    Err(err) => {
+                        println!("Error adding notification: {:?}", err);
+                    }
+                };
+            });
+            HttpResponse::Ok().json(BaseResponse {
+                code: 200,
+                message: String::from("Payment submitted successfully."),
+            })
+        }
+        Ok(false) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Unpaid statement not found!"),
+        }),
+        Err(err) => {
+            println!("Error submitting statement payment: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error submitting payment!"),
+            })
+        }
+    }
+}
+
+#[put("/api/seller-statements/{statement_id}/paid")]
+pub async fn mark_paid(
+    auth: Authorized<BillingManage>,
+    path: web::Path<i32>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let mut client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let statement_id = path.into_inner();
+
+    let statement = match seller_statement::get_seller_statement_by_id(statement_id, &client).await
+    {
+        Ok(Some(statement)) => statement,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(BaseResponse {
+                code: 404,
+                message: String::from("Seller statement not found!"),
+            })
+        }
+        Err(err) => {
+            println!("Error fetching seller statement: {:?}", err);
+            return HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error fetching seller statement!"),
+            });
+        }
+    };
+
+    match seller_statement::mark_paid(statement_id, auth.user_id, &mut client).await {
+        Ok(Some(MarkedPaid::Paid { shops_reopened })) => {
+            let mut message = format!(
+                "Your payment for the {} statement has been received.",
+                statement.period_start.format("%B %Y")
+            );
+            if shops_reopened {
+                message = format!("{} Your shop is open again.", message);
+            }
+            let mut map = HashMap::new();
+            map.insert(
+                "redirect".to_string(),
+                Value::String("seller-statement".to_string()),
+            );
+            map.insert("id".to_string(), Value::Number(statement_id.into()));
+            tokio::spawn(async move {
+                match notification::add_notification(
+                    statement.seller_id,
+                    "Statement Paid",
+                    &message,
+                    &Some(map),
+                    &client,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        println!("Notification added successfully.");
+                    }
+                    Err(err) => {
+                        println!("Error adding notification: {:?}", err);
+                    }
+                };
+            });
+            HttpResponse::Ok().json(BaseResponse {
+                code: 200,
+                message: String::from("Statement marked as paid."),
+            })
+        }
+        Ok(Some(MarkedPaid::AlreadyPaid)) => HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Statement is already paid!"),
+        }),
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Seller statement not found!"),
+        }),
+        Err(err) => {
+            println!("Error marking statement paid: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error marking statement paid!"),
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RejectPaymentRequest {
+    pub reason: Option<String>,
+}
+
+#[put("/api/seller-statements/{statement_id}/rejected")]
+pub async fn reject_payment(
+    _auth: Authorized<BillingManage>,
+    path: web::Path<i32>,
+    body: web::Json<RejectPaymentRequest>,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
+    };
+    let statement_id = path.into_inner();
+
+    let reason = body.reason.as_deref().unwrap_or("").trim();
+    if reason.is_empty() {
+        return HttpResponse::BadRequest().json(BaseResponse {
+            code: 400,
+            message: String::from("Please tell the seller the reason!"),
+        });
+    }
+
+    match seller_statement::reject_payment(statement_id, &client).await {
+        Ok(Some(seller_id)) => {
+            let message = format!(
+                "Your payment for statement #{} could not be confirmed. Reason: {} Please upload your payment screenshot again.",
+                statement_id, reason
+            );
+            let mut map = HashMap::new();
+            map.insert(
+                "redirect".to_string(),
+                Value::String("seller-statement".to_string()),
+            );
+            map.insert("id".to_string(), Value::Number(statement_id.into()));
+            tokio::spawn(async move {
+                match notification::add_notification(
+                    seller_id,
+                    "Statement Payment Rejected",
+                    &message,
+                    &Some(map),
+                    &client,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        println!("Notification added successfully.");
+                    }
+                    Err(err) => {
+                        println!("Error adding notification: {:?}", err);
+                    }
+                };
+            });
+            HttpResponse::Ok().json(BaseResponse {
+                code: 200,
+                message: String::from("Statement payment rejected."),
+            })
+        }
+        Ok(None) => HttpResponse::NotFound().json(BaseResponse {
+            code: 404,
+            message: String::from("Submitted payment not found!"),
+        }),
+        Err(err) => {
+            println!("Error rejecting statement payment: {:?}", err);
+            HttpResponse::InternalServerError().json(BaseResponse {
+                code: 500,
+                message: String::from("Error rejecting payment!"),
+            })
+        }
+    }
+}
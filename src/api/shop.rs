@@ -1,9 +1,9 @@
-use std::{collections::HashMap, sync::Arc};
+use std::collections::HashMap;
 
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use deadpool_postgres::Pool;
 use serde::Deserialize;
 use serde_json::Value;
-use tokio_postgres::Client;
 
 use crate::{
     models::{
@@ -12,8 +12,10 @@ use crate::{
         user,
     },
     utils::{
+        auth::{Authorized, OptionalAuthUser},
         common_struct::{BaseResponse, DataResponse, PaginationResponse},
-        jwt::verify_token_and_get_sub,
+        db::get_client,
+        permission::{ShopsManageOwn, SHOPS_MANAGE_ANY},
     },
 };
 
@@ -30,46 +32,16 @@ pub struct GetShopsQuery {
 
 #[get("/api/shops")]
 pub async fn get_shops(
-    req: HttpRequest,
-    client: web::Data<Arc<Client>>,
+    auth: OptionalAuthUser,
+    pool: web::Data<Pool>,
     query: web::Query<GetShopsQuery>,
 ) -> impl Responder {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let mut role = "user".to_string();
-    let mut user_id = 0;
-    if !token.is_empty() {
-        let sub = match verify_token_and_get_sub(&token) {
-            Some(s) => s,
-            None => {
-                return HttpResponse::Unauthorized().json(BaseResponse {
-                    code: 401,
-                    message: String::from("Invalid token"),
-                })
-            }
-        };
-        // Parse the `sub` value
-        let parsed_values: Vec<String> = sub.split(',').map(|s| s.to_string()).collect();
-        if parsed_values.len() != 2 {
-            return HttpResponse::InternalServerError().json(BaseResponse {
-                code: 500,
-                message: String::from("Invalid sub format in token"),
-            });
-        }
-        user_id = parsed_values[0].parse().unwrap();
-        role = parsed_values[1].clone();
-    }
+    let user_id = auth.user_id();
+    let role = auth.role();
 
     let platform = match &query.platform {
         Some(p) => p.as_str(),
@@ -115,59 +87,16 @@ pub async fn get_shops(
 
 #[post("/api/shops")]
 pub async fn add_shop(
-    req: HttpRequest,
+    auth: Authorized<ShopsManageOwn>,
     body: web::Json<ShopRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let user_id: i32 = parsed_values[0].parse().unwrap();
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let user_id = auth.user_id;
+    let role = auth.role.as_str();
 
     if body.name.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -200,7 +129,7 @@ pub async fn add_shop(
 
     match shop::add_shop(&body, user_id, role, &client).await {
         Ok(shop_id) => {
-            if role == "agent" {
+            if !auth.has_permission(SHOPS_MANAGE_ANY) {
                 tokio::spawn(async move {
                     let name = user::get_user_name(user_id, &client).await.unwrap();
                     let title = format!("Shop Approval Required");
@@ -248,31 +177,15 @@ pub async fn add_shop(
 
 #[get("/api/shops/{shop_id}")]
 pub async fn get_shop_by_id(
-    req: HttpRequest,
+    _auth: OptionalAuthUser,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let shop_id = path.into_inner();
-
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1].to_string()
-            } else {
-                "".to_string()
-            }
-        }
-        None => "".to_string(),
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    if !token.is_empty() && verify_token_and_get_sub(&token).is_none() {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Invalid token"),
-        });
-    }
+    let shop_id = path.into_inner();
 
     match shop::get_shop_by_id(shop_id, &client).await {
         Some(s) => HttpResponse::Ok().json(DataResponse {
@@ -289,60 +202,17 @@ pub async fn get_shop_by_id(
 
 #[put("/api/shops/{shop_id}")]
 pub async fn update_shop(
-    req: HttpRequest,
+    auth: Authorized<ShopsManageOwn>,
     path: web::Path<i32>,
     body: web::Json<ShopRequest>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let shop_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
-    };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let shop_id = path.into_inner();
+    let role = auth.role.as_str();
 
     if body.name.is_empty() {
         return HttpResponse::BadRequest().json(BaseResponse {
@@ -375,7 +245,7 @@ pub async fn update_shop(
 
     match shop::get_shop_by_id(shop_id, &client).await {
         Some(s) => {
-            if &s.status != "Pending Approval" && role == "agent" {
+            if &s.status != "Pending Approval" && !auth.has_permission(SHOPS_MANAGE_ANY) {
                 return HttpResponse::BadRequest().json(BaseResponse {
                     code: 400,
                     message: String::from("Your shop has been approved by the admin and can no longer be updated. If you need to make changes, please contact customer support!"),
@@ -451,59 +321,15 @@ pub async fn update_shop(
 
 #[delete("/api/shops/{shop_id}")]
 pub async fn delete_shop(
-    req: HttpRequest,
+    auth: Authorized<ShopsManageOwn>,
     path: web::Path<i32>,
-    client: web::Data<Arc<Client>>,
+    pool: web::Data<Pool>,
 ) -> HttpResponse {
-    let shop_id = path.into_inner();
-    // Extract the token from the Authorization header
-    let token = match req.headers().get("Authorization") {
-        Some(value) => {
-            let parts: Vec<&str> = value.to_str().unwrap_or("").split_whitespace().collect();
-            if parts.len() == 2 && parts[0] == "Bearer" {
-                parts[1]
-            } else {
-                return HttpResponse::BadRequest().json(BaseResponse {
-                    code: 400,
-                    message: String::from("Invalid Authorization header format"),
-                });
-            }
-        }
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Authorization header missing"),
-            })
-        }
-    };
-
-    let sub = match verify_token_and_get_sub(token) {
-        Some(s) => s,
-        None => {
-            return HttpResponse::Unauthorized().json(BaseResponse {
-                code: 401,
-                message: String::from("Invalid token"),
-            })
-        }
+    let client = match get_client(&pool).await {
+        Ok(client) => client,
+        Err(res) => return res,
     };
-
-    // Parse the `sub` value
-    let parsed_values: Vec<&str> = sub.split(',').collect();
-    if parsed_values.len() != 2 {
-        return HttpResponse::InternalServerError().json(BaseResponse {
-            code: 500,
-            message: String::from("Invalid sub format in token"),
-        });
-    }
-
-    let role: &str = parsed_values[1];
-
-    if role != "admin" && role != "agent" {
-        return HttpResponse::Unauthorized().json(BaseResponse {
-            code: 401,
-            message: String::from("Unauthorized!"),
-        });
-    }
+    let shop_id = path.into_inner();
 
     match product::is_products_exist("shop_id", shop_id, &client).await {
         Ok(is_exist) => {
@@ -525,7 +351,7 @@ pub async fn delete_shop(
 
     match shop::get_shop_by_id(shop_id, &client).await {
         Some(s) => {
-            if &s.status != "Pending Approval" && role == "agent" {
+            if &s.status != "Pending Approval" && !auth.has_permission(SHOPS_MANAGE_ANY) {
                 return HttpResponse::BadRequest().json(BaseResponse {
                     code: 400,
                     message: String::from("Your shop has been approved by the admin and can no longer be deleted. If you need to make changes, please contact customer support!"),
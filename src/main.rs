@@ -1,17 +1,73 @@
 extern crate dotenv;
 
-use std::{env, sync::Arc};
+use std::{env, time::Duration};
 
 use actix_cors::Cors;
 use actix_files as fs;
 use actix_web::{web, App, HttpServer};
 use dotenv::dotenv;
-use tokio_postgres::NoTls;
 
 mod api;
 mod models;
 mod utils;
 
+use models::{
+    auction::{finalize_expired_auctions, notify_closing_soon_auctions},
+    discount_rule::run_flash_sales,
+    seller_statement::run_billing,
+};
+use utils::{
+    code_delivery::create_code_delivery,
+    db::create_pool,
+    identity_provider::create_identity_providers,
+    migration::{get_migration_status, migrate, rollback},
+    setting::{get_auction_closer_interval, get_billing_interval, get_flash_sale_interval},
+};
+
+// `watchwonder migrate | rollback [steps] | status` manages the schema and exits,
+// with no arguments the pending migrations are applied and the server starts.
+async fn run_migration_command(command: &str, args: &[String], pool: &deadpool_postgres::Pool) {
+    let mut client = match pool.get().await {
+        Ok(client) => client,
+        Err(err) => panic!("Database connection error: {:?}", err),
+    };
+    match command {
+        "migrate" => match migrate(&mut client).await {
+            Ok(applied) if applied.is_empty() => println!("Database is up to date"),
+            Ok(_) => {}
+            Err(err) => panic!("Migration failed: {:?}", err),
+        },
+        "rollback" => {
+            let steps: usize = args
+                .first()
+                .map(|steps| steps.parse().expect("Steps must be number"))
+                .unwrap_or(1);
+            if let Err(err) = rollback(steps, &mut client).await {
+                panic!("Rollback failed: {}", err);
+            }
+        }
+        "status" => match get_migration_status(&client).await {
+            Ok(migrations) => {
+                for migration in migrations {
+                    let applied_at = match migration.applied_at {
+                        Some(applied_at) => applied_at.to_string(),
+                        None => String::from("pending"),
+                    };
+                    println!(
+                        "{:04}_{:<40} {}",
+                        migration.version, migration.name, applied_at
+                    );
+                }
+            }
+            Err(err) => panic!("Failed to read migration status: {:?}", err),
+        },
+        _ => panic!(
+            "Unknown command {}, expected migrate, rollback or status",
+            command
+        ),
+    }
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
@@ -20,17 +76,74 @@ async fn main() -> std::io::Result<()> {
         .parse()
         .expect("Port must be number");
     let conn = env::var("DB_CONNECTION").expect("DB_CONNECTION must be set");
-    let (client, connection) = tokio_postgres::connect(conn.as_str(), NoTls).await.unwrap();
-    let client = Arc::new(client);
+    let pool = create_pool(&conn);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        run_migration_command(command, &args[1..], &pool).await;
+        return Ok(());
+    }
+
+    // Fail fast on a bad connection string or a failed migration instead of on the first request
+    match pool.get().await {
+        Ok(mut client) => {
+            if let Err(err) = migrate(&mut client).await {
+                panic!("Migration failed: {:?}", err);
+            }
+        }
+        Err(err) => panic!("Database connection error: {:?}", err),
+    }
+
+    // Warn bidders about auctions that are about to end, then close auctions whose
+    // end time has passed and hand the item to the winner.
+    let closer_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(get_auction_closer_interval()));
+        loop {
+            interval.tick().await;
+            let mut client = match closer_pool.get().await {
+                Ok(client) => client,
+                Err(err) => {
+                    println!("Database connection error: {:?}", err);
+                    continue;
+                }
+            };
+            if let Err(err) = notify_closing_soon_auctions(&client).await {
+                println!("Error notifying closing soon auctions: {:?}", err);
+            }
+            finalize_expired_auctions(&mut client).await;
+        }
+    });
+
+    // Bill sellers for last month and suspend the shops of sellers with overdue statements
+    let billing_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(get_billing_interval()));
+        loop {
+            interval.tick().await;
+            match billing_pool.get().await {
+                Ok(client) => run_billing(&client).await,
+                Err(err) => println!("Database connection error: {:?}", err),
+            }
+        }
+    });
 
-    // The connection object performs the actual communication with the database,
-    // so spawn it off to run on its own.
+    // Announce flash sales to users who favourited their products as they start
+    let flash_sale_pool = pool.clone();
     tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("connection error: {}", e);
+        let mut interval = tokio::time::interval(Duration::from_secs(get_flash_sale_interval()));
+        loop {
+            interval.tick().await;
+            match flash_sale_pool.get().await {
+                Ok(client) => run_flash_sales(&client).await,
+                Err(err) => println!("Database connection error: {:?}", err),
+            }
         }
     });
 
+    let code_delivery = create_code_delivery();
+    let identity_providers = web::Data::new(create_identity_providers());
     HttpServer::new(move || {
         if !std::fs::metadata("./images").is_ok() {
             if let Err(err) = std::fs::create_dir_all("./images") {
@@ -42,6 +155,11 @@ async fn main() -> std::io::Result<()> {
                 println!("{:?}", err);
             }
         }
+        if std::fs::metadata("./reports").is_err() {
+            if let Err(err) = std::fs::create_dir_all("./reports") {
+                println!("{:?}", err);
+            }
+        }
         // let default_size = env::var("DEFAULT_REQUEST_SIZE")
         //     .unwrap_or_else(|_| "2097152".to_string())
         //     .parse::<usize>()
@@ -53,10 +171,13 @@ async fn main() -> std::io::Result<()> {
             .max_age(3600);
         App::new()
             .wrap(cors)
-            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::from(code_delivery.clone()))
+            .app_data(identity_providers.clone())
             .configure(api::init)
             .service(fs::Files::new("/images", "./images").show_files_listing())
             .service(fs::Files::new("/products", "./products").show_files_listing())
+            .service(fs::Files::new("/reports", "./reports"))
     })
     .bind(("0.0.0.0", port))?
     .run()
@@ -1,6 +1,7 @@
 pub mod address;
 pub mod advertisement;
 pub mod auction;
+pub mod auth_throttle;
 pub mod bank_account;
 pub mod brand;
 pub mod buyer_protection;
@@ -9,28 +10,37 @@ pub mod category;
 pub mod chat;
 pub mod condition;
 pub mod counter;
+pub mod coupon;
 pub mod currency;
 pub mod dial_glass_type;
 pub mod discount_rule;
 pub mod discount_type;
+pub mod favorite;
 pub mod fcm;
 pub mod gender;
 pub mod insurance;
+pub mod inventory_ledger;
 pub mod invoice;
 pub mod movement_country;
 pub mod movement_type;
 pub mod notification;
 pub mod order;
 pub mod other_accessories_type;
+pub mod password_reset;
 pub mod payment_type;
+pub mod pricing;
 pub mod product;
 pub mod reason_type;
+pub mod refresh_token;
 pub mod refund_reason;
+pub mod role;
 pub mod seller_agreement_contract;
+pub mod seller_application;
 pub mod seller_information;
 pub mod seller_registration_fee;
 pub mod seller_report;
 pub mod seller_review;
+pub mod seller_statement;
 pub mod shop;
 pub mod strap_material;
 pub mod terms_and_conditions;
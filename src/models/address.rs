@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Address {
@@ -27,7 +28,10 @@ pub struct NewAddress {
     pub note: String,
 }
 
-pub async fn get_address(user_id: i32, client: &Client) -> Result<Option<Address>, Error> {
+pub async fn get_address(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<Address>, Error> {
     let row=  client.query_one(
         "select address_id, street_address, city, state, postal_code, country, township, home_address, ward from user_addresses where user_id = $1 and deleted_at is null",
         &[&user_id],
@@ -1,8 +1,9 @@
 use std::{fs, path::Path};
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -16,7 +17,10 @@ pub struct AdvertisementRequest {
     pub level: i32,
 }
 
-pub async fn add_advertisement(data: &AdvertisementRequest, client: &Client) -> Result<i32, Error> {
+pub async fn add_advertisement(
+    data: &AdvertisementRequest,
+    client: &impl GenericClient,
+) -> Result<i32, Error> {
     let row= client
         .query_one(
             "insert into advertisements (media_type, media_url) values ($1, $2) returning advertisement_id",
@@ -40,7 +44,7 @@ pub async fn get_advertisements(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Advertisement>, Error> {
     let base_query = "from advertisements where deleted_at is null".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -95,7 +99,7 @@ pub async fn get_advertisements(
 
 pub async fn get_advertisement_by_id(
     advertisement_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Option<Advertisement> {
     match client
         .query_one(
@@ -122,7 +126,7 @@ pub async fn update_advertisement(
     advertisement_id: i32,
     data: &AdvertisementRequest,
     old_media_url: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client.execute("update advertisements set media_type = $1, media_url = $2, level = $3 where advertisement_id = $4", &[&data.media_type, &data.media_url, &data.level, &advertisement_id]).await?;
     if old_media_url != &data.media_url {
@@ -150,7 +154,7 @@ pub async fn update_advertisement(
 pub async fn delete_advertisement(
     advertisement_id: i32,
     old_media_url: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -1,12 +1,26 @@
-use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use serde_json::Value;
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
+    fcm::send_notification,
+    setting::{get_auction_closing_soon_minutes, get_auction_min_bid_increment},
+    socketio,
     sql::{generate_pagination_query, PaginationOptions},
 };
 
+use super::{
+    address::{self, NewAddress},
+    fcm::get_fcm_tokens,
+    invoice, notification,
+    order::{self, NewOrder, NewOrderItem},
+};
+
 #[derive(Deserialize)]
 pub struct AuctionRequest {
     pub product_id: i32,
@@ -15,12 +29,26 @@ pub struct AuctionRequest {
     pub start_bid: f64,
     pub reserve_price: f64,
     pub buy_it_now_available: bool,
+    pub buy_it_now_price: Option<f64>,
+    #[serde(default)]
+    pub soft_close_minutes: i32,
+    #[serde(default)]
+    pub proxy_bidding_enabled: bool,
 }
 
-pub async fn add_auction(data: &AuctionRequest, client: &Client) -> Result<i32, Error> {
-    let query: String =format!("insert into auctions (product_id, start_time, end_time, start_bid, reserve_price, buy_it_now_available) values ($1, '{}', '{}', {}, {}, $2) returning auction_id", data.start_time, data.end_time, data.start_bid, data.reserve_price);
+pub async fn add_auction(data: &AuctionRequest, client: &impl GenericClient) -> Result<i32, Error> {
+    let query: String =format!("insert into auctions (product_id, start_time, end_time, start_bid, reserve_price, buy_it_now_available, buy_it_now_price, soft_close_minutes, proxy_bidding_enabled) values ($1, '{}', '{}', {}, {}, $2, $3::float8, $4, $5) returning auction_id", data.start_time, data.end_time, data.start_bid, data.reserve_price);
     let row = client
-        .query_one(&query, &[&data.product_id, &data.buy_it_now_available])
+        .query_one(
+            &query,
+            &[
+                &data.product_id,
+                &data.buy_it_now_available,
+                &data.buy_it_now_price,
+                &data.soft_close_minutes,
+                &data.proxy_bidding_enabled,
+            ],
+        )
         .await?;
     let auction_id = row.get("auction_id");
     client
@@ -42,6 +70,12 @@ pub struct Auction {
     pub current_bid: f64,
     pub reserve_price: f64,
     pub buy_it_now_available: bool,
+    pub buy_it_now_price: Option<f64>,
+    pub soft_close_minutes: i32,
+    pub proxy_bidding_enabled: bool,
+    pub status: String,
+    pub winner_id: Option<i32>,
+    pub sold_out_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
 }
 
@@ -49,7 +83,7 @@ pub async fn get_auctions(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Auction>, Error> {
     let base_query = "from auctions where deleted_at is null and status = 'active'".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -57,7 +91,7 @@ pub async fn get_auctions(
     let order_options = "created_at desc";
 
     let result = generate_pagination_query(PaginationOptions {
-        select_columns: "auction_id, product_id, start_time, end_time, start_bid::text, current_bid::text, reserve_price::text, buy_it_now_available, created_at",
+        select_columns: "auction_id, product_id, start_time, end_time, start_bid::text, current_bid::text, reserve_price::text, buy_it_now_available, buy_it_now_price::text, soft_close_minutes, proxy_bidding_enabled, status, winner_id, sold_out_at, created_at",
         base_query: &base_query,
         search_columns: vec!["auction_id::text"],
         search: search.as_deref(),
@@ -94,6 +128,9 @@ pub async fn get_auctions(
             let reserve_price: String = row.get("reserve_price");
             let reserve_price: f64 = reserve_price.parse().unwrap();
 
+            let buy_it_now_price: Option<String> = row.get("buy_it_now_price");
+            let buy_it_now_price: Option<f64> = buy_it_now_price.map(|p| p.parse().unwrap());
+
             Auction {
                 auction_id: row.get("auction_id"),
                 product_id: row.get("product_id"),
@@ -103,6 +140,12 @@ pub async fn get_auctions(
                 current_bid,
                 reserve_price,
                 buy_it_now_available: row.get("buy_it_now_available"),
+                buy_it_now_price,
+                soft_close_minutes: row.get("soft_close_minutes"),
+                proxy_bidding_enabled: row.get("proxy_bidding_enabled"),
+                status: row.get("status"),
+                winner_id: row.get("winner_id"),
+                sold_out_at: row.get("sold_out_at"),
                 created_at: row.get("created_at"),
             }
         })
@@ -117,10 +160,10 @@ pub async fn get_auctions(
     })
 }
 
-pub async fn get_auction_by_id(auction_id: i32, client: &Client) -> Option<Auction> {
+pub async fn get_auction_by_id(auction_id: i32, client: &impl GenericClient) -> Option<Auction> {
     match client
         .query_one(
-            "select auction_id, product_id, start_time, end_time, start_bid::text, current_bid::text, reserve_price::text, buy_it_now_available, created_at from auctions where auction_id = $1 and deleted_at is null",
+            "select auction_id, product_id, start_time, end_time, start_bid::text, current_bid::text, reserve_price::text, buy_it_now_available, buy_it_now_price::text, soft_close_minutes, proxy_bidding_enabled, status, winner_id, sold_out_at, created_at from auctions where auction_id = $1 and deleted_at is null",
             &[&auction_id],
         )
         .await
@@ -135,6 +178,9 @@ pub async fn get_auction_by_id(auction_id: i32, client: &Client) -> Option<Aucti
             let reserve_price: String = row.get("reserve_price");
             let reserve_price: f64 = reserve_price.parse().unwrap();
 
+            let buy_it_now_price: Option<String> = row.get("buy_it_now_price");
+            let buy_it_now_price: Option<f64> = buy_it_now_price.map(|p| p.parse().unwrap());
+
             Some(Auction {
                 auction_id: row.get("auction_id"),
                 product_id: row.get("product_id"),
@@ -144,6 +190,12 @@ pub async fn get_auction_by_id(auction_id: i32, client: &Client) -> Option<Aucti
                 current_bid,
                 reserve_price,
                 buy_it_now_available: row.get("buy_it_now_available"),
+                buy_it_now_price,
+                soft_close_minutes: row.get("soft_close_minutes"),
+                proxy_bidding_enabled: row.get("proxy_bidding_enabled"),
+                status: row.get("status"),
+                winner_id: row.get("winner_id"),
+                sold_out_at: row.get("sold_out_at"),
                 created_at: row.get("created_at"),
         })},
         Err(err) => {
@@ -156,20 +208,27 @@ pub async fn get_auction_by_id(auction_id: i32, client: &Client) -> Option<Aucti
 pub async fn update_auction(
     auction_id: i32,
     data: &AuctionRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
-    // product_id, start_time, end_time, start_bid, reserve_price, buy_it_now_available
-    let query = format!("update auctions set product_id = $1, start_time = '{}', end_time = '{}', start_bid = {}, reserve_price = {}, buy_it_now_available = $2 where auction_id = $3", data.start_time, data.end_time, data.start_bid, data.reserve_price);
+    // product_id, start_time, end_time, start_bid, reserve_price, buy_it_now_available, buy_it_now_price, soft_close_minutes, proxy_bidding_enabled
+    let query = format!("update auctions set product_id = $1, start_time = '{}', end_time = '{}', start_bid = {}, reserve_price = {}, buy_it_now_available = $2, buy_it_now_price = $3::float8, soft_close_minutes = $4, proxy_bidding_enabled = $5 where auction_id = $6", data.start_time, data.end_time, data.start_bid, data.reserve_price);
     client
         .execute(
             &query,
-            &[&data.product_id, &data.buy_it_now_available, &auction_id],
+            &[
+                &data.product_id,
+                &data.buy_it_now_available,
+                &data.buy_it_now_price,
+                &data.soft_close_minutes,
+                &data.proxy_bidding_enabled,
+                &auction_id,
+            ],
         )
         .await?;
     Ok(())
 }
 
-pub async fn delete_auction(auction_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_auction(auction_id: i32, client: &impl GenericClient) -> Result<(), Error> {
     client
         .execute(
             "update auctions set deleted_at = CURRENT_TIMESTAMP where auction_id = $1",
@@ -178,3 +237,968 @@ pub async fn delete_auction(auction_id: i32, client: &Client) -> Result<(), Erro
         .await?;
     Ok(())
 }
+
+#[derive(Deserialize)]
+pub struct BidRequest {
+    pub bid_amount: f64,
+    pub max_amount: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct Bid {
+    pub bid_id: i32,
+    pub auction_id: i32,
+    pub bidder_id: i32,
+    pub bidder_name: String,
+    pub bid_amount: f64,
+    pub bid_time: NaiveDateTime,
+}
+
+pub struct PlacedBid {
+    pub bid_id: i32,
+    pub auction_id: i32,
+    pub bidder_id: i32,
+    pub bid_amount: f64,
+    pub current_bid: f64,
+    pub leader_id: i32,
+    pub end_time: NaiveDateTime,
+    pub outbid_user_ids: Vec<i32>,
+}
+
+pub enum BidResult {
+    Accepted(PlacedBid),
+    AuctionNotFound,
+    NotActive,
+    NotStarted,
+    Ended,
+    OwnAuction,
+    TooLow(f64),
+    ProxyBiddingDisabled,
+    MaxAmountTooLow,
+}
+
+// Highest amount wins; equal amounts go to whoever reached it first
+const HIGHEST_BID_ORDER: &str = "bid_amount desc, bid_time asc, bid_id asc";
+
+pub fn get_minimum_bid(start_bid: f64, current_bid: f64, has_bids: bool) -> f64 {
+    if has_bids {
+        current_bid + get_auction_min_bid_increment()
+    } else {
+        start_bid
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyBid {
+    pub bidder_id: i32,
+    pub max_amount: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AutoBid {
+    pub bidder_id: i32,
+    pub amount: f64,
+}
+
+/// Works out the automatic bids the proxy engine places after a bid has been accepted.
+///
+/// `proxies` must be ordered by `max_amount` descending and then by creation time, so that
+/// an earlier maximum beats a later one of the same amount. The returned bids are in insert
+/// order; when the last two share an amount the first one inserted keeps the lead.
+pub fn resolve_proxy_bids(
+    current_bid: f64,
+    leader_id: i32,
+    proxies: &[ProxyBid],
+    increment: f64,
+) -> Vec<AutoBid> {
+    let top = match proxies.first() {
+        Some(p) => p,
+        None => return vec![],
+    };
+    let runner_up = proxies.get(1);
+
+    if top.bidder_id == leader_id {
+        // The leader only has to answer a challenger able to beat the current price
+        let challenger = match runner_up {
+            Some(p) if p.max_amount >= current_bid + increment => p,
+            _ => return vec![],
+        };
+        let amount = top.max_amount.min(challenger.max_amount + increment);
+        let leader_bid = AutoBid {
+            bidder_id: top.bidder_id,
+            amount,
+        };
+        let challenger_bid = AutoBid {
+            bidder_id: challenger.bidder_id,
+            amount: challenger.max_amount,
+        };
+        return if amount > challenger.max_amount {
+            vec![challenger_bid, leader_bid]
+        } else {
+            vec![leader_bid, challenger_bid]
+        };
+    }
+
+    if top.max_amount < current_bid + increment {
+        return vec![];
+    }
+
+    // The strongest opponent is either the runner-up's maximum or the standing bid
+    let mut auto_bids = vec![];
+    let opponent_amount = match runner_up {
+        Some(p) if p.max_amount > current_bid => {
+            let amount = top.max_amount.min(p.max_amount + increment);
+            let opponent_bid = AutoBid {
+                bidder_id: p.bidder_id,
+                amount: p.max_amount,
+            };
+            if amount > p.max_amount {
+                auto_bids.push(opponent_bid);
+                auto_bids.push(AutoBid {
+                    bidder_id: top.bidder_id,
+                    amount,
+                });
+            } else {
+                auto_bids.push(AutoBid {
+                    bidder_id: top.bidder_id,
+                    amount,
+                });
+                auto_bids.push(opponent_bid);
+            }
+            return auto_bids;
+        }
+        _ => current_bid,
+    };
+    auto_bids.push(AutoBid {
+        bidder_id: top.bidder_id,
+        amount: top.max_amount.min(opponent_amount + increment),
+    });
+    auto_bids
+}
+
+// A bid inside the soft-close window gives everyone else the same window to respond
+pub fn get_soft_close_end_time(
+    end_time: NaiveDateTime,
+    bid_time: NaiveDateTime,
+    soft_close_minutes: i32,
+) -> NaiveDateTime {
+    let extended_end_time = bid_time + Duration::minutes(soft_close_minutes.into());
+    if soft_close_minutes > 0 && end_time < extended_end_time {
+        extended_end_time
+    } else {
+        end_time
+    }
+}
+
+pub async fn place_bid(
+    auction_id: i32,
+    bidder_id: i32,
+    bid_amount: f64,
+    max_amount: Option<f64>,
+    client: &mut impl GenericClient,
+) -> Result<BidResult, Error> {
+    let transaction = client.transaction().await?;
+
+    // Lock the auction row so concurrent bids are validated one after another
+    let row = match transaction
+        .query_opt(
+            "select a.status, a.start_bid::text, a.current_bid::text, a.soft_close_minutes, a.proxy_bidding_enabled, coalesce(p.creator_id, 0) as creator_id, a.end_time, now()::timestamp as bid_time, now()::timestamp < a.start_time as not_started, now()::timestamp > a.end_time as ended, exists(select 1 from bids b where b.auction_id = a.auction_id and b.deleted_at is null) as has_bids from auctions a join products p on p.product_id = a.product_id where a.auction_id = $1 and a.deleted_at is null for update of a",
+            &[&auction_id],
+        )
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(BidResult::AuctionNotFound);
+        }
+    };
+
+    let status: String = row.get("status");
+    let creator_id: i32 = row.get("creator_id");
+    let not_started: bool = row.get("not_started");
+    let ended: bool = row.get("ended");
+    let has_bids: bool = row.get("has_bids");
+    let soft_close_minutes: i32 = row.get("soft_close_minutes");
+    let end_time: NaiveDateTime = row.get("end_time");
+    let bid_time: NaiveDateTime = row.get("bid_time");
+    let proxy_bidding_enabled: bool = row.get("proxy_bidding_enabled");
+
+    let start_bid: String = row.get("start_bid");
+    let start_bid: f64 = start_bid.parse().unwrap();
+
+    let current_bid: String = row.get("current_bid");
+    let current_bid: f64 = current_bid.parse().unwrap();
+
+    let result = if status != "active" {
+        Some(BidResult::NotActive)
+    } else if not_started {
+        Some(BidResult::NotStarted)
+    } else if ended {
+        Some(BidResult::Ended)
+    } else if creator_id == bidder_id {
+        Some(BidResult::OwnAuction)
+    } else if max_amount.is_some() && !proxy_bidding_enabled {
+        Some(BidResult::ProxyBiddingDisabled)
+    } else if max_amount.is_some_and(|m| m < bid_amount) {
+        Some(BidResult::MaxAmountTooLow)
+    } else {
+        let minimum_bid = get_minimum_bid(start_bid, current_bid, has_bids);
+        if bid_amount < minimum_bid {
+            Some(BidResult::TooLow(minimum_bid))
+        } else {
+            None
+        }
+    };
+    if let Some(r) = result {
+        transaction.rollback().await?;
+        return Ok(r);
+    }
+
+    let highest_bid_query = format!("select bidder_id, bid_amount::text from bids where auction_id = $1 and deleted_at is null order by {HIGHEST_BID_ORDER} limit 1");
+    let previous_leader_id: Option<i32> = transaction
+        .query_opt(&highest_bid_query, &[&auction_id])
+        .await?
+        .map(|row| row.get("bidder_id"));
+
+    let query = format!("insert into bids (auction_id, bidder_id, bid_amount) values ($1, $2, {bid_amount}) returning bid_id");
+    let row = transaction
+        .query_one(&query, &[&auction_id, &bidder_id])
+        .await?;
+    let bid_id: i32 = row.get("bid_id");
+
+    if let Some(max) = max_amount {
+        let query = format!("insert into proxy_bids (auction_id, bidder_id, max_amount) values ($1, $2, {max}) on conflict (auction_id, bidder_id) where deleted_at is null do update set max_amount = excluded.max_amount, updated_at = CURRENT_TIMESTAMP");
+        transaction
+            .execute(&query, &[&auction_id, &bidder_id])
+            .await?;
+    }
+
+    if proxy_bidding_enabled {
+        let proxies: Vec<ProxyBid> = transaction
+            .query(
+                "select bidder_id, max_amount::text from proxy_bids where auction_id = $1 and deleted_at is null order by max_amount desc, created_at asc, proxy_bid_id asc",
+                &[&auction_id],
+            )
+            .await?
+            .iter()
+            .map(|row| {
+                let max_amount: String = row.get("max_amount");
+                ProxyBid {
+                    bidder_id: row.get("bidder_id"),
+                    max_amount: max_amount.parse().unwrap(),
+                }
+            })
+            .collect();
+        let auto_bids = resolve_proxy_bids(
+            bid_amount,
+            bidder_id,
+            &proxies,
+            get_auction_min_bid_increment(),
+        );
+        for auto_bid in &auto_bids {
+            let query = format!(
+                "insert into bids (auction_id, bidder_id, bid_amount) values ($1, $2, {})",
+                auto_bid.amount
+            );
+            transaction
+                .execute(&query, &[&auction_id, &auto_bid.bidder_id])
+                .await?;
+        }
+    }
+
+    let row = transaction
+        .query_one(&highest_bid_query, &[&auction_id])
+        .await?;
+    let leader_id: i32 = row.get("bidder_id");
+    let highest_bid: String = row.get("bid_amount");
+    let highest_bid: f64 = highest_bid.parse().unwrap();
+
+    // An extended auction may announce its closing again
+    let end_time = get_soft_close_end_time(end_time, bid_time, soft_close_minutes);
+    let query = format!("update auctions set current_bid = {highest_bid}, closing_soon_notified_at = case when end_time <> $2 then null else closing_soon_notified_at end, end_time = $2, updated_at = CURRENT_TIMESTAMP where auction_id = $1");
+    transaction
+        .execute(&query, &[&auction_id, &end_time])
+        .await?;
+
+    transaction.commit().await?;
+
+    let mut outbid_user_ids: Vec<i32> = vec![];
+    for user_id in [previous_leader_id, Some(bidder_id)].into_iter().flatten() {
+        if user_id != leader_id && !outbid_user_ids.contains(&user_id) {
+            outbid_user_ids.push(user_id);
+        }
+    }
+
+    Ok(BidResult::Accepted(PlacedBid {
+        bid_id,
+        auction_id,
+        bidder_id,
+        bid_amount,
+        current_bid: highest_bid,
+        leader_id,
+        end_time,
+        outbid_user_ids,
+    }))
+}
+
+// Bidders of the auction plus the seller, i.e. everyone watching the price
+async fn get_auction_watcher_ids(
+    auction_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<i32>, Error> {
+    Ok(client
+        .query(
+            "select bidder_id as user_id from bids where auction_id = $1 and deleted_at is null union select p.creator_id from auctions a join products p on p.product_id = a.product_id where a.auction_id = $1 and p.creator_id is not null",
+            &[&auction_id],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get("user_id"))
+        .collect())
+}
+
+pub async fn broadcast_bid(bid: &PlacedBid, client: &impl GenericClient) -> Result<(), Error> {
+    let rooms = get_auction_watcher_ids(bid.auction_id, client).await?;
+
+    let mut payload: HashMap<String, Value> = HashMap::new();
+    payload.insert(
+        "auction_id".to_string(),
+        Value::Number(bid.auction_id.into()),
+    );
+    payload.insert("bid_id".to_string(), Value::Number(bid.bid_id.into()));
+    payload.insert("bidder_id".to_string(), Value::Number(bid.bidder_id.into()));
+    if let Some(amount) = serde_json::Number::from_f64(bid.bid_amount) {
+        payload.insert("bid_amount".to_string(), Value::Number(amount));
+    }
+    payload.insert("leader_id".to_string(), Value::Number(bid.leader_id.into()));
+    if let Some(amount) = serde_json::Number::from_f64(bid.current_bid) {
+        payload.insert("current_bid".to_string(), Value::Number(amount));
+    }
+    payload.insert(
+        "end_time".to_string(),
+        Value::String(bid.end_time.to_string()),
+    );
+    if let Err(err) = socketio::emit("auction-bid", &rooms, Some(payload)).await {
+        println!("{:?}", err);
+    }
+
+    let mut data = HashMap::new();
+    data.insert(
+        "redirect".to_string(),
+        Value::String("auction-detail".to_string()),
+    );
+    data.insert("id".to_string(), Value::Number(bid.auction_id.into()));
+
+    let title = format!("Outbid on Auction #{}", bid.auction_id);
+    let body = format!(
+        "Someone placed a higher bid of {}. Bid again to stay in the lead!",
+        bid.current_bid
+    );
+    for outbid_user_id in &bid.outbid_user_ids {
+        for fcm_token in &get_fcm_tokens(*outbid_user_id, client).await? {
+            if let Err(err) = send_notification(&title, &body, fcm_token, Some(data.clone())).await
+            {
+                println!("{:?}", err);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn notify_closing_soon_auctions(client: &impl GenericClient) -> Result<(), Error> {
+    let minutes = get_auction_closing_soon_minutes();
+    let rows = client
+        .query(
+            "update auctions set closing_soon_notified_at = CURRENT_TIMESTAMP where status = 'active' and deleted_at is null and closing_soon_notified_at is null and end_time > now()::timestamp and end_time <= now()::timestamp + make_interval(mins => $1) returning auction_id, end_time",
+            &[&minutes],
+        )
+        .await?;
+    for row in &rows {
+        let auction_id: i32 = row.get("auction_id");
+        let end_time: NaiveDateTime = row.get("end_time");
+        let rooms = get_auction_watcher_ids(auction_id, client).await?;
+
+        let mut payload: HashMap<String, Value> = HashMap::new();
+        payload.insert("auction_id".to_string(), Value::Number(auction_id.into()));
+        payload.insert("end_time".to_string(), Value::String(end_time.to_string()));
+        payload.insert("minutes_left".to_string(), Value::Number(minutes.into()));
+        if let Err(err) = socketio::emit("auction-closing-soon", &rooms, Some(payload)).await {
+            println!("{:?}", err);
+        }
+    }
+    Ok(())
+}
+
+pub async fn get_bids(
+    auction_id: i32,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    client: &impl GenericClient,
+) -> Result<PaginationResult<Bid>, Error> {
+    let base_query = "from bids b inner join users u on u.user_id = b.bidder_id where b.deleted_at is null and b.auction_id = $1".to_string();
+    let params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(auction_id)];
+
+    let order_options = "b.bid_amount desc, b.bid_time asc, b.bid_id asc";
+
+    let result = generate_pagination_query(PaginationOptions {
+        select_columns: "b.bid_id, b.auction_id, b.bidder_id, u.name bidder_name, b.bid_amount::text, b.bid_time",
+        base_query: &base_query,
+        search_columns: vec![],
+        search: None,
+        order_options: Some(order_options),
+        page,
+        per_page,
+    });
+
+    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
+
+    let row = client.query_one(&result.count_query, &params_slice).await?;
+    let total: i64 = row.get("total");
+
+    let mut page_counts = 0;
+    let mut current_page = 0;
+    let mut limit = 0;
+    if let (Some(p), Some(pp)) = (page, per_page) {
+        current_page = p;
+        limit = pp;
+        page_counts = (total as f64 / limit as f64).ceil() as usize;
+    }
+
+    let bids: Vec<Bid> = client
+        .query(&result.query, &params_slice)
+        .await?
+        .iter()
+        .map(|row| {
+            let bid_amount: String = row.get("bid_amount");
+            let bid_amount: f64 = bid_amount.parse().unwrap();
+
+            Bid {
+                bid_id: row.get("bid_id"),
+                auction_id: row.get("auction_id"),
+                bidder_id: row.get("bidder_id"),
+                bidder_name: row.get("bidder_name"),
+                bid_amount,
+                bid_time: row.get("bid_time"),
+            }
+        })
+        .collect();
+
+    Ok(PaginationResult {
+        data: bids,
+        total,
+        page: current_page,
+        per_page: limit,
+        page_counts,
+    })
+}
+
+#[derive(Serialize)]
+pub struct AuctionCloseResult {
+    pub auction_id: i32,
+    pub status: String,
+    pub winner_id: Option<i32>,
+    pub winning_bid: Option<f64>,
+    pub order_id: Option<i32>,
+    pub is_buy_it_now: bool,
+}
+
+pub async fn close_auction(
+    auction_id: i32,
+    client: &mut impl GenericClient,
+) -> Result<Option<AuctionCloseResult>, Error> {
+    let transaction = client.transaction().await?;
+
+    let row = match transaction
+        .query_opt(
+            "select product_id, reserve_price::text from auctions where auction_id = $1 and status = 'active' and deleted_at is null for update",
+            &[&auction_id],
+        )
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(None);
+        }
+    };
+    let product_id: i32 = row.get("product_id");
+    let reserve_price: String = row.get("reserve_price");
+    let reserve_price: f64 = reserve_price.parse().unwrap();
+
+    let highest_bid = transaction
+        .query_opt(
+            &format!("select bidder_id, bid_amount::text from bids where auction_id = $1 and deleted_at is null order by {HIGHEST_BID_ORDER} limit 1"),
+            &[&auction_id],
+        )
+        .await?;
+
+    let (status, winner_id, winning_bid) = match highest_bid {
+        Some(row) => {
+            let bid_amount: String = row.get("bid_amount");
+            let bid_amount: f64 = bid_amount.parse().unwrap();
+            if bid_amount >= reserve_price {
+                let bidder_id: i32 = row.get("bidder_id");
+                ("completed", Some(bidder_id), Some(bid_amount))
+            } else {
+                ("unsold", None, None)
+            }
+        }
+        None => ("unsold", None, None),
+    };
+
+    transaction
+        .execute(
+            "update auctions set status = $1, winner_id = $2, sold_out_at = case when $2::int is null then null else CURRENT_TIMESTAMP end, updated_at = CURRENT_TIMESTAMP where auction_id = $3",
+            &[&status, &winner_id, &auction_id],
+        )
+        .await?;
+
+    // The auction only counts as closed once the winner has an order, otherwise it stays
+    // active and is picked up again by the next run of the closer
+    let order_id = match (winner_id, winning_bid) {
+        (Some(winner_id), Some(winning_bid)) => Some(
+            add_winner_order(auction_id, product_id, winner_id, winning_bid, &transaction).await?,
+        ),
+        _ => None,
+    };
+
+    transaction.commit().await?;
+    Ok(Some(AuctionCloseResult {
+        auction_id,
+        status: status.to_string(),
+        winner_id,
+        winning_bid,
+        order_id,
+        is_buy_it_now: false,
+    }))
+}
+
+pub enum BuyItNowResult {
+    Purchased(AuctionCloseResult),
+    AuctionNotFound,
+    NotActive,
+    NotStarted,
+    Ended,
+    OwnAuction,
+    Unavailable,
+}
+
+pub async fn buy_it_now(
+    auction_id: i32,
+    buyer_id: i32,
+    client: &mut impl GenericClient,
+) -> Result<BuyItNowResult, Error> {
+    let transaction = client.transaction().await?;
+
+    let row = match transaction
+        .query_opt(
+            "select a.product_id, a.status, a.buy_it_now_available, a.buy_it_now_price::text, a.current_bid::text, coalesce(p.creator_id, 0) as creator_id, now()::timestamp < a.start_time as not_started, now()::timestamp > a.end_time as ended from auctions a join products p on p.product_id = a.product_id where a.auction_id = $1 and a.deleted_at is null for update of a",
+            &[&auction_id],
+        )
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(BuyItNowResult::AuctionNotFound);
+        }
+    };
+
+    let product_id: i32 = row.get("product_id");
+    let status: String = row.get("status");
+    let buy_it_now_available: bool = row.get("buy_it_now_available");
+    let creator_id: i32 = row.get("creator_id");
+    let not_started: bool = row.get("not_started");
+    let ended: bool = row.get("ended");
+
+    let buy_it_now_price: Option<String> = row.get("buy_it_now_price");
+    let buy_it_now_price: Option<f64> = buy_it_now_price.map(|p| p.parse().unwrap());
+
+    let current_bid: String = row.get("current_bid");
+    let current_bid: f64 = current_bid.parse().unwrap();
+
+    let rejection = if status != "active" {
+        Some(BuyItNowResult::NotActive)
+    } else if not_started {
+        Some(BuyItNowResult::NotStarted)
+    } else if ended {
+        Some(BuyItNowResult::Ended)
+    } else if creator_id == buyer_id {
+        Some(BuyItNowResult::OwnAuction)
+    } else {
+        None
+    };
+    if let Some(r) = rejection {
+        transaction.rollback().await?;
+        return Ok(r);
+    }
+
+    // Buy It Now is withdrawn once bidding has reached the buy-now price
+    let price = match buy_it_now_price {
+        Some(p) if buy_it_now_available && p > 0.0 && current_bid < p => p,
+        _ => {
+            transaction.rollback().await?;
+            return Ok(BuyItNowResult::Unavailable);
+        }
+    };
+
+    let query = format!("update auctions set status = 'completed', winner_id = $1, current_bid = {price}, sold_out_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP where auction_id = $2");
+    transaction
+        .execute(&query, &[&buyer_id, &auction_id])
+        .await?;
+
+    transaction
+        .execute(
+            "update bids set payment_status = 'cancelled', updated_at = CURRENT_TIMESTAMP where auction_id = $1 and deleted_at is null",
+            &[&auction_id],
+        )
+        .await?;
+    transaction
+        .execute(
+            "update proxy_bids set deleted_at = CURRENT_TIMESTAMP where auction_id = $1 and deleted_at is null",
+            &[&auction_id],
+        )
+        .await?;
+
+    let order_id = add_winner_order(auction_id, product_id, buyer_id, price, &transaction).await?;
+
+    transaction.commit().await?;
+    Ok(BuyItNowResult::Purchased(AuctionCloseResult {
+        auction_id,
+        status: "completed".to_string(),
+        winner_id: Some(buyer_id),
+        winning_bid: Some(price),
+        order_id: Some(order_id),
+        is_buy_it_now: true,
+    }))
+}
+
+pub async fn has_auction_ended(auction_id: i32, client: &impl GenericClient) -> bool {
+    match client
+        .query_one(
+            "select end_time <= now()::timestamp as ended from auctions where auction_id = $1",
+            &[&auction_id],
+        )
+        .await
+    {
+        Ok(row) => row.get("ended"),
+        Err(err) => {
+            println!("{:?}", err);
+            false
+        }
+    }
+}
+
+pub async fn get_expired_auction_ids(client: &impl GenericClient) -> Result<Vec<i32>, Error> {
+    Ok(client
+        .query(
+            "select auction_id from auctions where status = 'active' and deleted_at is null and end_time <= now()::timestamp",
+            &[],
+        )
+        .await?
+        .iter()
+        .map(|row| row.get("auction_id"))
+        .collect())
+}
+
+// Runs inside the transaction that closes the auction
+async fn add_winner_order(
+    auction_id: i32,
+    product_id: i32,
+    winner_id: i32,
+    winning_bid: f64,
+    client: &impl GenericClient,
+) -> Result<i32, Error> {
+    let address = match address::get_address(winner_id, client).await {
+        Ok(Some(a)) => NewAddress {
+            street_address: a.street_address,
+            city: a.city,
+            state: a.state,
+            postal_code: a.postal_code,
+            country: a.country,
+            township: a.township,
+            ward: a.ward,
+            home_address: a.home_address,
+            note: String::new(),
+        },
+        _ => NewAddress {
+            street_address: String::new(),
+            city: String::new(),
+            state: String::new(),
+            postal_code: String::new(),
+            country: String::new(),
+            township: String::new(),
+            ward: String::new(),
+            home_address: String::new(),
+            note: String::new(),
+        },
+    };
+    let new_order = NewOrder {
+        order_items: vec![NewOrderItem {
+            product_id,
+            quantity: 1,
+            price: Some(winning_bid),
+        }],
+        address,
+        payment_type: "Cash on Delivery".to_string(),
+        payslip_screenshot_path: String::new(),
+        rule_id: None,
+        shop_id: None,
+        coupon_code: None,
+    };
+
+    let currency_id =
+        order::are_items_same_currency_and_get_currency_id(&new_order.order_items, client)
+            .await
+            .unwrap_or(1);
+
+    let order_id = order::add_order(&new_order, winner_id, currency_id, client).await?;
+    // The item is sold either way, so a short stock count must not cost the winner the order
+    if !order::update_stocks(&new_order.order_items, order_id, client).await? {
+        println!("Insufficient stock for auctioned product #{product_id}");
+    }
+    client
+        .execute(
+            "update auctions set order_id = $1 where auction_id = $2",
+            &[&order_id, &auction_id],
+        )
+        .await?;
+    Ok(order_id)
+}
+
+pub async fn finalize_auction(
+    auction_id: i32,
+    client: &mut impl GenericClient,
+) -> Result<Option<AuctionCloseResult>, Box<dyn std::error::Error>> {
+    match close_auction(auction_id, client).await? {
+        Some(result) => {
+            // The auction and the winner's order are already committed at this point
+            if let Err(err) = settle_auction(&result, client).await {
+                println!("Error settling auction #{auction_id}: {:?}", err);
+            }
+            Ok(Some(result))
+        }
+        None => Ok(None),
+    }
+}
+
+// Sends the winner the invoice for the order created when the auction closed and tells the
+// winner and the seller how the auction ended
+pub async fn settle_auction(
+    result: &AuctionCloseResult,
+    client: &impl GenericClient,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let auction_id = result.auction_id;
+    let row = client
+        .query_one(
+            "select coalesce(p.creator_id, 0) as creator_id, b.name brand_name, p.model from auctions a join products p on p.product_id = a.product_id join brands b on b.brand_id = p.brand_id where a.auction_id = $1",
+            &[&auction_id],
+        )
+        .await?;
+    let creator_id: i32 = row.get("creator_id");
+    let brand_name: &str = row.get("brand_name");
+    let model: &str = row.get("model");
+    let product_name = format!("{brand_name} {model}");
+
+    let mut payload: HashMap<String, Value> = HashMap::new();
+    payload.insert("auction_id".to_string(), Value::Number(auction_id.into()));
+    payload.insert("status".to_string(), Value::String(result.status.clone()));
+
+    match (result.winner_id, result.winning_bid, result.order_id) {
+        (Some(winner_id), Some(winning_bid), Some(order_id)) => {
+            if let Err(err) = invoice::export_invoice(order_id, winner_id, client).await {
+                println!("{:?}", err);
+            }
+
+            let mut map = HashMap::new();
+            map.insert(
+                "redirect".to_string(),
+                Value::String("order-detail".to_string()),
+            );
+            map.insert("id".to_string(), Value::Number(order_id.into()));
+
+            let (title, message) = if result.is_buy_it_now {
+                (
+                    format!("Auction #{auction_id} Purchased"),
+                    format!("You bought {product_name} for {winning_bid} with Buy It Now. Your order #{order_id} has been placed."),
+                )
+            } else {
+                (
+                    format!("Auction #{auction_id} Won"),
+                    format!("Congratulations! You won the auction for {product_name} with a bid of {winning_bid}. Your order #{order_id} has been placed."),
+                )
+            };
+            if let Err(err) = notification::add_notification(
+                winner_id,
+                &title,
+                &message,
+                &Some(map.clone()),
+                client,
+            )
+            .await
+            {
+                println!("Error adding notification: {:?}", err);
+            }
+
+            if creator_id > 0 {
+                let title = format!("Auction #{auction_id} Sold");
+                let message = if result.is_buy_it_now {
+                    format!("Your auction for {product_name} was bought with Buy It Now for {winning_bid}. Please review and process order #{order_id}.")
+                } else {
+                    format!("Your auction for {product_name} has ended with a winning bid of {winning_bid}. Please review and process order #{order_id}.")
+                };
+                if let Err(err) =
+                    notification::add_notification(creator_id, &title, &message, &Some(map), client)
+                        .await
+                {
+                    println!("Error adding notification: {:?}", err);
+                }
+            }
+
+            payload.insert("winner_id".to_string(), Value::Number(winner_id.into()));
+            payload.insert("order_id".to_string(), Value::Number(order_id.into()));
+            if let Some(bid) = serde_json::Number::from_f64(winning_bid) {
+                payload.insert("winning_bid".to_string(), Value::Number(bid));
+            }
+            if let Err(err) = socketio::emit(
+                "auction-closed",
+                &vec![winner_id, creator_id],
+                Some(payload),
+            )
+            .await
+            {
+                println!("{:?}", err);
+            }
+        }
+        _ => {
+            if creator_id > 0 {
+                let title = format!("Auction #{auction_id} Unsold");
+                let message = format!(
+                    "Your auction for {product_name} has ended without meeting the reserve price."
+                );
+                if let Err(err) =
+                    notification::add_notification(creator_id, &title, &message, &None, client)
+                        .await
+                {
+                    println!("Error adding notification: {:?}", err);
+                }
+                if let Err(err) =
+                    socketio::emit("auction-closed", &vec![creator_id], Some(payload)).await
+                {
+                    println!("{:?}", err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn finalize_expired_auctions(client: &mut impl GenericClient) {
+    let auction_ids = match get_expired_auction_ids(client).await {
+        Ok(ids) => ids,
+        Err(err) => {
+            println!("Error retrieving expired auctions: {:?}", err);
+            return;
+        }
+    };
+    for auction_id in auction_ids {
+        match finalize_auction(auction_id, client).await {
+            Ok(Some(result)) => {
+                println!("Auction #{auction_id} closed as {}.", result.status)
+            }
+            Ok(None) => {}
+            Err(err) => {
+                println!("Error closing auction #{auction_id}: {:?}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn proxy(bidder_id: i32, max_amount: f64) -> ProxyBid {
+        ProxyBid {
+            bidder_id,
+            max_amount,
+        }
+    }
+
+    fn auto_bid(bidder_id: i32, amount: f64) -> AutoBid {
+        AutoBid { bidder_id, amount }
+    }
+
+    fn at(hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn proxy_leader_answers_a_new_challenger() {
+        let bids = resolve_proxy_bids(50.0, 2, &[proxy(1, 100.0)], 5.0);
+        assert_eq!(bids, vec![auto_bid(1, 55.0)]);
+    }
+
+    #[test]
+    fn proxy_leader_outbids_a_challenger_proxy() {
+        let bids = resolve_proxy_bids(60.0, 2, &[proxy(1, 100.0), proxy(2, 80.0)], 5.0);
+        assert_eq!(bids, vec![auto_bid(2, 80.0), auto_bid(1, 85.0)]);
+    }
+
+    #[test]
+    fn proxy_leader_stops_at_its_maximum() {
+        let bids = resolve_proxy_bids(60.0, 2, &[proxy(1, 70.0), proxy(2, 68.0)], 5.0);
+        assert_eq!(bids, vec![auto_bid(2, 68.0), auto_bid(1, 70.0)]);
+    }
+
+    #[test]
+    fn leading_proxy_answers_a_challenger_proxy() {
+        let bids = resolve_proxy_bids(60.0, 1, &[proxy(1, 100.0), proxy(2, 80.0)], 5.0);
+        assert_eq!(bids, vec![auto_bid(2, 80.0), auto_bid(1, 85.0)]);
+    }
+
+    #[test]
+    fn equal_maximums_go_to_the_earlier_proxy() {
+        let bids = resolve_proxy_bids(60.0, 2, &[proxy(1, 100.0), proxy(2, 100.0)], 5.0);
+        // Both bids share an amount, the one inserted first keeps the lead
+        assert_eq!(bids, vec![auto_bid(1, 100.0), auto_bid(2, 100.0)]);
+    }
+
+    #[test]
+    fn maximum_below_the_next_increment_places_nothing() {
+        assert!(resolve_proxy_bids(98.0, 2, &[proxy(1, 100.0)], 5.0).is_empty());
+    }
+
+    #[test]
+    fn leader_ignores_a_challenger_below_the_next_increment() {
+        let bids = resolve_proxy_bids(98.0, 1, &[proxy(1, 150.0), proxy(2, 100.0)], 5.0);
+        assert!(bids.is_empty());
+    }
+
+    #[test]
+    fn no_proxies_places_nothing() {
+        assert!(resolve_proxy_bids(50.0, 1, &[], 5.0).is_empty());
+    }
+
+    #[test]
+    fn bid_inside_soft_close_window_extends_end_time() {
+        assert_eq!(get_soft_close_end_time(at(12, 0), at(11, 58), 5), at(12, 3));
+    }
+
+    #[test]
+    fn bid_before_soft_close_window_keeps_end_time() {
+        assert_eq!(get_soft_close_end_time(at(12, 0), at(11, 50), 5), at(12, 0));
+    }
+
+    #[test]
+    fn soft_close_disabled_keeps_end_time() {
+        assert_eq!(get_soft_close_end_time(at(12, 0), at(11, 59), 0), at(12, 0));
+    }
+}
@@ -0,0 +1,115 @@
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
+
+use crate::utils::setting::{
+    get_account_lockout_after_failures, get_ip_lockout_after_failures,
+    get_login_backoff_after_failures, get_login_backoff_base_seconds, get_login_lockout_minutes,
+};
+
+pub enum ThrottleKey {
+    Ip(String),
+    // Keyed on the submitted username whether or not it exists, so a lockout does not
+    // tell which usernames are taken
+    Account(String),
+}
+
+impl ThrottleKey {
+    fn key(&self) -> String {
+        match self {
+            ThrottleKey::Ip(ip) => format!("ip:{}", ip),
+            ThrottleKey::Account(username) => format!("account:{}", username.to_lowercase()),
+        }
+    }
+
+    fn lockout_after(&self) -> i32 {
+        match self {
+            ThrottleKey::Ip(_) => get_ip_lockout_after_failures(),
+            ThrottleKey::Account(_) => get_account_lockout_after_failures(),
+        }
+    }
+}
+
+pub struct FailedAttempt {
+    pub failures: i32,
+    // True only for the failure that started the lockout, later ones while it lasts are false
+    pub locked_out: bool,
+}
+
+// Seconds until every given key is free again, 0 when none of them is locked
+pub async fn get_locked_seconds(
+    scope: &str,
+    keys: &[ThrottleKey],
+    client: &impl GenericClient,
+) -> Result<i64, Error> {
+    let keys: Vec<String> = keys.iter().map(|key| key.key()).collect();
+    let row = client
+        .query_one(
+            "select coalesce(max(ceil(extract(epoch from locked_until - CURRENT_TIMESTAMP))), 0)::bigint as seconds from auth_throttles where scope = $1 and throttle_key = any($2) and locked_until > CURRENT_TIMESTAMP",
+            &[&scope, &keys],
+        )
+        .await?;
+    Ok(row.get("seconds"))
+}
+
+fn get_lock_seconds(failures: i32, lockout_after: i32) -> i64 {
+    let lockout_seconds = get_login_lockout_minutes() * 60;
+    let backoff_after = get_login_backoff_after_failures();
+    if failures >= lockout_after {
+        lockout_seconds
+    } else if failures >= backoff_after {
+        let exponent = (failures - backoff_after).min(30) as u32;
+        get_login_backoff_base_seconds()
+            .saturating_mul(2_i64.pow(exponent))
+            .min(lockout_seconds)
+    } else {
+        0
+    }
+}
+
+// Counts a failure and locks the key for a doubling wait, or for the full lockout once
+// the limit is reached. Failures older than the lockout period are forgotten.
+pub async fn record_failure(
+    scope: &str,
+    key: &ThrottleKey,
+    client: &impl GenericClient,
+) -> Result<FailedAttempt, Error> {
+    let row = client
+        .query_one(
+            "insert into auth_throttles (scope, throttle_key, failures, last_failure_at) values ($1, $2, 1, CURRENT_TIMESTAMP)
+            on conflict (scope, throttle_key) do update set
+            failures = case when auth_throttles.last_failure_at < CURRENT_TIMESTAMP - make_interval(mins => $3) then 1 else auth_throttles.failures + 1 end,
+            last_failure_at = CURRENT_TIMESTAMP
+            returning failures",
+            &[&scope, &key.key(), &(get_login_lockout_minutes() as i32)],
+        )
+        .await?;
+    let failures: i32 = row.get("failures");
+    let lockout_after = key.lockout_after();
+    let lock_seconds = get_lock_seconds(failures, lockout_after);
+    if lock_seconds > 0 {
+        client
+            .execute(
+                "update auth_throttles set locked_until = CURRENT_TIMESTAMP + make_interval(secs => $3) where scope = $1 and throttle_key = $2",
+                &[&scope, &key.key(), &(lock_seconds as f64)],
+            )
+            .await?;
+    }
+    Ok(FailedAttempt {
+        failures,
+        locked_out: failures == lockout_after,
+    })
+}
+
+pub async fn clear_failures(
+    scope: &str,
+    key: &ThrottleKey,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "delete from auth_throttles where scope = $1 and throttle_key = $2",
+            &[&scope, &key.key()],
+        )
+        .await?;
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use std::{fs, path::Path};
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -29,7 +30,7 @@ pub async fn get_bank_accounts(
     shop_id: Option<i32>,
     role: &str,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<BankAccount>, Error> {
     let mut base_query = "from bank_accounts where deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -113,7 +114,10 @@ pub struct BankAccountRequest {
     pub shop_id: Option<i32>,
 }
 
-pub async fn add_bank_account(data: &BankAccountRequest, client: &Client) -> Result<(), Error> {
+pub async fn add_bank_account(
+    data: &BankAccountRequest,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     client
         .execute(
             "insert into bank_accounts (account_type, account_holder_name, account_number, bank_logo, shop_id) values ($1, $2, $3, $4, $5)",
@@ -123,7 +127,10 @@ pub async fn add_bank_account(data: &BankAccountRequest, client: &Client) -> Res
     Ok(())
 }
 
-pub async fn get_bank_account_by_id(account_id: i32, client: &Client) -> Option<BankAccount> {
+pub async fn get_bank_account_by_id(
+    account_id: i32,
+    client: &impl GenericClient,
+) -> Option<BankAccount> {
     let result = client
         .query_one(
             "select b.account_id, b.account_type, b.account_holder_name, b.account_number, b.bank_logo, b.created_at, b.shop_id, s.name shop_name from bank_accounts b left join shops s on s.shop_id = b.shop_id where b.deleted_at is null and b.account_id = $1",
@@ -150,7 +157,7 @@ pub async fn update_bank_account(
     account_id: i32,
     old_bank_logo: &str,
     data: &BankAccountRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -191,7 +198,7 @@ pub async fn update_bank_account(
 pub async fn delete_bank_account(
     account_id: i32,
     old_bank_logo: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -219,7 +226,7 @@ pub async fn delete_bank_account(
     Ok(())
 }
 
-pub async fn get_bank_logos(client: &Client) -> Vec<String> {
+pub async fn get_bank_logos(client: &impl GenericClient) -> Vec<String> {
     match client
         .query("select bank_logo from bank_accounts", &[])
         .await
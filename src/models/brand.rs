@@ -1,8 +1,9 @@
 use std::{fs, path::Path};
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -28,7 +29,7 @@ pub async fn get_brands(
     platform: &str,
     user_id: i32,
     version: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Brand>, Error> {
     let mut base_query = "from brands where deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -112,7 +113,7 @@ pub async fn add_brand(
     logo_url: &str,
     creator_id: i32,
     level: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     // Insert the new brands into the database
     client
@@ -131,7 +132,7 @@ pub async fn update_brand(
     logo_url: &str,
     old_logo_url: &str,
     level: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client.execute(
             "update brands set name = $1, description = $2, logo_url = $3, level = $4 where brand_id = $5 and deleted_at is null",
@@ -159,7 +160,7 @@ pub async fn update_brand(
     Ok(())
 }
 
-pub async fn get_brand_by_id(brand_id: i32, client: &Client) -> Option<Brand> {
+pub async fn get_brand_by_id(brand_id: i32, client: &impl GenericClient) -> Option<Brand> {
     let result = client
         .query_one(
             "select brand_id, name, description, logo_url, level, created_at from brands where brand_id = $1 and deleted_at is null",
@@ -180,7 +181,11 @@ pub async fn get_brand_by_id(brand_id: i32, client: &Client) -> Option<Brand> {
     }
 }
 
-pub async fn delete_brand(brand_id: i32, old_logo_url: &str, client: &Client) -> Result<(), Error> {
+pub async fn delete_brand(
+    brand_id: i32,
+    old_logo_url: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     client.execute(
         "update brands set deleted_at = CURRENT_TIMESTAMP where brand_id = $1 and deleted_at is null",
         &[&brand_id],
@@ -205,7 +210,7 @@ pub async fn delete_brand(brand_id: i32, old_logo_url: &str, client: &Client) ->
     Ok(())
 }
 
-pub async fn get_logo_urls(client: &Client) -> Vec<String> {
+pub async fn get_logo_urls(client: &impl GenericClient) -> Vec<String> {
     match client.query("select logo_url from brands", &[]).await {
         Ok(rows) => rows.iter().map(|row| row.get("logo_url")).collect(),
         Err(err) => {
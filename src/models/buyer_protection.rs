@@ -1,6 +1,7 @@
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -18,7 +19,7 @@ pub async fn get_buyer_protections(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<BuyerProtection>, Error> {
     let base_query = "from buyer_protections where deleted_at is null".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -73,7 +74,7 @@ pub struct BuyerProtectionRequest {
 
 pub async fn add_buyer_protection(
     data: &BuyerProtectionRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -86,7 +87,7 @@ pub async fn add_buyer_protection(
 
 pub async fn get_buyer_protection_by_id(
     buyer_protection_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Option<BuyerProtection> {
     let result = client
         .query_one(
@@ -108,7 +109,7 @@ pub async fn get_buyer_protection_by_id(
 pub async fn update_buyer_protection(
     buyer_protection_id: i32,
     data: &BuyerProtectionRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -121,7 +122,7 @@ pub async fn update_buyer_protection(
 
 pub async fn delete_buyer_protection(
     buyer_protection_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -1,6 +1,7 @@
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
-pub async fn get_case_materials(client: &Client) -> Result<Vec<String>, Error> {
+pub async fn get_case_materials(client: &impl GenericClient) -> Result<Vec<String>, Error> {
     let rows = client
         .query(
             "select description from case_materials where deleted_at is null order by description",
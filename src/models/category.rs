@@ -1,8 +1,9 @@
 use std::{fs, path::Path};
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -23,7 +24,7 @@ pub async fn get_categories(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Category>, Error> {
     let base_query = "from categories where deleted_at is null".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -104,7 +105,7 @@ pub async fn add_category(
     data: &CategoryRequest,
     creator_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let level = if let Some(l) = data.level {
         if role == "admin" {
@@ -124,7 +125,7 @@ pub async fn add_category(
     Ok(())
 }
 
-pub async fn get_category_by_id(category_id: i32, client: &Client) -> Option<Category> {
+pub async fn get_category_by_id(category_id: i32, client: &impl GenericClient) -> Option<Category> {
     let result = client
         .query_one(
             "select category_id, name, description, cover_image, level, created_at from categories where deleted_at is null and category_id = $1",
@@ -151,7 +152,7 @@ pub async fn update_category(
     data: &CategoryRequest,
     old_level: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let level = if let Some(l) = data.level {
         if role == "admin" {
@@ -200,7 +201,7 @@ pub async fn update_category(
 pub async fn delete_category(
     category_id: i32,
     old_cover_image: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -228,7 +229,7 @@ pub async fn delete_category(
     Ok(())
 }
 
-pub async fn get_cover_images(client: &Client) -> Vec<String> {
+pub async fn get_cover_images(client: &impl GenericClient) -> Vec<String> {
     match client
         .query("select cover_image from categories", &[])
         .await
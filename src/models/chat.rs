@@ -4,9 +4,10 @@ use std::{
 };
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -29,7 +30,7 @@ pub async fn add_message(
     data: &MessageRequest,
     sender_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(i32, i32), Error> {
     let mut chat_id = data.chat_id;
     let row = client
@@ -228,7 +229,7 @@ pub async fn get_chat_sessions(
     per_page: Option<usize>,
     user_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<ChatSession>, Error> {
     let mut base_query = "from chats c join (select message_text, created_at, sender_id, chat_id, status from messages where deleted_at is null and created_at in (select max(created_at) from messages where deleted_at is null group by chat_id)) as m on m.chat_id = c.chat_id join users u on m.sender_id = u.user_id left join chat_deletes cd on c.chat_id = cd.chat_id and cd.user_id = $1 where c.deleted_at is null and cd.chat_id is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(user_id)];
@@ -328,7 +329,7 @@ pub async fn get_chat_session_by_id(
     chat_id: i32,
     user_id: i32,
     receiver_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<ChatSession, Error> {
     let chat_id = if chat_id == 0 {
         let query = format!("select cp.chat_id from chat_participants cp join chats c on c.chat_id = cp.chat_id where cp.user_id in ({}, {}) and c.deleted_at is null group by cp.chat_id having count(distinct cp.user_id) = 2", user_id, receiver_id);
@@ -409,7 +410,7 @@ pub async fn get_chat_messages(
     chat_id: i32,
     user_id: i32,
     receiver_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<ChatMessage>, Error> {
     let chat_id = if chat_id == 0 {
         let query = format!("select cp.chat_id from chat_participants cp join chats c on c.chat_id = cp.chat_id where cp.user_id in ({}, {}) and c.deleted_at is null group by cp.chat_id having count(distinct cp.user_id) = 2", user_id, receiver_id);
@@ -500,7 +501,7 @@ pub async fn get_chat_messages(
 pub async fn get_chat_message_by_id(
     message_id: i32,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<ChatMessage, Error> {
     let row = client.query_one("select m.chat_id, m.message_id, m.sender_id, u.name as sender_name, u.profile_image, m.message_text, m.status, m.created_at from messages m join users u on u.user_id = m.sender_id where m.deleted_at is null and m.message_id = $1", &[&message_id]).await?;
 
@@ -533,7 +534,7 @@ pub async fn update_message_status(
     message_id: i32,
     status: &str,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -596,13 +597,13 @@ pub async fn update_message_status(
     Ok(())
 }
 
-pub async fn is_own_message(message_id: i32, user_id: i32, client: &Client) -> bool {
+pub async fn is_own_message(message_id: i32, user_id: i32, client: &impl GenericClient) -> bool {
     let row= client.query_one("select count(*) as total from messages where message_id = $1 and sender_id = $2 and deleted_at is null", &[&message_id, &user_id]).await.unwrap();
     let total: i64 = row.get("total");
     total > 0
 }
 
-pub async fn delete_message(message_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_message(message_id: i32, client: &impl GenericClient) -> Result<(), Error> {
     client
         .execute(
             "update messages set deleted_at = CURRENT_TIMESTAMP where message_id = $1",
@@ -629,7 +630,7 @@ pub async fn delete_message(message_id: i32, client: &Client) -> Result<(), Erro
 pub async fn get_total_unread_counts(
     role: &str,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<i64, Error> {
     let mut query = "select count(*) as unread_counts from messages m join chats c on c.chat_id = m.chat_id left join chat_deletes cd on c.chat_id = cd.chat_id and cd.user_id = $1 where m.deleted_at is null and c.deleted_at is null and cd.chat_id is null and m.status != 'read' and m.sender_id != $2".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(user_id), Box::new(user_id)];
@@ -647,7 +648,7 @@ pub async fn get_total_unread_counts(
     Ok(row.get("unread_counts"))
 }
 
-pub async fn get_admin_ids(client: &Client) -> Result<Vec<i32>, Error> {
+pub async fn get_admin_ids(client: &impl GenericClient) -> Result<Vec<i32>, Error> {
     let rows = client
         .query(
             "select user_id from users where role = 'admin' and deleted_at is null",
@@ -673,7 +674,7 @@ pub struct UpdateStateRequest {
 
 pub async fn update_instantio_state(
     body: &UpdateStateRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     if body.event == "disconnect" {
         let user_id = body.data.room.unwrap();
@@ -721,7 +722,7 @@ pub async fn update_instantio_state(
 
 pub async fn get_last_active_at(
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Option<NaiveDateTime>, Error> {
     let row = client
         .query_one(
@@ -735,7 +736,7 @@ pub async fn get_last_active_at(
 pub async fn get_receiver_ids_from_chat_id(
     chat_id: i32,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<i32>, Error> {
     let rows = client
         .query(
@@ -750,7 +751,7 @@ pub async fn delete_chat_session(
     chat_id: i32,
     user_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     if role == "admin" {
         client.execute(
@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct Condition {
@@ -7,7 +8,7 @@ pub struct Condition {
     pub description: String,
 }
 
-pub async fn get_conditions(client: &Client) -> Result<Vec<Condition>, Error> {
+pub async fn get_conditions(client: &impl GenericClient) -> Result<Vec<Condition>, Error> {
     let rows=  client.query("select condition_id, description from conditions where deleted_at is null order by description", &[]).await?;
 
     Ok(rows
@@ -1,4 +1,5 @@
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
 fn prefix_zeros(original: &str, desired_length: usize) -> String {
     // If the original string is already long enough, return it as is
@@ -13,7 +14,7 @@ fn prefix_zeros(original: &str, desired_length: usize) -> String {
     "0".repeat(zeros_needed) + original
 }
 
-pub async fn generate_invoice_id(client: &Client) -> Result<String, Error> {
+pub async fn generate_invoice_id(client: &impl GenericClient) -> Result<String, Error> {
     let row = client
         .query_one(
             "update counters set n = n + 1 where label = '777' returning n",
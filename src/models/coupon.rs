@@ -0,0 +1,416 @@
+use chrono::{Local, NaiveDateTime};
+use deadpool_postgres::GenericClient;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::{types::ToSql, Error, Row};
+
+use crate::utils::{
+    common_struct::PaginationResult,
+    sql::{generate_pagination_query, PaginationOptions},
+};
+
+use super::{
+    order::{self, NewOrderItem},
+    pricing::{self, Discount, DiscountScope, PRICE_COLUMNS},
+};
+
+#[derive(Serialize)]
+pub struct Coupon {
+    pub coupon_id: i32,
+    pub code: String,
+    pub description: String,
+    pub discount_type: String,
+    pub discount_percent: f64,
+    pub discount_amount: f64,
+    pub shop_id: Option<i32>,
+    pub shop_name: Option<String>,
+    pub product_id: Option<i32>,
+    pub min_order_amount: f64,
+    pub max_redemptions: Option<i32>,
+    pub max_redemptions_per_user: Option<i32>,
+    pub redemption_counts: i64,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+    pub creator_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+const COUPON_COLUMNS: &str = "c.coupon_id, c.code, c.description, c.discount_type, c.discount_percent::text, c.discount_amount::text, c.shop_id, s.name as shop_name, c.product_id, c.min_order_amount::text, c.max_redemptions, c.max_redemptions_per_user, (select count(*) from coupon_redemptions cr where cr.coupon_id = c.coupon_id and cr.released_at is null) as redemption_counts, c.starts_at, c.ends_at, c.creator_id, c.created_at";
+
+const COUPON_QUERY: &str =
+    "from coupons c left join shops s on s.shop_id = c.shop_id where c.deleted_at is null";
+
+fn to_coupon(row: &Row) -> Coupon {
+    let discount_percent: String = row.get("discount_percent");
+    let discount_amount: String = row.get("discount_amount");
+    let min_order_amount: String = row.get("min_order_amount");
+
+    Coupon {
+        coupon_id: row.get("coupon_id"),
+        code: row.get("code"),
+        description: row.get("description"),
+        discount_type: row.get("discount_type"),
+        discount_percent: discount_percent.parse().unwrap(),
+        discount_amount: discount_amount.parse().unwrap(),
+        shop_id: row.get("shop_id"),
+        shop_name: row.get("shop_name"),
+        product_id: row.get("product_id"),
+        min_order_amount: min_order_amount.parse().unwrap(),
+        max_redemptions: row.get("max_redemptions"),
+        max_redemptions_per_user: row.get("max_redemptions_per_user"),
+        redemption_counts: row.get("redemption_counts"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        creator_id: row.get("creator_id"),
+        created_at: row.get("created_at"),
+    }
+}
+
+pub async fn get_coupons(
+    search: &Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    shop_id: Option<i32>,
+    creator_id: i32,
+    manage_any: bool,
+    client: &impl GenericClient,
+) -> Result<PaginationResult<Coupon>, Error> {
+    let mut base_query = COUPON_QUERY.to_string();
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
+
+    if manage_any {
+        if let Some(sid) = shop_id {
+            params.push(Box::new(sid));
+            base_query = format!("{base_query} and c.shop_id = ${}", params.len());
+        }
+    } else {
+        params.push(Box::new(creator_id));
+        base_query = format!("{base_query} and c.creator_id = ${}", params.len());
+    }
+
+    let result = generate_pagination_query(PaginationOptions {
+        select_columns: COUPON_COLUMNS,
+        base_query: &base_query,
+        search_columns: vec!["c.code", "c.description", "s.name"],
+        search: search.as_deref(),
+        order_options: Some("c.created_at desc"),
+        page,
+        per_page,
+    });
+
+    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
+
+    let row = client.query_one(&result.count_query, &params_slice).await?;
+    let total: i64 = row.get("total");
+
+    let mut page_counts = 0;
+    let mut current_page = 0;
+    let mut limit = 0;
+    if let (Some(p), Some(pp)) = (page, per_page) {
+        current_page = p;
+        limit = pp;
+        page_counts = (total as f64 / limit as f64).ceil() as usize;
+    }
+
+    let coupons = client
+        .query(&result.query, &params_slice)
+        .await?
+        .iter()
+        .map(to_coupon)
+        .collect();
+
+    Ok(PaginationResult {
+        data: coupons,
+        total,
+        page: current_page,
+        per_page: limit,
+        page_counts,
+    })
+}
+
+pub async fn get_coupon_by_id(
+    coupon_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<Coupon>, Error> {
+    let query = format!("select {COUPON_COLUMNS} {COUPON_QUERY} and c.coupon_id = $1");
+    let row = client.query_opt(&query, &[&coupon_id]).await?;
+    Ok(row.as_ref().map(to_coupon))
+}
+
+// Codes are matched without regard to case, `except_coupon_id` lets a coupon keep its own
+pub async fn is_code_taken(
+    code: &str,
+    except_coupon_id: Option<i32>,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            "select exists (select 1 from coupons where upper(code) = upper($1) and coupon_id is distinct from $2 and deleted_at is null) as taken",
+            &[&code, &except_coupon_id],
+        )
+        .await?;
+    Ok(row.get("taken"))
+}
+
+#[derive(Deserialize)]
+pub struct CouponRequest {
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: String,
+    pub discount_percent: Option<f64>,
+    pub discount_amount: Option<f64>,
+    pub shop_id: Option<i32>,
+    pub product_id: Option<i32>,
+    pub min_order_amount: Option<f64>,
+    pub max_redemptions: Option<i32>,
+    pub max_redemptions_per_user: Option<i32>,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
+}
+
+pub async fn add_coupon(
+    data: &CouponRequest,
+    creator_id: i32,
+    client: &impl GenericClient,
+) -> Result<i32, Error> {
+    let query = format!("insert into coupons (code, description, discount_type, discount_percent, discount_amount, shop_id, product_id, min_order_amount, max_redemptions, max_redemptions_per_user, starts_at, ends_at, creator_id) values ($1, $2, $3, {}, {}, $4, $5, {}, $6, $7, $8, $9, $10) returning coupon_id", data.discount_percent.unwrap_or(0.0), data.discount_amount.unwrap_or(0.0), data.min_order_amount.unwrap_or(0.0));
+    let row = client
+        .query_one(
+            &query,
+            &[
+                &data.code.trim(),
+                &data.description.as_deref().unwrap_or(""),
+                &data.discount_type,
+                &data.shop_id,
+                &data.product_id,
+                &data.max_redemptions,
+                &data.max_redemptions_per_user,
+                &data.starts_at,
+                &data.ends_at,
+                &creator_id,
+            ],
+        )
+        .await?;
+    Ok(row.get("coupon_id"))
+}
+
+pub async fn update_coupon(
+    coupon_id: i32,
+    data: &CouponRequest,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    let query = format!("update coupons set code = $1, description = $2, discount_type = $3, discount_percent = {}, discount_amount = {}, shop_id = $4, product_id = $5, min_order_amount = {}, max_redemptions = $6, max_redemptions_per_user = $7, starts_at = $8, ends_at = $9 where coupon_id = $10", data.discount_percent.unwrap_or(0.0), data.discount_amount.unwrap_or(0.0), data.min_order_amount.unwrap_or(0.0));
+    client
+        .execute(
+            &query,
+            &[
+                &data.code.trim(),
+                &data.description.as_deref().unwrap_or(""),
+                &data.discount_type,
+                &data.shop_id,
+                &data.product_id,
+                &data.max_redemptions,
+                &data.max_redemptions_per_user,
+                &data.starts_at,
+                &data.ends_at,
+                &coupon_id,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_coupon(coupon_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update coupons set deleted_at = CURRENT_TIMESTAMP where coupon_id = $1",
+            &[&coupon_id],
+        )
+        .await?;
+    Ok(())
+}
+
+// An order item priced the way add_order prices it
+pub struct CouponLine {
+    pub product_id: i32,
+    pub shop_id: i32,
+    pub amount: f64,
+}
+
+pub async fn get_coupon_lines(
+    items: &[NewOrderItem],
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<CouponLine>, Error> {
+    let pricing_context = pricing::get_pricing_context(user_id, client).await?;
+    let query = format!(
+        "select p.product_id, {PRICE_COLUMNS} from products p where p.product_id = $1 and p.deleted_at is null"
+    );
+
+    let mut lines = vec![];
+    for item in items {
+        let row = match client.query_opt(&query, &[&item.product_id]).await? {
+            Some(row) => row,
+            None => continue,
+        };
+        let price = match item.price {
+            Some(price) => price,
+            None => pricing_context.product_price(&row).final_price,
+        };
+        lines.push(CouponLine {
+            product_id: item.product_id,
+            shop_id: row.get("shop_id"),
+            amount: price * item.quantity as f64,
+        });
+    }
+    Ok(lines)
+}
+
+#[derive(Serialize)]
+pub struct CouponDiscount {
+    pub coupon_id: i32,
+    pub code: String,
+    pub order_amount: f64,
+    pub discount_amount: f64,
+    pub total: f64,
+}
+
+pub enum CouponCheck {
+    Valid(CouponDiscount),
+    Invalid(String),
+}
+
+// Works out the discount, or why the coupon cannot be used on these items
+fn evaluate_coupon(
+    coupon: &Coupon,
+    user_redemptions: i64,
+    lines: &[CouponLine],
+    now: NaiveDateTime,
+) -> Result<f64, String> {
+    if coupon.starts_at.is_some_and(|starts_at| now < starts_at) {
+        return Err(String::from("This coupon is not active yet!"));
+    }
+    if coupon.ends_at.is_some_and(|ends_at| now >= ends_at) {
+        return Err(String::from("This coupon has expired!"));
+    }
+    if let Some(shop_id) = coupon.shop_id {
+        if lines.iter().any(|line| line.shop_id != shop_id) {
+            return Err(String::from("This coupon is not valid for this shop!"));
+        }
+    }
+    let eligible_amount: f64 = lines
+        .iter()
+        .filter(|line| coupon.product_id.is_none_or(|p| p == line.product_id))
+        .map(|line| line.amount)
+        .sum();
+    if eligible_amount <= 0.0 {
+        return Err(String::from("This coupon is not valid for these items!"));
+    }
+    let order_amount: f64 = lines.iter().map(|line| line.amount).sum();
+    if order_amount < coupon.min_order_amount {
+        return Err(format!(
+            "This coupon needs an order of at least {:.2}!",
+            coupon.min_order_amount
+        ));
+    }
+    if coupon
+        .max_redemptions
+        .is_some_and(|max| coupon.redemption_counts >= max as i64)
+    {
+        return Err(String::from("This coupon has been fully redeemed!"));
+    }
+    if coupon
+        .max_redemptions_per_user
+        .is_some_and(|max| user_redemptions >= max as i64)
+    {
+        return Err(String::from(
+            "You have already used this coupon as many times as allowed!",
+        ));
+    }
+
+    let discount = Discount {
+        rule_id: None,
+        scope: if coupon.product_id.is_some() {
+            DiscountScope::Product
+        } else {
+            DiscountScope::All
+        },
+        discount_type: coupon.discount_type.clone(),
+        discount_percent: coupon.discount_percent,
+        amount_off: coupon.discount_amount,
+        starts_at: None,
+        discount_expiration: None,
+        discount_reason: coupon.description.clone(),
+        coupon_code: None,
+    };
+    Ok(pricing::calculate_price(eligible_amount, &[discount], &[], now).discount_amount)
+}
+
+// With `for_update` the coupon row stays locked until the transaction ends, so two
+// orders cannot both take the last redemption
+pub async fn check_coupon(
+    code: &str,
+    user_id: i32,
+    lines: &[CouponLine],
+    for_update: bool,
+    client: &impl GenericClient,
+) -> Result<CouponCheck, Error> {
+    let lock = if for_update { " for update of c" } else { "" };
+    let query =
+        format!("select {COUPON_COLUMNS} {COUPON_QUERY} and upper(c.code) = upper($1){lock}");
+    let mut coupon = match client.query_opt(&query, &[&code.trim()]).await? {
+        Some(row) => to_coupon(&row),
+        None => return Ok(CouponCheck::Invalid(String::from("Invalid coupon!"))),
+    };
+
+    // Counted in a statement of its own, after the lock is granted, so redemptions committed
+    // by an order that held the lock before us are included
+    let row = client
+        .query_one(
+            "select count(*) as redemptions, count(*) filter (where user_id = $2) as user_redemptions from coupon_redemptions where coupon_id = $1 and released_at is null",
+            &[&coupon.coupon_id, &user_id],
+        )
+        .await?;
+    coupon.redemption_counts = row.get("redemptions");
+    let user_redemptions: i64 = row.get("user_redemptions");
+
+    let order_amount: f64 = lines.iter().map(|line| line.amount).sum();
+    match evaluate_coupon(&coupon, user_redemptions, lines, Local::now().naive_local()) {
+        Ok(discount_amount) => Ok(CouponCheck::Valid(CouponDiscount {
+            coupon_id: coupon.coupon_id,
+            code: coupon.code,
+            order_amount,
+            discount_amount,
+            total: order_amount - discount_amount,
+        })),
+        Err(message) => Ok(CouponCheck::Invalid(message)),
+    }
+}
+
+pub async fn redeem_coupon(
+    discount: &CouponDiscount,
+    order_id: i32,
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    let query = format!("insert into coupon_redemptions (coupon_id, user_id, order_id, discount_amount) values ($1, $2, $3, {})", discount.discount_amount);
+    client
+        .execute(&query, &[&discount.coupon_id, &user_id, &order_id])
+        .await?;
+    let query = format!(
+        "update orders set coupon_discount = {} where order_id = $1",
+        discount.discount_amount
+    );
+    client.execute(&query, &[&order_id]).await?;
+    order::update_order_totals(order_id, client).await
+}
+
+// Gives the order's coupon back, the order keeps the discount it was placed with
+pub async fn release_coupon(order_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update coupon_redemptions set released_at = CURRENT_TIMESTAMP where order_id = $1 and released_at is null",
+            &[&order_id],
+        )
+        .await?;
+    Ok(())
+}
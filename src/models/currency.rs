@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct Currency {
@@ -9,7 +10,7 @@ pub struct Currency {
     pub symbol: String,
 }
 
-pub async fn get_currencies(client: &Client) -> Result<Vec<Currency>, Error> {
+pub async fn get_currencies(client: &impl GenericClient) -> Result<Vec<Currency>, Error> {
     let rows=  client.query("select currency_id, currency_code, currency_name, symbol from currencies where deleted_at is null order by currency_code", &[]).await?;
     Ok(rows
         .iter()
@@ -22,7 +23,7 @@ pub async fn get_currencies(client: &Client) -> Result<Vec<Currency>, Error> {
         .collect())
 }
 
-pub async fn get_default_currency_id(client: &Client) -> Result<i32, Error> {
+pub async fn get_default_currency_id(client: &impl GenericClient) -> Result<i32, Error> {
     let row = client
         .query_one(
             "select currency_id from currencies where currency_code = 'MMK' and deleted_at is null",
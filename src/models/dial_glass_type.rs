@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct DialGlassType {
@@ -7,7 +8,9 @@ pub struct DialGlassType {
     pub description: String,
 }
 
-pub async fn get_dial_glass_types(client: &Client) -> Result<Vec<DialGlassType>, Error> {
+pub async fn get_dial_glass_types(
+    client: &impl GenericClient,
+) -> Result<Vec<DialGlassType>, Error> {
     let rows=  client.query("select dial_glass_type_id, description from dial_glass_types where deleted_at is null order by description", &[]).await?;
 
     Ok(rows
@@ -1,12 +1,18 @@
+use std::collections::HashMap;
+
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use serde_json::Value;
+use tokio_postgres::{types::ToSql, Error, Row};
 
 use crate::utils::{
     common_struct::PaginationResult,
     sql::{generate_pagination_query, PaginationOptions},
 };
 
+use super::{notification, pricing::DISCOUNT_BY_AMOUNT};
+
 #[derive(Serialize)]
 pub struct DiscountRule {
     pub rule_id: i32,
@@ -20,9 +26,33 @@ pub struct DiscountRule {
     pub coupon_code: Option<String>,
     pub shop_id: i32,
     pub shop_name: String,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
 }
 
+fn to_discount_rule(row: &Row) -> DiscountRule {
+    let discount_percent: &str = row.get("discount_percent");
+    let discounted_price: &str = row.get("discounted_price");
+
+    DiscountRule {
+        rule_id: row.get("rule_id"),
+        discount_for: row.get("discount_for"),
+        discount_for_id: row.get("discount_for_id"),
+        discount_percent: discount_percent.parse().unwrap(),
+        discount_expiration: row.get("discount_expiration"),
+        discount_reason: row.get("discount_reason"),
+        discounted_price: discounted_price.parse().unwrap(),
+        discount_type: row.get("discount_type"),
+        coupon_code: row.get("coupon_code"),
+        shop_id: row.get("shop_id"),
+        shop_name: row.get("shop_name"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
 pub async fn get_discount_rules(
     search: &Option<String>,
     page: Option<usize>,
@@ -30,7 +60,7 @@ pub async fn get_discount_rules(
     shop_id: Option<i32>,
     creator_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<DiscountRule>, Error> {
     let mut base_query =
         "from discount_rules dr join shops s on dr.shop_id = s.shop_id where dr.deleted_at is null"
@@ -51,7 +81,7 @@ pub async fn get_discount_rules(
 
     let result = generate_pagination_query(PaginationOptions {
         select_columns:
-            "dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.created_at",
+            "dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.starts_at, dr.ends_at, dr.created_at",
         base_query: &base_query,
         search_columns: vec!["dr.rule_id::text", "dr.discount_for", "dr.discount_reason", "dr.discount_type", "s.name"],
         search: search.as_deref(),
@@ -78,28 +108,7 @@ pub async fn get_discount_rules(
         .query(&result.query, &params_slice)
         .await?
         .iter()
-        .map(|row| {
-            let discount_percent: &str = row.get("discount_percent");
-            let discount_percent: f64 = discount_percent.parse().unwrap();
-
-            let discounted_price: &str = row.get("discounted_price");
-            let discounted_price: f64 = discounted_price.parse().unwrap();
-
-            DiscountRule {
-                rule_id: row.get("rule_id"),
-                discount_for: row.get("discount_for"),
-                discount_for_id: row.get("discount_for_id"),
-                discount_percent,
-                discount_expiration: row.get("discount_expiration"),
-                discount_reason: row.get("discount_reason"),
-                discounted_price,
-                discount_type: row.get("discount_type"),
-                coupon_code: row.get("coupon_code"),
-                shop_id: row.get("shop_id"),
-                shop_name: row.get("shop_name"),
-                created_at: row.get("created_at"),
-            }
-        })
+        .map(to_discount_rule)
         .collect();
 
     Ok(PaginationResult {
@@ -122,12 +131,14 @@ pub struct DiscountRuleRequest {
     pub discount_type: String,
     pub coupon_code: Option<String>,
     pub shop_id: i32,
+    pub starts_at: Option<NaiveDateTime>,
+    pub ends_at: Option<NaiveDateTime>,
 }
 
 pub async fn add_discount_rule(
     data: &DiscountRuleRequest,
     creator_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let discount_expiration = if let Some(de) = &data.discount_expiration {
         format!("'{de}'")
@@ -135,7 +146,7 @@ pub async fn add_discount_rule(
         "null".to_string()
     };
 
-    let query = format!("insert into discount_rules (discount_for, discount_for_id, discount_percent, discount_expiration, discount_reason, discounted_price, discount_type, coupon_code, creator_id, shop_id) values ($1, $2, {}, {}, $3, {}, $4, $5, $6, $7)", data.discount_percent, discount_expiration, data.discounted_price);
+    let query = format!("insert into discount_rules (discount_for, discount_for_id, discount_percent, discount_expiration, discount_reason, discounted_price, discount_type, coupon_code, creator_id, shop_id, starts_at, ends_at) values ($1, $2, {}, coalesce($9::timestamp, {discount_expiration}), $3, {}, $4, $5, $6, $7, $8, coalesce($9::timestamp, {discount_expiration}))", data.discount_percent, data.discounted_price);
     client
         .execute(
             &query,
@@ -147,45 +158,27 @@ pub async fn add_discount_rule(
                 &data.coupon_code,
                 &creator_id,
                 &data.shop_id,
+                &data.starts_at,
+                &data.ends_at,
             ],
         )
         .await?;
-
-    update_products_discount(data, client).await?;
     Ok(())
 }
 
-pub async fn get_discount_rule_by_id(rule_id: i32, client: &Client) -> Option<DiscountRule> {
+pub async fn get_discount_rule_by_id(
+    rule_id: i32,
+    client: &impl GenericClient,
+) -> Option<DiscountRule> {
     let result = client
         .query_one(
-            "select dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.created_at from discount_rules dr join shops s on dr.shop_id = s.shop_id where dr.deleted_at is null and dr.rule_id = $1",
+            "select dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.starts_at, dr.ends_at, dr.created_at from discount_rules dr join shops s on dr.shop_id = s.shop_id where dr.deleted_at is null and dr.rule_id = $1",
             &[&rule_id],
         )
         .await;
 
     match result {
-        Ok(row) => {
-            let discount_percent: &str = row.get("discount_percent");
-            let discount_percent: f64 = discount_percent.parse().unwrap();
-
-            let discounted_price: &str = row.get("discounted_price");
-            let discounted_price: f64 = discounted_price.parse().unwrap();
-
-            Some(DiscountRule {
-                rule_id: row.get("rule_id"),
-                discount_for: row.get("discount_for"),
-                discount_for_id: row.get("discount_for_id"),
-                discount_percent,
-                discount_expiration: row.get("discount_expiration"),
-                discount_reason: row.get("discount_reason"),
-                discounted_price,
-                discount_type: row.get("discount_type"),
-                coupon_code: row.get("coupon_code"),
-                shop_id: row.get("shop_id"),
-                shop_name: row.get("shop_name"),
-                created_at: row.get("created_at"),
-            })
-        }
+        Ok(row) => Some(to_discount_rule(&row)),
         Err(_) => None,
     }
 }
@@ -193,7 +186,7 @@ pub async fn get_discount_rule_by_id(rule_id: i32, client: &Client) -> Option<Di
 pub async fn update_discount_rule(
     rule_id: i32,
     data: &DiscountRuleRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let discount_expiration = if let Some(de) = &data.discount_expiration {
         format!("'{de}'")
@@ -201,7 +194,8 @@ pub async fn update_discount_rule(
         "null".to_string()
     };
 
-    let query = format!("update discount_rules set discount_for = $1, discount_for_id = $2, discount_percent = {}, discount_expiration = {}, discount_reason = $3, discounted_price = {}, discount_type = $4, coupon_code = $5, shop_id = $6 where rule_id = $7", data.discount_percent, discount_expiration, data.discounted_price);
+    // A rescheduled sale is announced again when it starts
+    let query = format!("update discount_rules set discount_for = $1, discount_for_id = $2, discount_percent = {}, discount_expiration = coalesce($9::timestamp, {discount_expiration}), discount_reason = $3, discounted_price = {}, discount_type = $4, coupon_code = $5, shop_id = $6, starts_at = $8, ends_at = coalesce($9::timestamp, {discount_expiration}), activated_at = case when starts_at is not distinct from $8 then activated_at end, deactivated_at = case when ends_at is not distinct from coalesce($9::timestamp, {discount_expiration}) then deactivated_at end, updated_at = CURRENT_TIMESTAMP where rule_id = $7", data.discount_percent, data.discounted_price);
     client
         .execute(
             &query,
@@ -213,259 +207,184 @@ pub async fn update_discount_rule(
                 &data.coupon_code,
                 &data.shop_id,
                 &rule_id,
+                &data.starts_at,
+                &data.ends_at,
             ],
         )
         .await?;
+    Ok(())
+}
 
-    update_products_discount(data, client).await?;
+pub async fn delete_discount_rule(rule_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update discount_rules set deleted_at = CURRENT_TIMESTAMP where rule_id = $1",
+            &[&rule_id],
+        )
+        .await?;
     Ok(())
 }
 
-async fn update_products_discount(
-    data: &DiscountRuleRequest,
-    client: &Client,
+// Sales that are set up but haven't started yet, soonest first
+pub async fn get_upcoming_sales(
+    page: Option<usize>,
+    per_page: Option<usize>,
+    shop_id: Option<i32>,
+    client: &impl GenericClient,
+) -> Result<PaginationResult<DiscountRule>, Error> {
+    let mut base_query = "from discount_rules dr join shops s on dr.shop_id = s.shop_id where dr.deleted_at is null and s.deleted_at is null and dr.discount_type != 'No Discount' and dr.starts_at > now()::timestamp".to_string();
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
+
+    if let Some(sid) = shop_id {
+        params.push(Box::new(sid));
+        base_query = format!("{base_query} and dr.shop_id = ${}", params.len());
+    }
+
+    let order_options = "dr.starts_at asc, dr.rule_id asc";
+    let result = generate_pagination_query(PaginationOptions {
+        select_columns:
+            "dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.starts_at, dr.ends_at, dr.created_at",
+        base_query: &base_query,
+        search_columns: vec![],
+        search: None,
+        order_options: Some(order_options),
+        page,
+        per_page,
+    });
+
+    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
+
+    let row = client.query_one(&result.count_query, &params_slice).await?;
+    let total: i64 = row.get("total");
+
+    let mut page_counts = 0;
+    let mut current_page = 0;
+    let mut limit = 0;
+    if let (Some(p), Some(pp)) = (page, per_page) {
+        current_page = p;
+        limit = pp;
+        page_counts = (total as f64 / limit as f64).ceil() as usize;
+    }
+
+    let sales = client
+        .query(&result.query, &params_slice)
+        .await?
+        .iter()
+        .map(to_discount_rule)
+        .collect();
+
+    Ok(PaginationResult {
+        data: sales,
+        total,
+        page: current_page,
+        per_page: limit,
+        page_counts,
+    })
+}
+
+// Users who favourited a product the rule covers
+async fn get_sale_favoriter_ids(
+    rule: &DiscountRule,
+    client: &impl GenericClient,
+) -> Result<Vec<i32>, Error> {
+    let rows = client
+        .query(
+            "select distinct f.user_id from product_favorites f join products p on p.product_id = f.product_id join users u on u.user_id = f.user_id where p.deleted_at is null and u.deleted_at is null and p.shop_id = $1 and ($2 = 'all' or ($2 = 'product' and p.product_id = $3) or ($2 = 'brand' and p.brand_id = $3) or ($2 = 'category' and p.category_id = $3))",
+            &[&rule.shop_id, &rule.discount_for, &rule.discount_for_id],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get("user_id")).collect())
+}
+
+async fn notify_sale_started(
+    rule: &DiscountRule,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
-    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![
-        Box::new(&data.discount_for),
-        Box::new(&data.discount_reason),
-        Box::new(&data.discount_type),
-        Box::new(&data.coupon_code),
-        Box::new(data.shop_id),
-    ];
-    let discount_expiration = if let Some(de) = &data.discount_expiration {
-        de
-    } else {
-        "null"
-    };
-    let dp = if &data.discount_type == "Discount by Specific Amount" {
-        format!("price - {}", data.discounted_price)
+    let user_ids = get_sale_favoriter_ids(rule, client).await?;
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let discount = if rule.discount_type == DISCOUNT_BY_AMOUNT {
+        format!("{:.2} off", rule.discounted_price)
     } else {
-        format!("{}", data.discounted_price)
+        format!("{}% off", rule.discount_percent)
     };
-
-    let base_update_query = &format!("update products set discount_updated_by = $1, discount_percent = {}, discount_expiration = '{discount_expiration}', discount_reason = $2, discounted_price = {}, discount_type = $3, coupon_code = $4 where deleted_at is null and shop_id = $5", data.discount_percent, dp);
-    let update_query = if data.discount_for == "brand" {
-        params.push(Box::new(data.discount_for_id));
-        format!(
-            "{base_update_query} and discount_updated_by != 'product' and brand_id = ${}",
-            params.len()
-        )
-    } else if data.discount_for == "category" {
-        params.push(Box::new(data.discount_for_id));
+    let message = if rule.discount_reason.is_empty() {
         format!(
-            "{base_update_query} and discount_updated_by not in ('product', 'brand') and category_id = ${}",
-            params.len()
+            "A sale on watches you saved has started at {}: {}.",
+            rule.shop_name, discount
         )
     } else {
         format!(
-            "{base_update_query} and discount_updated_by not in ('product', 'brand', 'category')"
+            "{} has started at {}: {} watches you saved.",
+            rule.discount_reason, rule.shop_name, discount
         )
     };
-    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
-    client.execute(&update_query, &params_slice).await?;
+    let mut map = HashMap::new();
+    map.insert(
+        "redirect".to_string(),
+        Value::String("flash-sale".to_string()),
+    );
+    map.insert("rule_id".to_string(), Value::Number(rule.rule_id.into()));
+    map.insert("shop_id".to_string(), Value::Number(rule.shop_id.into()));
+    let data = Some(map);
+
+    for user_id in user_ids {
+        if let Err(err) =
+            notification::add_notification(user_id, "Flash Sale Started", &message, &data, client)
+                .await
+        {
+            println!("Error notifying user #{} of flash sale: {:?}", user_id, err);
+        }
+    }
     Ok(())
 }
 
-pub async fn delete_discount_rule(
-    rule_id: i32,
-    old_discount_rule: &DiscountRule,
-    client: &Client,
-) -> Result<(), Error> {
-    client
-        .execute(
-            "update discount_rules set deleted_at = CURRENT_TIMESTAMP where rule_id = $1",
-            &[&rule_id],
+// Rules are priced by their own start and end times, so a sale is on exactly when it
+// should be. This records the sales that went live or ended since the last run and
+// tells users who favourited the products on sale.
+pub async fn run_flash_sales(client: &impl GenericClient) {
+    let started = client
+        .query(
+            "update discount_rules dr set activated_at = CURRENT_TIMESTAMP from shops s where s.shop_id = dr.shop_id and dr.deleted_at is null and dr.activated_at is null and dr.discount_type != 'No Discount' and dr.starts_at <= now()::timestamp and (dr.ends_at is null or dr.ends_at > now()::timestamp) returning dr.rule_id, dr.discount_for, dr.discount_for_id, dr.discount_percent::text, dr.discount_expiration, dr.discount_reason, dr.discounted_price::text, dr.discount_type, dr.coupon_code, dr.shop_id, s.name shop_name, dr.starts_at, dr.ends_at, dr.created_at",
+            &[],
         )
-        .await?;
-    let update_query = format!("update products set discount_percent = 0.0, discount_expiration = null, discount_reason = '', discounted_price = 0.0, discount_type = 'No Discount', coupon_code = null where deleted_at is null and shop_id = {} and discount_updated_by = '{}'", old_discount_rule.shop_id, old_discount_rule.discount_for);
-    client.execute(&update_query, &[]).await?;
-    Ok(())
+        .await;
+    match started {
+        Ok(rows) => {
+            for row in &rows {
+                let rule = to_discount_rule(row);
+                println!("Flash sale #{} started", rule.rule_id);
+                if let Err(err) = notify_sale_started(&rule, client).await {
+                    println!("Error notifying flash sale #{}: {:?}", rule.rule_id, err);
+                }
+            }
+        }
+        Err(err) => println!("Error starting flash sales: {:?}", err),
+    }
+
+    match client
+        .query(
+            "update discount_rules set deactivated_at = CURRENT_TIMESTAMP where deleted_at is null and deactivated_at is null and starts_at is not null and ends_at <= now()::timestamp returning rule_id",
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => {
+            for row in &rows {
+                let rule_id: i32 = row.get("rule_id");
+                println!("Flash sale #{} ended", rule_id);
+            }
+        }
+        Err(err) => println!("Error ending flash sales: {:?}", err),
+    }
 }
 
-// pub async fn get_discount_rules_for_calculation(
-//     client: &Client,
-// ) -> Result<Vec<DiscountRule>, Error> {
-//     let rows=  client.query("select rule_id, discount_for, discount_for_id, discount_percent::text, discount_expiration, discount_reason, discounted_price::text, discount_type, shop_id, created_at from discount_rules
-//     where (discount_expiration is null or discount_expiration >= CURRENT_DATE) and deleted_at is null
-//     ", &[]).await?;
-//     Ok(rows
-//         .iter()
-//         .map(|row| {
-//             let discount_percent: &str = row.get("discount_percent");
-//             let discount_percent: f64 = discount_percent.parse().unwrap();
-
-//             let discounted_price: &str = row.get("discounted_price");
-//             let discounted_price: f64 = discounted_price.parse().unwrap();
-
-//             DiscountRule {
-//                 rule_id: row.get("rule_id"),
-//                 discount_for: row.get("discount_for"),
-//                 discount_for_id: row.get("discount_for_id"),
-//                 discount_percent,
-//                 discount_expiration: row.get("discount_expiration"),
-//                 discount_reason: row.get("discount_reason"),
-//                 discounted_price,
-//                 discount_type: row.get("discount_type"),
-//                 shop_id: row.get("shop_id"),
-//                 shop_name: "".to_string(),
-//                 created_at: row.get("created_at"),
-//             }
-//         })
-//         .collect())
-// }
-
-// pub struct DiscountCalculationResult {
-//     pub discount_percent: f64,
-//     pub discounted_price: f64,
-//     pub discount_reason: String,
-//     pub discount_type: String,
-// }
-
-// pub async fn calculate_discounted_price(
-//     price: f64,
-//     product_id: i32,
-//     shop_id: i32,
-//     category_id: i32,
-//     brand_id: i32,
-//     client: &Client,
-// ) -> DiscountCalculationResult {
-//     let query = "
-//     SELECT
-//     rule_id,
-//     discount_for,
-//     discount_for_id,
-//     discount_percent::text,
-//     discount_expiration,
-//     discount_reason,
-//     discounted_price::text,
-//     discount_type,
-//     shop_id,
-//     created_at
-// FROM discount_rules
-// WHERE
-//     deleted_at IS NULL
-//     AND (discount_expiration IS NULL OR discount_expiration >= CURRENT_DATE)
-//     AND shop_id = $1
-//     AND (
-//         (discount_for = 'product' AND discount_for_id = $2)
-//         OR (discount_for = 'brand' AND discount_for_id = $3)
-//         OR (discount_for = 'category' AND discount_for_id = $4)
-//         OR discount_for = 'all'
-//     )
-// ORDER BY
-//     CASE
-//         WHEN discount_for = 'product' THEN 1
-//         WHEN discount_for = 'brand' THEN 2
-//         WHEN discount_for = 'category' THEN 3
-//         WHEN discount_for = 'all' THEN 4
-//     END
-// LIMIT 1;
-//     ";
-//     match client
-//         .query_one(query, &[&shop_id, &product_id, &brand_id, &category_id])
-//         .await
-//     {
-//         Ok(row) => {
-//             let discount_percent: &str = row.get("discount_percent");
-//             let discount_percent: f64 = discount_percent.parse().unwrap();
-
-//             let discounted_price: &str = row.get("discounted_price");
-//             let discounted_price: f64 = discounted_price.parse().unwrap();
-
-//             let rule = DiscountRule {
-//                 rule_id: row.get("rule_id"),
-//                 discount_for: row.get("discount_for"),
-//                 discount_for_id: row.get("discount_for_id"),
-//                 discount_percent,
-//                 discount_expiration: row.get("discount_expiration"),
-//                 discount_reason: row.get("discount_reason"),
-//                 discounted_price,
-//                 discount_type: row.get("discount_type"),
-//                 shop_id: row.get("shop_id"),
-//                 shop_name: "".to_string(),
-//                 created_at: row.get("created_at"),
-//             };
-
-//             return calculate_discount(price, &rule);
-//         }
-//         Err(err) => {
-//             println!("Discount calculation error: {:?}", err);
-//             return DiscountCalculationResult {
-//                 discount_percent: 0.0,
-//                 discounted_price: price,
-//                 discount_reason: String::new(),
-//                 discount_type: "No Discount".to_string(),
-//             };
-//         }
-//     };
-// }
-
-// fn calculate_discount(price: f64, rule: &DiscountRule) -> DiscountCalculationResult {
-//     match rule.discount_type.as_str() {
-//         "Discount by Specific Percentage" => DiscountCalculationResult {
-//             discount_percent: rule.discount_percent,
-//             discounted_price: price * (1.0 - rule.discount_percent / 100.0),
-//             discount_reason: rule.discount_reason.clone(),
-//             discount_type: rule.discount_type.clone(),
-//         },
-//         "Discount by Specific Amount" => DiscountCalculationResult {
-//             discount_percent: 0.0,
-//             discounted_price: rule.discounted_price,
-//             discount_reason: rule.discount_reason.clone(),
-//             discount_type: rule.discount_type.clone(),
-//         },
-//         _ => DiscountCalculationResult {
-//             discount_percent: 0.0,
-//             discounted_price: price,
-//             discount_reason: String::new(),
-//             discount_type: rule.discount_type.clone(),
-//         },
-//     }
-// }
-
-// pub fn calculate_discounted_price(
-//     price: f64,
-//     product_id: i32,
-//     shop_id: i32,
-//     category_id: i32,
-//     brand_id: i32,
-//     discount_rules: &[DiscountRule],
-// ) -> (f64, String) {
-//     let mut applicable_rules: Vec<&DiscountRule> = discount_rules
-//         .iter()
-//         .filter(|rule| rule.shop_id == shop_id)
-//         .collect();
-
-//     // Sort rules by priority
-//     applicable_rules.sort_by_key(|rule| match rule.discount_for.as_str() {
-//         "product" => 1,
-//         "brand" => 2,
-//         "category" => 3,
-//         "all" => 4,
-//         _ => 5,
-//     });
-
-//     for rule in applicable_rules {
-//         match rule.discount_for.as_str() {
-//             "product" if rule.discount_for_id == product_id => {
-//                 return calculate_discount(price, rule)
-//             }
-//             "brand" if rule.discount_for_id == brand_id => return calculate_discount(price, rule),
-//             "category" if rule.discount_for_id == category_id => {
-//                 return calculate_discount(price, rule)
-//             }
-//             "all" => return calculate_discount(price, rule),
-//             _ => (),
-//         }
-//     }
-
-//     (price, String::new()) // default case if no rule matches
-// }
-
-pub async fn is_coupon_code_available(coupon_code: &str, client: &Client) -> bool {
+pub async fn is_coupon_code_available(coupon_code: &str, client: &impl GenericClient) -> bool {
     let result = client
         .query(
-            "SELECT coupon_code FROM discount_rules WHERE coupon_code = $1 AND discount_type != 'No Discount' AND (discount_expiration IS NULL OR discount_expiration > CURRENT_TIMESTAMP) and deleted_at is null",
+            "SELECT coupon_code FROM discount_rules WHERE coupon_code = $1 AND discount_type != 'No Discount' AND (starts_at IS NULL OR starts_at <= CURRENT_TIMESTAMP) AND (coalesce(ends_at, discount_expiration) IS NULL OR coalesce(ends_at, discount_expiration) > CURRENT_TIMESTAMP) and deleted_at is null",
             &[&coupon_code],
         )
         .await;
@@ -476,10 +395,24 @@ pub async fn is_coupon_code_available(coupon_code: &str, client: &Client) -> boo
     }
 }
 
+pub async fn has_used_coupon(
+    coupon_code: &str,
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            "select exists (select 1 from used_coupons where user_id = $1 and coupon_code = $2 and deleted_at is null) as used",
+            &[&user_id, &coupon_code],
+        )
+        .await?;
+    Ok(row.get("used"))
+}
+
 pub async fn add_used_coupon(
     coupon_code: &str,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -490,7 +423,10 @@ pub async fn add_used_coupon(
     Ok(())
 }
 
-pub async fn delete_used_coupon(used_coupon_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_used_coupon(
+    used_coupon_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     client
         .execute(
             "update used_coupons set deleted_at = CURRENT_TIMESTAMP where used_coupon_id = $1",
@@ -515,7 +451,7 @@ pub async fn get_used_coupons(
     per_page: Option<usize>,
     user_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<UsedCoupon>, Error> {
     let mut base_query = "from used_coupons where deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -572,7 +508,10 @@ pub async fn get_used_coupons(
     })
 }
 
-pub async fn get_used_coupon_by_id(used_coupon_id: i32, client: &Client) -> Option<UsedCoupon> {
+pub async fn get_used_coupon_by_id(
+    used_coupon_id: i32,
+    client: &impl GenericClient,
+) -> Option<UsedCoupon> {
     let result = client
         .query_one(
             "select used_coupon_id, user_id, coupon_code, created_at where deleted_at is null and used_coupon_id = $1",
@@ -0,0 +1,54 @@
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
+
+// Returns false when there is no such product, favouriting a product twice is a no-op
+pub async fn add_favorite(
+    user_id: i32,
+    product_id: i32,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            "select exists (select 1 from products where product_id = $1 and deleted_at is null) as found",
+            &[&product_id],
+        )
+        .await?;
+    let found: bool = row.get("found");
+    if !found {
+        return Ok(false);
+    }
+    client
+        .execute(
+            "insert into product_favorites (user_id, product_id) values ($1, $2) on conflict (user_id, product_id) do nothing",
+            &[&user_id, &product_id],
+        )
+        .await?;
+    Ok(true)
+}
+
+pub async fn delete_favorite(
+    user_id: i32,
+    product_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "delete from product_favorites where user_id = $1 and product_id = $2",
+            &[&user_id, &product_id],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn get_favorite_product_ids(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<i32>, Error> {
+    let rows = client
+        .query(
+            "select f.product_id from product_favorites f join products p on p.product_id = f.product_id where f.user_id = $1 and p.deleted_at is null order by f.created_at desc",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get("product_id")).collect())
+}
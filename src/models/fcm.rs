@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize, Deserialize)]
 pub struct Fcm {
@@ -7,7 +8,11 @@ pub struct Fcm {
     pub device_type: String,
 }
 
-pub async fn add_fcm_token(user_id: i32, fcm: &Fcm, client: &Client) -> Result<(), Error> {
+pub async fn add_fcm_token(
+    user_id: i32,
+    fcm: &Fcm,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     client.execute("insert into fcm_tokens (user_id, token, device_type) 
     values ($1, $2, $3) 
     on conflict (user_id, device_type)
@@ -15,7 +20,10 @@ pub async fn add_fcm_token(user_id: i32, fcm: &Fcm, client: &Client) -> Result<(
     Ok(())
 }
 
-pub async fn get_fcm_tokens(user_id: i32, client: &Client) -> Result<Vec<String>, Error> {
+pub async fn get_fcm_tokens(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<String>, Error> {
     let rows = client
         .query(
             "select token from fcm_tokens where user_id = $1",
@@ -25,7 +33,7 @@ pub async fn get_fcm_tokens(user_id: i32, client: &Client) -> Result<Vec<String>
     Ok(rows.iter().map(|row| row.get("token")).collect())
 }
 
-pub async fn get_admin_fcm_tokens(client: &Client) -> Result<Vec<String>, Error> {
+pub async fn get_admin_fcm_tokens(client: &impl GenericClient) -> Result<Vec<String>, Error> {
     let rows = client
         .query(
             "select f.token from fcm_tokens f inner join users u on u.user_id = f.user_id where u.role = 'admin' and u.deleted_at is null",
@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct Gender {
@@ -7,7 +8,7 @@ pub struct Gender {
     pub description: String,
 }
 
-pub async fn get_genders(client: &Client) -> Result<Vec<Gender>, Error> {
+pub async fn get_genders(client: &impl GenericClient) -> Result<Vec<Gender>, Error> {
     let rows=  client.query("select gender_id, description from genders where deleted_at is null order by description", &[]).await?;
 
     Ok(rows
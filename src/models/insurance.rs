@@ -1,6 +1,7 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -25,7 +26,7 @@ pub async fn get_insurance_rules(
     per_page: Option<usize>,
     amount: Option<f64>,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<InsuranceRule>, Error> {
     let mut base_query = "from commission_rules where deleted_at is null".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -109,7 +110,10 @@ pub struct InsuranceRuleRequest {
     pub effective_to: DateTime<Utc>,
 }
 
-pub async fn add_insurance_rule(data: &InsuranceRuleRequest, client: &Client) -> Result<(), Error> {
+pub async fn add_insurance_rule(
+    data: &InsuranceRuleRequest,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     let query = format!("insert into commission_rules (description, commission_percentage, min_order_amount, max_order_amount, effective_from, effective_to) values ($1, {}, {}, {}, $2, $3)", &data.commission_percentage, &data.min_order_amount, &data.max_order_amount);
     client
         .execute(
@@ -124,7 +128,10 @@ pub async fn add_insurance_rule(data: &InsuranceRuleRequest, client: &Client) ->
     Ok(())
 }
 
-pub async fn get_insurance_rule_by_id(rule_id: i32, client: &Client) -> Option<InsuranceRule> {
+pub async fn get_insurance_rule_by_id(
+    rule_id: i32,
+    client: &impl GenericClient,
+) -> Option<InsuranceRule> {
     let result = client
         .query_one(
             "select rule_id, description, commission_percentage::text, min_order_amount::text, max_order_amount::text, effective_from, effective_to, created_at from commission_rules where deleted_at is null and rule_id = $1",
@@ -159,7 +166,7 @@ pub async fn get_insurance_rule_by_id(rule_id: i32, client: &Client) -> Option<I
 pub async fn update_insurance_rule(
     rule_id: i32,
     data: &InsuranceRuleRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let query = format!("update commission_rules set description = $1, commission_percentage = {}, min_order_amount = {}, max_order_amount = {}, effective_from = $2, effective_to = $3 where rule_id = $4", &data.commission_percentage, &data.min_order_amount, &data.max_order_amount);
     client
@@ -176,7 +183,7 @@ pub async fn update_insurance_rule(
     Ok(())
 }
 
-pub async fn delete_insurance_rule(rule_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_insurance_rule(rule_id: i32, client: &impl GenericClient) -> Result<(), Error> {
     client
         .execute(
             "update commission_rules set deleted_at = CURRENT_TIMESTAMP where rule_id = $1",
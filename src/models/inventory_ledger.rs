@@ -0,0 +1,33 @@
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
+
+pub async fn add_ledger_entry(
+    product_id: i32,
+    order_id: Option<i32>,
+    quantity_change: i32,
+    reason: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "insert into inventory_ledger (product_id, order_id, quantity_change, reason) values ($1, $2, $3, $4)",
+            &[&product_id, &order_id, &quantity_change, &reason],
+        )
+        .await?;
+    Ok(())
+}
+
+// Must run before the product update so the difference against the old quantity can be taken.
+pub async fn record_stock_adjustment(
+    product_id: i32,
+    stock_quantity: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "insert into inventory_ledger (product_id, quantity_change, reason) select product_id, $2 - stock_quantity, 'adjustment' from products where product_id = $1 and stock_quantity <> $2",
+            &[&product_id, &stock_quantity],
+        )
+        .await?;
+    Ok(())
+}
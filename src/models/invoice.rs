@@ -1,19 +1,68 @@
 use std::{collections::HashMap, fs};
 
+use deadpool_postgres::GenericClient;
 use serde_json::Value;
-use tokio_postgres::Client;
 
-use crate::utils::{report_forge, socketio};
+use crate::utils::{
+    pdf::{load_fonts, Align, Color, PdfDocument, TextStyle, PAGE_HEIGHT, PAGE_WIDTH},
+    socketio,
+};
+
+// Myanmar text is set in whichever font in ./fonts covers it, Unifont Myanmar unless
+// a better one is added
+const FONTS_DIR: &str = "./fonts";
+const REPORTS_DIR: &str = "./reports";
+const BODY_FONT: &str = "Nunito-Regular";
+const SCRIPT_FONT: &str = "RougeScript-Regular";
+
+const TEXT_COLOR: Color = Color(0x64, 0x64, 0x64);
+const HEADING_COLOR: Color = Color(0x4a, 0x4a, 0x4a);
+const HEADER_COLOR: Color = Color(0x03, 0x1b, 0x38);
+const BORDER_COLOR: Color = Color(0x9c, 0x9a, 0x9c);
+const WHITE: Color = Color(0xff, 0xff, 0xff);
+
+const MARGIN: f32 = 32.0;
+const CONTENT_TOP: f32 = 192.0;
+const CONTENT_BOTTOM: f32 = PAGE_HEIGHT - 40.0;
+const CELL_PADDING: f32 = 7.5;
+// Description, quantity, price and total take these shares of the table width
+const COLUMNS: [f32; 4] = [0.4, 0.1, 0.25, 0.25];
+
+#[cfg_attr(test, derive(serde::Deserialize))]
+struct InvoiceItem {
+    description: String,
+    discount_reason: String,
+    quantity: String,
+    original_price: Option<String>,
+    price: String,
+    total: String,
+}
+
+#[cfg_attr(test, derive(serde::Deserialize))]
+struct Invoice {
+    customer_name: String,
+    customer_phone: String,
+    customer_address: String,
+    shop_name: String,
+    seller_phone: String,
+    seller_address: String,
+    order_date: String,
+    order_id: i32,
+    invoice_id: String,
+    payment_type: String,
+    note: String,
+    items: Vec<InvoiceItem>,
+    sub_total: String,
+}
 
 pub async fn export_invoice(
     order_id: i32,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Option<String>, Box<dyn std::error::Error>> {
-    let row= client.query_one("select u.name customer_name, u.phone, oa.home_address, oa.street_address, oa.city, oa.state, oa.postal_code, oa.country, oa.township, oa.ward, s.name shop_name, u2.phone seller_phone, s.address shop_address, to_char(o.created_at, 'DD Month YYYY') as order_date, oi.order_id, o.invoice_id, o.payment_type, oa.note
-    from order_items oi join orders o on o.order_id = oi.order_id join users u on u.user_id = o.user_id join products p on oi.product_id = p.product_id join shops s on s.shop_id = p.shop_id join users u2 on u2.user_id = p.creator_id join order_addresses oa on oa.address_id = o.shipping_address_id
-    where o.order_id = $1", &[&order_id]).await?;
-    let mut contents = fs::read_to_string("./images/invoice.html")?;
+    let row= client.query_one("select u.name customer_name, u.phone, oa.home_address, oa.street_address, oa.city, oa.state, oa.postal_code, oa.country, oa.township, oa.ward, s.name shop_name, coalesce(u2.phone, '') seller_phone, s.address shop_address, to_char(o.created_at, 'FMDD FMMonth YYYY') as order_date, oi.order_id, o.invoice_id, o.payment_type, oa.note
+    from order_items oi join orders o on o.order_id = oi.order_id join users u on u.user_id = o.user_id join products p on oi.product_id = p.product_id join shops s on s.shop_id = p.shop_id left join users u2 on u2.user_id = p.creator_id join order_addresses oa on oa.address_id = o.shipping_address_id
+    where o.order_id = $1 limit 1", &[&order_id]).await?;
 
     let home_address: &str = row.get("home_address");
     let street_address: &str = row.get("street_address");
@@ -31,23 +80,29 @@ pub async fn export_invoice(
         state,
         country,
     ]
+    .into_iter()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<&str>>()
     .join(", ");
-    contents = contents
-        .replace("[customer_name]", row.get("customer_name"))
-        .replace("[customer_phone]", row.get("phone"))
-        .replace("[customer_address]", &customer_address)
-        .replace("[shop_name]", row.get("shop_name"))
-        .replace("[seller_phone]", row.get("seller_phone"))
-        .replace("[seller_address]", row.get("shop_address"))
-        .replace("[order_date]", row.get("order_date"))
-        .replace("[order_id]", &order_id.to_string())
-        .replace("[invoice_id]", row.get("invoice_id"))
-        .replace("[payment_type]", row.get("payment_type"))
-        .replace("[note]", row.get("note"));
-
-    let rows= client.query("select b.name brand_name, p.model, oi.quantity, c.symbol, to_char(oi.price, 'FM999,999,999.00') as price, to_char((oi.quantity * oi.price), 'FM999,999,999.00') as total from order_items oi join products p on p.product_id = oi.product_id join brands b on b.brand_id = p.brand_id join currencies c on c.currency_id = oi.currency_id where oi.order_id = $1 order by b.name, p.model", &[&order_id]).await?;
-
-    let mut order_items = String::new();
+    let invoice_id: String = row.get("invoice_id");
+    let mut invoice = Invoice {
+        customer_name: row.get("customer_name"),
+        customer_phone: row.get("phone"),
+        customer_address,
+        shop_name: row.get("shop_name"),
+        seller_phone: row.get("seller_phone"),
+        seller_address: row.get("shop_address"),
+        order_date: row.get("order_date"),
+        order_id,
+        invoice_id: invoice_id.clone(),
+        payment_type: row.get("payment_type"),
+        note: row.get("note"),
+        items: vec![],
+        sub_total: String::new(),
+    };
+
+    let rows= client.query("select b.name brand_name, p.model, oi.quantity, c.symbol, to_char(oi.price, 'FM999,999,999.00') as price, to_char(coalesce(oi.original_price, oi.price), 'FM999,999,999.00') as original_price, oi.discount_reason, to_char((oi.quantity * oi.price), 'FM999,999,999.00') as total from order_items oi join products p on p.product_id = oi.product_id join brands b on b.brand_id = p.brand_id join currencies c on c.currency_id = oi.currency_id where oi.order_id = $1 order by b.name, p.model", &[&order_id]).await?;
+
     let mut symbol = String::new();
     for row in &rows {
         let brand_name: &str = row.get("brand_name");
@@ -55,62 +110,463 @@ pub async fn export_invoice(
         let quantity: i32 = row.get("quantity");
         symbol = row.get("symbol");
         let price: &str = row.get("price");
+        let original_price: &str = row.get("original_price");
+        let discount_reason: &str = row.get("discount_reason");
         let total: &str = row.get("total");
 
-        order_items = format!(
-            "{order_items} <div class=\"flex row\"><div style=\"text-align: left\">{} {}</div><div>{}</div><div>{} {}</div><div>{} {}</div></div>", brand_name, model, quantity, symbol, price, symbol, total
-        );
+        // Discounted items show the price they were marked down from and why
+        let is_discounted = original_price != price;
+        invoice.items.push(InvoiceItem {
+            description: format!("{} {}", brand_name, model),
+            discount_reason: if is_discounted {
+                discount_reason.to_string()
+            } else {
+                String::new()
+            },
+            quantity: quantity.to_string(),
+            original_price: if is_discounted {
+                Some(format!("{} {}", symbol, original_price))
+            } else {
+                None
+            },
+            price: format!("{} {}", symbol, price),
+            total: format!("{} {}", symbol, total),
+        });
     }
-    contents = contents.replace("[order_items]", &order_items);
 
     let row = client
         .query_one(
-            "select to_char(sum(quantity * price), 'FM999,999,999.00') as sub_total from order_items where order_id = $1",
+            "select to_char(sum(oi.quantity * oi.price) - o.coupon_discount, 'FM999,999,999.00') as sub_total, o.coupon_discount > 0 as has_coupon, to_char(o.coupon_discount, 'FM999,999,999.00') as coupon_discount, coalesce((select c.code from coupon_redemptions cr join coupons c on c.coupon_id = cr.coupon_id where cr.order_id = o.order_id order by cr.redemption_id desc limit 1), '') as coupon_code from order_items oi join orders o on o.order_id = oi.order_id where oi.order_id = $1 group by o.order_id",
             &[&order_id],
         )
         .await?;
+    let has_coupon: bool = row.get("has_coupon");
+    if has_coupon {
+        let coupon_code: &str = row.get("coupon_code");
+        let coupon_discount: &str = row.get("coupon_discount");
+        invoice.items.push(InvoiceItem {
+            description: format!("Coupon {}", coupon_code),
+            discount_reason: String::new(),
+            quantity: String::new(),
+            original_price: None,
+            price: String::new(),
+            total: format!("- {} {}", symbol, coupon_discount),
+        });
+    }
     let sub_total: &str = row.get("sub_total");
-    let sub_total = format!("{symbol} {sub_total}");
-    contents = contents.replace("[sub_total]", &sub_total);
-
-    println!("contents: {}", contents);
-    match report_forge::site_to_pdf(&contents).await {
-        Ok(response) => {
-            let code = response.get("code").and_then(Value::as_i64).unwrap_or(0) as i32;
-            if code == 200 {
-                let invoice_url = response
-                    .get("data")
-                    .and_then(Value::as_str)
-                    .unwrap_or("")
-                    .to_string();
-                println!("invoice_url: {}", invoice_url);
-                client
-                    .execute(
-                        "update orders set invoice_url = $1 where order_id = $2",
-                        &[&invoice_url, &order_id],
-                    )
-                    .await?;
-                let mut payload: HashMap<String, Value> = HashMap::new();
-                payload.insert(
-                    "invoice_url".to_string(),
-                    Value::String(invoice_url.clone()),
-                );
-                match socketio::emit("new-invoice", &vec![user_id], Some(payload)).await {
-                    Ok(_) => {
-                        println!("event sent successfully.");
-                    }
-                    Err(err) => {
-                        println!("{:?}", err);
-                    }
-                };
-                Ok(Some(invoice_url))
-            } else {
-                Ok(None)
-            }
+    invoice.sub_total = format!("{symbol} {sub_total}");
+
+    let file_name = format!("INV-{}.pdf", invoice_id);
+    let path = format!("{REPORTS_DIR}/{file_name}");
+    // Reading the fonts, laying out and writing the file all block, so they run off
+    // the async workers
+    let saved = tokio::task::spawn_blocking(move || save_invoice(&invoice, &path)).await??;
+    if !saved {
+        return Ok(None);
+    }
+
+    let invoice_url = format!("/reports/{file_name}");
+    client
+        .execute(
+            "update orders set invoice_url = $1 where order_id = $2",
+            &[&invoice_url, &order_id],
+        )
+        .await?;
+    let mut payload: HashMap<String, Value> = HashMap::new();
+    payload.insert(
+        "invoice_url".to_string(),
+        Value::String(invoice_url.clone()),
+    );
+    match socketio::emit("new-invoice", &vec![user_id], Some(payload)).await {
+        Ok(_) => {
+            println!("event sent successfully.");
         }
         Err(err) => {
             println!("{:?}", err);
-            Ok(None)
         }
+    };
+    Ok(Some(invoice_url))
+}
+
+// Renders the invoice into `path`, false when it couldn't be rendered
+fn save_invoice(invoice: &Invoice, path: &str) -> std::io::Result<bool> {
+    let pdf = match render_invoice(invoice) {
+        Ok(pdf) => pdf,
+        Err(err) => {
+            println!("Error rendering invoice: {:?}", err);
+            return Ok(false);
+        }
+    };
+    fs::create_dir_all(REPORTS_DIR)?;
+    fs::write(path, pdf)?;
+    Ok(true)
+}
+
+// Lays the invoice out like the old HTML template, on as many pages as the items need.
+// The same invoice always renders to the same bytes.
+fn render_invoice(invoice: &Invoice) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let fonts = load_fonts(FONTS_DIR)?;
+    let mut doc = PdfDocument::new(&fonts)?;
+
+    let text = TextStyle {
+        font: BODY_FONT,
+        size: 10.5,
+        color: TEXT_COLOR,
+    };
+    let small = TextStyle { size: 8.0, ..text };
+    let label = TextStyle { size: 13.2, ..text };
+    let heading = TextStyle {
+        size: 18.0,
+        color: HEADING_COLOR,
+        ..text
+    };
+    let header = TextStyle {
+        color: WHITE,
+        ..text
+    };
+    let thank_you = TextStyle {
+        font: SCRIPT_FONT,
+        size: 36.0,
+        color: Color(0x00, 0x03, 0x23),
+    };
+
+    let width = PAGE_WIDTH - MARGIN * 2.0;
+    let half = width / 2.0;
+    let right = MARGIN + half;
+    let columns: Vec<(f32, f32)> = COLUMNS
+        .iter()
+        .scan(MARGIN, |x, share| {
+            let column = (*x, width * share);
+            *x += width * share;
+            Some(column)
+        })
+        .collect();
+
+    draw_page_background(&mut doc)?;
+
+    let mut left_y = CONTENT_TOP;
+    doc.text(MARGIN, left_y, "Bill To:", &heading);
+    left_y += doc.line_height(&heading);
+    for line in [
+        invoice.customer_name.clone(),
+        phone_label(&invoice.customer_phone),
+        invoice.customer_address.clone(),
+    ]
+    .iter()
+    .filter(|line| !line.is_empty())
+    {
+        for wrapped in doc.wrap(line, &label, half - CELL_PADDING) {
+            doc.text(MARGIN, left_y, &wrapped, &label);
+            left_y += doc.line_height(&label);
+        }
+    }
+
+    let mut right_y = CONTENT_TOP;
+    doc.text_in(right, right_y, half, Align::Right, "From:", &heading);
+    right_y += doc.line_height(&heading);
+    for line in [
+        "Watch Vault Mobile App".to_string(),
+        invoice.shop_name.clone(),
+        phone_label(&invoice.seller_phone),
+        invoice.seller_address.clone(),
+    ]
+    .iter()
+    .filter(|line| !line.is_empty())
+    {
+        for wrapped in doc.wrap(line, &label, half - CELL_PADDING) {
+            doc.text_in(right, right_y, half, Align::Right, &wrapped, &label);
+            right_y += doc.line_height(&label);
+        }
+    }
+
+    let mut y = left_y.max(right_y + 30.0);
+    doc.text(
+        MARGIN,
+        y,
+        &format!("Date: {}", invoice.order_date.trim()),
+        &label,
+    );
+    let numbers = format!(
+        "Order: #{}    No: INV-{}",
+        invoice.order_id, invoice.invoice_id
+    );
+    doc.text_in(
+        MARGIN,
+        y,
+        width,
+        Align::Right,
+        &numbers,
+        &TextStyle {
+            color: HEADING_COLOR,
+            ..label
+        },
+    );
+    y += doc.line_height(&label) + 24.0;
+
+    let header_height = doc.line_height(&header) + CELL_PADDING * 2.0;
+    let draw_header = |doc: &mut PdfDocument, y: f32| {
+        doc.rect(MARGIN, y, width, header_height, HEADER_COLOR);
+        for ((x, column_width), title) in
+            columns.iter().zip(["Description", "Qty", "Price", "Total"])
+        {
+            doc.text_in(
+                *x,
+                y + CELL_PADDING,
+                *column_width,
+                Align::Center,
+                title,
+                &header,
+            );
+        }
+        y + header_height
+    };
+    y = draw_header(&mut doc, y);
+
+    for item in &invoice.items {
+        let description_width = columns[0].1 - CELL_PADDING * 2.0;
+        let description_lines = doc.wrap(&item.description, &text, description_width);
+        let reason_lines = if item.discount_reason.is_empty() {
+            vec![]
+        } else {
+            doc.wrap(&item.discount_reason, &small, description_width)
+        };
+        let description_height = description_lines.len() as f32 * doc.line_height(&text)
+            + reason_lines.len() as f32 * doc.line_height(&small);
+        let price_height = if item.original_price.is_some() {
+            2.0
+        } else {
+            1.0
+        } * doc.line_height(&text);
+        let row_height = description_height.max(price_height) + CELL_PADDING * 2.0;
+
+        if y + row_height > CONTENT_BOTTOM {
+            doc.new_page();
+            draw_page_background(&mut doc)?;
+            y = draw_header(&mut doc, CONTENT_TOP);
+        }
+
+        for (x, column_width) in &columns {
+            doc.stroke_rect(*x, y, *column_width, row_height, BORDER_COLOR, 0.75);
+        }
+
+        let mut line_y = y + CELL_PADDING;
+        for line in &description_lines {
+            doc.text_in(
+                columns[0].0 + CELL_PADDING,
+                line_y,
+                description_width,
+                Align::Left,
+                line,
+                &text,
+            );
+            line_y += doc.line_height(&text);
+        }
+        for line in &reason_lines {
+            doc.text(columns[0].0 + CELL_PADDING, line_y, line, &small);
+            line_y += doc.line_height(&small);
+        }
+
+        doc.text_in(
+            columns[1].0,
+            y + CELL_PADDING,
+            columns[1].1,
+            Align::Center,
+            &item.quantity,
+            &text,
+        );
+
+        let mut price_y = y + CELL_PADDING;
+        if let Some(original_price) = &item.original_price {
+            let x = doc.text_in(
+                columns[2].0,
+                price_y,
+                columns[2].1,
+                Align::Center,
+                original_price,
+                &text,
+            );
+            let strike_y = price_y + doc.line_height(&text) / 2.0;
+            let strike_width = doc.measure(original_price, &text);
+            doc.line(x, strike_y, x + strike_width, strike_y, TEXT_COLOR, 0.75);
+            price_y += doc.line_height(&text);
+        }
+        doc.text_in(
+            columns[2].0,
+            price_y,
+            columns[2].1,
+            Align::Center,
+            &item.price,
+            &text,
+        );
+
+        doc.text_in(
+            columns[3].0,
+            y + CELL_PADDING,
+            columns[3].1,
+            Align::Center,
+            &item.total,
+            &text,
+        );
+        y += row_height;
+    }
+
+    // Everything below the items stays together, on a new page if it has to
+    let footer_height = header_height + 12.0 + 60.0 + 190.0;
+    if y + 12.0 + footer_height > CONTENT_BOTTOM {
+        doc.new_page();
+        draw_page_background(&mut doc)?;
+        y = CONTENT_TOP;
+    } else {
+        y += 12.0;
+    }
+
+    doc.rect(right, y, half, header_height, HEADER_COLOR);
+    doc.text_in(
+        right,
+        y + CELL_PADDING,
+        half / 2.0,
+        Align::Center,
+        "Sub Total",
+        &header,
+    );
+    doc.text_in(
+        right + half / 2.0,
+        y + CELL_PADDING,
+        half / 2.0,
+        Align::Center,
+        &invoice.sub_total,
+        &header,
+    );
+    y += header_height + 12.0;
+
+    let note_x = MARGIN + doc.text(MARGIN, y, "Note:", &label);
+    let mut note_y = y;
+    for line in doc.wrap(&invoice.note, &label, half - (note_x - MARGIN)) {
+        let line_width = doc.text(note_x, note_y, &line, &label);
+        let underline_y = note_y + doc.line_height(&label) - 3.0;
+        doc.line(
+            note_x,
+            underline_y,
+            note_x + line_width,
+            underline_y,
+            TEXT_COLOR,
+            0.75,
+        );
+        note_y += doc.line_height(&label);
+    }
+    doc.text_in(right, y, half, Align::Center, "Thank You!", &thank_you);
+    y = (note_y).max(y + doc.line_height(&thank_you)) + 12.0;
+
+    doc.text(MARGIN, y, "Payment Information:", &label);
+    let payment_y = y + doc.line_height(&label) + 12.0;
+    for (i, line) in doc
+        .wrap(&invoice.payment_type, &heading, half)
+        .iter()
+        .enumerate()
+    {
+        doc.text(
+            MARGIN,
+            payment_y + i as f32 * doc.line_height(&heading),
+            line,
+            &heading,
+        );
+    }
+    doc.image(
+        "./images/qr.jpeg",
+        right + (half - 150.0) / 2.0,
+        y,
+        150.0,
+        150.0,
+    )?;
+
+    Ok(doc.finish())
+}
+
+fn phone_label(phone: &str) -> String {
+    if phone.is_empty() {
+        String::new()
+    } else {
+        format!("+{}", phone)
+    }
+}
+
+fn draw_page_background(doc: &mut PdfDocument) -> Result<(), Box<dyn std::error::Error>> {
+    doc.image(
+        "./images/invoice_background.jpeg",
+        0.0,
+        0.0,
+        PAGE_WIDTH,
+        PAGE_HEIGHT,
+    )?;
+    doc.image("./images/logo.png", PAGE_WIDTH - 181.5, 18.0, 187.5, 187.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const FIXTURES_DIR: &str = "./tests/fixtures/invoices";
+
+    // Renders `<name>.json` and compares it byte for byte with `<name>.pdf`. Run with
+    // UPDATE_GOLDEN=1 to write the golden files again after an intended layout change.
+    fn assert_renders_like_golden(name: &str) {
+        let fixture = fs::read_to_string(format!("{FIXTURES_DIR}/{name}.json")).unwrap();
+        let invoice: Invoice = serde_json::from_str(&fixture).unwrap();
+        let pdf = render_invoice(&invoice).unwrap();
+
+        let golden_path = format!("{FIXTURES_DIR}/{name}.pdf");
+        if env::var("UPDATE_GOLDEN").is_ok() {
+            fs::write(&golden_path, &pdf).unwrap();
+        }
+        let golden = fs::read(&golden_path).unwrap();
+        assert!(
+            pdf == golden,
+            "{name} no longer renders like {golden_path}, run with UPDATE_GOLDEN=1 if the change is intended"
+        );
+    }
+
+    #[test]
+    fn renders_single_item_invoice() {
+        assert_renders_like_golden("single_item");
+    }
+
+    #[test]
+    fn renders_discounted_items_over_several_pages() {
+        assert_renders_like_golden("multi_page");
+    }
+
+    #[test]
+    fn renders_myanmar_text() {
+        assert_renders_like_golden("myanmar");
+    }
+
+    // A character no font has would be drawn as the empty .notdef box, which the golden
+    // file would happily pin
+    #[test]
+    fn fonts_cover_every_character_of_the_myanmar_invoice() {
+        let fixture = fs::read_to_string(format!("{FIXTURES_DIR}/myanmar.json")).unwrap();
+        let fonts = load_fonts(FONTS_DIR).unwrap();
+        let faces: Vec<rustybuzz::Face> = fonts
+            .iter()
+            .map(|font| rustybuzz::Face::from_slice(&font.data, 0).unwrap())
+            .collect();
+        for c in fixture.chars().filter(|c| !c.is_whitespace()) {
+            assert!(
+                faces
+                    .iter()
+                    .any(|face| face.glyph_index(c).is_some_and(|id| id.0 != 0)),
+                "no font in {FONTS_DIR} has a glyph for {c:?} (U+{:04X})",
+                c as u32
+            );
+        }
+    }
+
+    #[test]
+    fn renders_the_same_bytes_every_time() {
+        let fixture = fs::read_to_string(format!("{FIXTURES_DIR}/myanmar.json")).unwrap();
+        let invoice: Invoice = serde_json::from_str(&fixture).unwrap();
+        assert!(render_invoice(&invoice).unwrap() == render_invoice(&invoice).unwrap());
     }
 }
@@ -1,6 +1,7 @@
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
-pub async fn get_movement_countries(client: &Client) -> Result<Vec<String>, Error> {
+pub async fn get_movement_countries(client: &impl GenericClient) -> Result<Vec<String>, Error> {
     let rows = client
         .query(
             "select description from movement_countries where deleted_at is null order by description",
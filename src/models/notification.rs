@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -30,7 +31,7 @@ pub async fn get_notifications(
     per_page: Option<usize>,
     user_id: i32,
     status_list: &Option<Vec<String>>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Notification>, Error> {
     let mut base_query =
         "from notifications n inner join users u on n.user_id = u.user_id where n.deleted_at is null and u.deleted_at is null and n.user_id = $1"
@@ -104,7 +105,7 @@ pub async fn get_notifications(
     })
 }
 
-pub async fn get_unread_counts(user_id: i32, client: &Client) -> Result<i64, Error> {
+pub async fn get_unread_counts(user_id: i32, client: &impl GenericClient) -> Result<i64, Error> {
     let result = client
         .query_one(
             "select count(*) as total from notifications where user_id = $1 and status = 'Unread' and deleted_at is null",
@@ -118,7 +119,7 @@ pub async fn get_unread_counts(user_id: i32, client: &Client) -> Result<i64, Err
 pub async fn update_notification_status(
     notification_id: i32,
     status: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client.execute("update notifications set status = $1 where notification_id = $2 and deleted_at is null", &[&status, &notification_id]).await?;
     Ok(())
@@ -128,11 +129,11 @@ pub async fn add_notification_to_admins(
     title: &str,
     message: &str,
     data: &Option<HashMap<String, Value>>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let user_id_list = get_admin_user_ids(&client).await?;
+    let user_id_list = get_admin_user_ids(client).await?;
     for user_id in user_id_list {
-        add_notification(user_id, title, message, data, &client).await?;
+        add_notification(user_id, title, message, data, client).await?;
     }
     Ok(())
 }
@@ -140,7 +141,7 @@ pub async fn add_notification_to_admins(
 pub async fn add_notifications_for_all_users(
     title: &str,
     message: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let rows = client
         .query(
@@ -165,7 +166,7 @@ pub async fn add_notification(
     title: &str,
     message: &str,
     data: &Option<HashMap<String, Value>>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Box<dyn std::error::Error>> {
     client
         .execute(
@@ -173,7 +174,7 @@ pub async fn add_notification(
             &[&user_id, &title, &message],
         )
         .await?;
-    let fcm_tokens = match get_fcm_tokens(user_id, &client).await {
+    let fcm_tokens = match get_fcm_tokens(user_id, client).await {
         Ok(tokens) => tokens,
         Err(_) => vec![],
     };
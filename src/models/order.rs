@@ -1,14 +1,21 @@
 use chrono::{NaiveDate, NaiveDateTime};
+use deadpool_postgres::GenericClient;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
     sql::{generate_pagination_query, PaginationOptions},
 };
 
-use super::{address::NewAddress, counter, user};
+use super::{
+    address::NewAddress,
+    counter,
+    coupon::{self, CouponCheck},
+    inventory_ledger,
+    pricing::{self, PRICE_COLUMNS},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Order {
@@ -27,6 +34,7 @@ pub struct Order {
     pub note: String,
     pub status: String,
     pub order_total: f64,
+    pub coupon_discount: f64,
     pub item_counts: i32,
     pub payment_type: String,
     pub payslip_screenshot_path: String,
@@ -60,6 +68,9 @@ pub struct OrderItem {
 pub struct NewOrderItem {
     pub product_id: i32,
     pub quantity: i32,
+    // Fixed unit price for items sold outside the catalog, e.g. a winning auction bid
+    #[serde(skip)]
+    pub price: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,13 +81,14 @@ pub struct NewOrder {
     pub payslip_screenshot_path: String,
     pub rule_id: Option<i32>,
     pub shop_id: Option<i32>,
+    pub coupon_code: Option<String>,
 }
 
 pub async fn add_order(
     order: &NewOrder,
     user_id: i32,
     currency_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<i32, Error> {
     client.execute("INSERT INTO user_addresses (user_id, street_address, city, state, postal_code, country, township, home_address, ward) 
     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
@@ -98,34 +110,38 @@ pub async fn add_order(
         .await?;
     let order_id: i32 = order_row.get("order_id");
 
+    client
+        .execute(
+            "insert into order_status_history (order_id, to_status, actor_id) values ($1, 'Pending', $2)",
+            &[&order_id, &user_id],
+        )
+        .await?;
+
+    let pricing_context = pricing::get_pricing_context(user_id, client).await?;
+    let price_query = format!(
+        "select p.product_id, {PRICE_COLUMNS} from products p where p.product_id = $1 and p.deleted_at is null"
+    );
     for item in &order.order_items {
-        // let query = format!("insert into order_items (order_id, product_id, quantity, price, currency_id) values ($1, $2, $3, (select coalesce(case when discount_expiration is null then (price - (price * discount_percent / 100)) when now() >= discount_expiration then price else (price - (price * discount_percent / 100)) end, 0.0) from products where product_id = $4 and deleted_at is null), $5)");
-        let query = format!("INSERT INTO order_items (order_id, product_id, quantity, price, currency_id) 
-VALUES (
-    $1, 
-    $2, 
-    $3, 
-    (SELECT COALESCE(
-        CASE 
-            WHEN p.discount_type = 'No Discount' THEN 
-                p.price 
-            WHEN (p.coupon_code IS NULL OR (p.coupon_code IS NOT NULL AND p.coupon_code IN (SELECT coupon_code FROM used_coupons WHERE user_id = {user_id} AND deleted_at IS NULL))) AND p.discount_type != 'No Discount' THEN
-                CASE 
-                    WHEN p.discount_type = 'Discount by Specific Amount' AND (p.discount_expiration IS NULL OR now()::timestamp < p.discount_expiration) THEN 
-                        p.discounted_price 
-                    WHEN p.discount_expiration IS NULL THEN 
-                        (p.price - (p.price * p.discount_percent / 100)) 
-                    WHEN now()::timestamp >= p.discount_expiration THEN 
-                        p.price 
-                    ELSE 
-                        (p.price - (p.price * p.discount_percent / 100)) 
-                END 
-            ELSE 
-                p.price 
-        END, 0.0) 
-    FROM products p WHERE p.product_id = $4 AND p.deleted_at IS NULL), 
-    $5
-)");
+        if let Some(price) = item.price {
+            let query = format!("insert into order_items (order_id, product_id, quantity, price, original_price, currency_id) values ($1, $2, $3, {price}, {price}, $4)");
+            client
+                .execute(
+                    &query,
+                    &[&order_id, &item.product_id, &item.quantity, &currency_id],
+                )
+                .await?;
+            continue;
+        }
+        let row = client.query_one(&price_query, &[&item.product_id]).await?;
+        let price_breakdown = pricing_context.product_price(&row);
+        let (discount_reason, discount_rule_id) = match &price_breakdown.applied_discount {
+            Some(discount) => (discount.discount_reason.clone(), discount.rule_id),
+            None => (String::new(), None),
+        };
+        let query = format!(
+            "insert into order_items (order_id, product_id, quantity, price, original_price, discount_reason, discount_rule_id, currency_id) values ($1, $2, $3, {}, {}, $4, $5, $6)",
+            price_breakdown.final_price, price_breakdown.original_price
+        );
         client
             .execute(
                 &query,
@@ -133,7 +149,8 @@ VALUES (
                     &order_id,
                     &item.product_id,
                     &item.quantity,
-                    &item.product_id,
+                    &discount_reason,
+                    &discount_rule_id,
                     &currency_id,
                 ],
             )
@@ -149,23 +166,22 @@ VALUES (
             .await?;
     }
 
-    let params: Vec<Box<dyn ToSql + Sync>> = vec![
-        Box::new(order_id),
-        Box::new(order_id),
-        Box::new(order_id),
-        Box::new(order.rule_id.unwrap_or(0)),
-        Box::new(order_id),
-    ];
-    let total_query =
-        "select coalesce(sum(price * quantity), 0) from order_items where deleted_at is null"
-            .to_string();
-    let update_query = format!("update orders set order_total = ({total_query} and order_id = ${}), item_counts = (select count(*) from order_items where order_id = ${} and deleted_at is null), commission_amount = (({total_query} and order_id = ${}) * (select coalesce(commission_percentage / 100, 0) from commission_rules where rule_id = ${} and deleted_at is null)) where order_id = ${} and deleted_at is null", params.len() - 4, params.len() - 3, params.len() - 2, params.len() - 1,  params.len());
-
-    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
-    client.execute(&update_query, &params_slice).await?;
+    update_order_totals(order_id, client).await?;
     Ok(order_id)
 }
 
+// Totals the order's items less its coupon discount, with the commission of its
+// insurance option on that total
+pub async fn update_order_totals(order_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update orders o set order_total = t.total - o.coupon_discount, item_counts = t.item_counts, commission_amount = (t.total - o.coupon_discount) * (select coalesce(r.commission_percentage / 100, 0) from insurance_options i join commission_rules r on r.rule_id = i.rule_id where i.order_id = o.order_id and i.deleted_at is null and r.deleted_at is null) from (select coalesce(sum(price * quantity), 0) as total, count(*) as item_counts from order_items where order_id = $1 and deleted_at is null) t where o.order_id = $1 and o.deleted_at is null",
+            &[&order_id],
+        )
+        .await?;
+    Ok(())
+}
+
 pub async fn get_orders(
     search: &Option<String>,
     page: Option<usize>,
@@ -178,7 +194,9 @@ pub async fn get_orders(
     status: &Option<String>,
     user_id: i32,
     role: &str,
-    client: &Client,
+    can_view_phone: bool,
+    can_view_address: bool,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Order>, Error> {
     let mut base_query =
         "from orders o inner join users u on o.user_id = u.user_id inner join order_addresses a on o.shipping_address_id = a.address_id left join insurance_options i on i.order_id = o.order_id left join commission_rules r on r.rule_id = i.rule_id inner join currencies cur on cur.currency_id = o.currency_id where o.deleted_at is null and u.deleted_at is null and a.deleted_at is null and i.deleted_at is null and r.deleted_at is null and cur.deleted_at is null"
@@ -225,7 +243,7 @@ pub async fn get_orders(
     let order_options = "o.created_at desc".to_string();
 
     let result=  generate_pagination_query(PaginationOptions {
-        select_columns: "o.order_id, o.user_id order_user_id, u.name user_name, u.phone, u.email, a.home_address, a.street_address, a.city, a.state, a.postal_code, a.country, a.township, a.ward, a.note, o.created_at, o.status, o.order_total::text, (coalesce(o.commission_amount, 0))::text as commission_amount, o.item_counts, o.payment_type, o.payslip_screenshot_path, coalesce(r.rule_id, 0) as rule_id, coalesce(r.description, '') as rule_description, cur.symbol, o.invoice_id, o.invoice_url",
+        select_columns: "o.order_id, o.user_id order_user_id, u.name user_name, u.phone, u.email, a.home_address, a.street_address, a.city, a.state, a.postal_code, a.country, a.township, a.ward, a.note, o.created_at, o.status, o.order_total::text, o.coupon_discount::text, (coalesce(o.commission_amount, 0))::text as commission_amount, o.item_counts, o.payment_type, o.payslip_screenshot_path, coalesce(r.rule_id, 0) as rule_id, coalesce(r.description, '') as rule_description, cur.symbol, o.invoice_id, o.invoice_url",
         base_query: &base_query,
         search_columns: vec![ "o.order_id::text", "u.name", "u.phone", "u.email", "a.home_address", "a.street_address", "a.city", "a.state", "a.postal_code", "a.country", "a.township", "a.ward", "a.note","o.status", "o.payment_type", "r.description"],
         search: search.as_deref(),
@@ -248,14 +266,6 @@ pub async fn get_orders(
         page_counts = (total as f64 / limit as f64).ceil() as usize;
     }
 
-    let mut can_view_address = false;
-    let mut can_view_phone = false;
-    if role == "agent" {
-        let permission = user::get_user_permission(user_id, client).await;
-        can_view_address = permission.can_view_address;
-        can_view_phone = permission.can_view_phone;
-    }
-
     let orders = client
         .query(&result.query, &params_slice[..])
         .await?
@@ -264,9 +274,11 @@ pub async fn get_orders(
             let order_user_id: i32 = row.get("order_user_id");
             let order_total: String = row.get("order_total");
             let order_total: f64 = order_total.parse().unwrap();
+            let coupon_discount: String = row.get("coupon_discount");
+            let coupon_discount: f64 = coupon_discount.parse().unwrap();
             let commission_amount: String = row.get("commission_amount");
             let commission_amount: f64 = commission_amount.parse().unwrap();
-            let phone: String = if !can_view_phone && role == "agent" && user_id != order_user_id {
+            let phone: String = if !can_view_phone && user_id != order_user_id {
                 let phone: String = row.get("phone");
                 let re = Regex::new(r"\d").unwrap();
                 format!("959{}", re.replace_all(&phone["959".len()..], "x"))
@@ -295,6 +307,7 @@ pub async fn get_orders(
                 note: row.get("note"),
                 status: row.get("status"),
                 order_total,
+                coupon_discount,
                 commission_amount,
                 created_at: row.get("created_at"),
                 item_counts: row.get("item_counts"),
@@ -329,7 +342,7 @@ pub async fn get_order_items(
     order_id: Option<i32>,
     user_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<OrderItem>, Error> {
     let mut base_query = "from order_items oi inner join orders o on oi.order_id = o.order_id inner join products p on p.product_id = oi.product_id inner join brands b on b.brand_id = p.brand_id inner join currencies cur on cur.currency_id = oi.currency_id where oi.deleted_at is null and o.deleted_at is null and p.deleted_at is null and cur.deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -421,19 +434,170 @@ pub async fn get_order_items(
     })
 }
 
-pub async fn update_order(order_id: i32, status: &str, client: &Client) -> Result<(), Error> {
+// Statuses an order may move to from each status. Refunded is final.
+const ORDER_STATUS_TRANSITIONS: [(&str, &[&str]); 11] = [
+    (
+        "Pending",
+        &[
+            "Processing",
+            "On Hold",
+            "Backordered",
+            "Cancelled",
+            "Failed",
+        ],
+    ),
+    (
+        "Processing",
+        &["Shipped", "On Hold", "Backordered", "Cancelled", "Failed"],
+    ),
+    ("On Hold", &["Pending", "Processing", "Cancelled", "Failed"]),
+    ("Backordered", &["Processing", "Cancelled", "Failed"]),
+    ("Shipped", &["Delivered", "Returned", "Failed"]),
+    ("Delivered", &["Completed", "Returned"]),
+    ("Completed", &["Returned", "Refunded"]),
+    ("Returned", &["Refunded"]),
+    ("Failed", &["Cancelled"]),
+    ("Cancelled", &["Refunded"]),
+    ("Refunded", &[]),
+];
+
+pub fn is_valid_order_status(status: &str) -> bool {
+    ORDER_STATUS_TRANSITIONS.iter().any(|(s, _)| *s == status)
+}
+
+/// Whether an order may move from `from` to `to`.
+///
+/// `update_status` is whether the actor holds `orders.update_status`, which allows any
+/// transition in the table. Without it the actor is the buyer acting through
+/// `orders.manage_own`, who may only cancel an order that has not shipped yet, confirm a
+/// delivered order as completed, or return it once delivered.
+pub fn can_change_order_status(from: &str, to: &str, update_status: bool) -> bool {
+    let allowed = ORDER_STATUS_TRANSITIONS
+        .iter()
+        .find(|(s, _)| *s == from)
+        .is_some_and(|(_, next)| next.contains(&to));
+    if !allowed {
+        return false;
+    }
+    if update_status {
+        return true;
+    }
+    match to {
+        "Cancelled" => ["Pending", "On Hold", "Backordered"].contains(&from),
+        "Completed" => from == "Delivered",
+        "Returned" => ["Delivered", "Completed"].contains(&from),
+        _ => false,
+    }
+}
+
+// Statuses that put the ordered quantities back on the shelf
+const STOCK_RESTORING_STATUSES: [&str; 4] = ["Cancelled", "Refunded", "Returned", "Failed"];
+
+// Statuses that give the order's coupon back to the customer
+const COUPON_RELEASING_STATUSES: [&str; 2] = ["Cancelled", "Failed"];
+
+// Puts the items of an order back into stock. `stock_restored_at` makes sure this happens
+// only once, e.g. a Returned order that is later Refunded is not restocked twice.
+async fn restore_order_stock(
+    order_id: i32,
+    status: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    let restored = client
+        .execute(
+            "UPDATE orders SET stock_restored_at = CURRENT_TIMESTAMP WHERE order_id = $1 AND stock_restored_at IS NULL",
+            &[&order_id],
+        )
+        .await?;
+    if restored == 0 {
+        return Ok(());
+    }
+
+    let reason = format!("order_{}", status.to_lowercase());
+    let rows = client
+        .query(
+            "SELECT product_id, quantity FROM order_items WHERE order_id = $1 AND deleted_at IS NULL",
+            &[&order_id],
+        )
+        .await?;
+    for row in &rows {
+        let product_id: i32 = row.get("product_id");
+        let quantity: i32 = row.get("quantity");
+
+        client
+            .execute(
+                "UPDATE products SET stock_quantity = stock_quantity + $1 WHERE product_id = $2",
+                &[&quantity, &product_id],
+            )
+            .await?;
+        inventory_ledger::add_ledger_entry(product_id, Some(order_id), quantity, &reason, client)
+            .await?;
+    }
+    Ok(())
+}
+
+pub enum OrderStatusUpdate {
+    Updated,
+    NotFound,
+    InvalidTransition(String),
+}
+
+pub async fn update_order(
+    order_id: i32,
+    status: &str,
+    actor_id: i32,
+    update_status: bool,
+    note: &str,
+    client: &mut impl GenericClient,
+) -> Result<OrderStatusUpdate, Error> {
     // Start a transaction
-    client.execute("BEGIN", &[]).await?;
+    let transaction = client.transaction().await?;
 
     // Lock the order row and get the current status
-    let row = client
-        .query_one(
-            "SELECT status AS old_status FROM orders WHERE order_id = $1 AND deleted_at IS NULL FOR UPDATE",
+    let row = match transaction
+        .query_opt(
+            "SELECT o.status AS old_status, o.stock_restored_at IS NOT NULL AS stock_restored, EXISTS (SELECT 1 FROM coupon_redemptions cr WHERE cr.order_id = o.order_id AND cr.released_at IS NOT NULL) AS coupon_released FROM orders o WHERE o.order_id = $1 AND o.deleted_at IS NULL FOR UPDATE OF o",
             &[&order_id],
         )
-        .await?;
-    let old_status: &str = row.get("old_status");
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(OrderStatusUpdate::NotFound);
+        }
+    };
+    let old_status: String = row.get("old_status");
+    let stock_restored: bool = row.get("stock_restored");
+    let coupon_released: bool = row.get("coupon_released");
+
+    // An order whose items went back on the shelf no longer holds any stock, and one whose
+    // coupon was given back no longer holds its redemption, so neither may move on to a
+    // status that would ship them
+    let reactivates =
+        (stock_restored || coupon_released) && !STOCK_RESTORING_STATUSES.contains(&status);
+    if reactivates || !can_change_order_status(&old_status, status, update_status) {
+        transaction.rollback().await?;
+        return Ok(OrderStatusUpdate::InvalidTransition(old_status));
+    }
+
+    change_order_status(order_id, &old_status, status, actor_id, note, &transaction).await?;
+
+    // Commit the transaction
+    transaction.commit().await?;
+
+    Ok(OrderStatusUpdate::Updated)
+}
 
+// Applies an already validated transition; the caller owns the transaction and the row lock.
+pub async fn change_order_status(
+    order_id: i32,
+    old_status: &str,
+    status: &str,
+    actor_id: i32,
+    note: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     // Update the order status
     client
         .execute(
@@ -442,32 +606,60 @@ pub async fn update_order(order_id: i32, status: &str, client: &Client) -> Resul
         )
         .await?;
 
-    // Additional logic for cancelled orders
-    if status == "Cancelled" && old_status != "Cancelled" {
-        let rows = client
-            .query(
-                "SELECT product_id, quantity FROM order_items WHERE order_id = $1 AND deleted_at IS NULL",
-                &[&order_id],
-            )
-            .await?;
-        for row in &rows {
-            let product_id: i32 = row.get("product_id");
-            let quantity: i32 = row.get("quantity");
+    client
+        .execute(
+            "INSERT INTO order_status_history (order_id, from_status, to_status, actor_id, note) VALUES ($1, $2, $3, $4, $5)",
+            &[&order_id, &old_status, &status, &actor_id, &note],
+        )
+        .await?;
 
-            client.execute(
-                "UPDATE products SET stock_quantity = stock_quantity + $1 WHERE product_id = $2",
-                &[&quantity, &product_id]
-            ).await?;
-        }
+    if STOCK_RESTORING_STATUSES.contains(&status) {
+        restore_order_stock(order_id, status, client).await?;
+    }
+    if COUPON_RELEASING_STATUSES.contains(&status) {
+        coupon::release_coupon(order_id, client).await?;
     }
-
-    // Commit the transaction
-    client.execute("COMMIT", &[]).await?;
 
     Ok(())
 }
 
-// pub async fn order_exists(order_id: i32, client: &Client) -> Result<bool, Error> {
+#[derive(Serialize)]
+pub struct OrderStatusHistory {
+    pub history_id: i32,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub actor_id: Option<i32>,
+    pub actor_name: Option<String>,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+pub async fn get_order_status_history(
+    order_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<OrderStatusHistory>, Error> {
+    let rows = client
+        .query(
+            "select h.history_id, h.from_status, h.to_status, h.actor_id, u.name as actor_name, h.note, h.created_at from order_status_history h left join users u on u.user_id = h.actor_id where h.order_id = $1 order by h.created_at asc, h.history_id asc",
+            &[&order_id],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| OrderStatusHistory {
+            history_id: row.get("history_id"),
+            from_status: row.get("from_status"),
+            to_status: row.get("to_status"),
+            actor_id: row.get("actor_id"),
+            actor_name: row.get("actor_name"),
+            note: row.get("note"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+// pub async fn order_exists(order_id: i32, client: &impl GenericClient) -> Result<bool, Error> {
 //     // Execute a query to check if the username exists in the users table
 //     let row = client
 //         .query_one(
@@ -480,7 +672,7 @@ pub async fn update_order(order_id: i32, status: &str, client: &Client) -> Resul
 //     Ok(row.is_ok())
 // }
 
-pub async fn get_user_id_by_order_id(order_id: i32, client: &Client) -> Option<i32> {
+pub async fn get_user_id_by_order_id(order_id: i32, client: &impl GenericClient) -> Option<i32> {
     match client
         .query_one(
             "select user_id from orders where order_id = $1 and deleted_at is null",
@@ -493,7 +685,7 @@ pub async fn get_user_id_by_order_id(order_id: i32, client: &Client) -> Option<i
     }
 }
 
-pub async fn get_status_by_order_id(order_id: i32, client: &Client) -> Option<String> {
+pub async fn get_status_by_order_id(order_id: i32, client: &impl GenericClient) -> Option<String> {
     match client
         .query_one(
             "select status from orders where order_id = $1 and deleted_at is null",
@@ -506,7 +698,10 @@ pub async fn get_status_by_order_id(order_id: i32, client: &Client) -> Option<St
     }
 }
 
-pub async fn are_items_from_single_shop(items: &Vec<NewOrderItem>, client: &Client) -> bool {
+pub async fn are_items_from_single_shop(
+    items: &Vec<NewOrderItem>,
+    client: &impl GenericClient,
+) -> bool {
     let product_ids: Vec<&i32> = items.iter().map(|item| &item.product_id).collect();
     let query =
         "SELECT DISTINCT shop_id FROM products WHERE deleted_at IS NULL AND product_id = ANY($1)";
@@ -519,9 +714,24 @@ pub async fn are_items_from_single_shop(items: &Vec<NewOrderItem>, client: &Clie
     }
 }
 
+pub async fn has_items_in_active_auction(
+    items: &[NewOrderItem],
+    client: &impl GenericClient,
+) -> bool {
+    let product_ids: Vec<&i32> = items.iter().map(|item| &item.product_id).collect();
+    let query = "SELECT EXISTS(SELECT 1 FROM auctions WHERE deleted_at IS NULL AND status = 'active' AND product_id = ANY($1)) AS in_auction";
+    match client.query_one(query, &[&product_ids]).await {
+        Ok(row) => row.get("in_auction"),
+        Err(err) => {
+            println!("{:?}", err);
+            false
+        }
+    }
+}
+
 pub async fn are_items_same_currency_and_get_currency_id(
     items: &Vec<NewOrderItem>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Option<i32> {
     let product_ids: Vec<&i32> = items.iter().map(|item| &item.product_id).collect();
     let query =
@@ -535,7 +745,7 @@ pub async fn are_items_same_currency_and_get_currency_id(
     }
 }
 
-pub async fn get_order_shop_name(order_id: i32, client: &Client) -> String {
+pub async fn get_order_shop_name(order_id: i32, client: &impl GenericClient) -> String {
     match client.query_one("select s.name from order_items oi join products p on p.product_id = oi.product_id join shops s on s.shop_id = p.shop_id where s.deleted_at is null and oi.deleted_at is null and p.deleted_at is null and oi.order_id = $1 limit 1", &[&order_id]).await {
         Ok(row) => row.get("name"),
         Err(err) => {
@@ -545,10 +755,13 @@ pub async fn get_order_shop_name(order_id: i32, client: &Client) -> String {
     }
 }
 
-pub async fn update_stocks(items: &Vec<NewOrderItem>, client: &Client) -> Result<bool, Error> {
-    // Start the transaction
-    client.execute("BEGIN", &[]).await?;
-
+// Takes the ordered quantities off the shelf. Returns `false` without touching the remaining
+// items as soon as one of them is short, so the caller must roll its transaction back.
+pub async fn update_stocks(
+    items: &[NewOrderItem],
+    order_id: i32,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
     for item in items {
         // Lock the row and check the stock quantity
         let row = client
@@ -567,14 +780,61 @@ pub async fn update_stocks(items: &Vec<NewOrderItem>, client: &Client) -> Result
                 "UPDATE products SET stock_quantity = stock_quantity - $1 WHERE product_id = $2",
                 &[&item.quantity, &item.product_id]
             ).await?;
+            inventory_ledger::add_ledger_entry(
+                item.product_id,
+                Some(order_id),
+                -item.quantity,
+                "order_placed",
+                client,
+            )
+            .await?;
         } else {
-            // Not enough stock, rollback the transaction
-            client.execute("ROLLBACK", &[]).await?;
             return Ok(false);
         }
     }
 
-    // If all updates are successful, commit the transaction
-    client.execute("COMMIT", &[]).await?;
     Ok(true)
 }
+
+pub enum OrderPlacement {
+    Placed(i32),
+    InsufficientStock,
+    CouponRejected(String),
+}
+
+// Creates the order and takes its items out of stock in one transaction, so a failure at any
+// step leaves neither a partial order nor a used invoice number behind.
+pub async fn place_order(
+    order: &NewOrder,
+    user_id: i32,
+    currency_id: i32,
+    client: &mut impl GenericClient,
+) -> Result<OrderPlacement, Error> {
+    let transaction = client.transaction().await?;
+
+    let coupon_discount = match order.coupon_code.as_deref().map(str::trim) {
+        Some(code) if !code.is_empty() => {
+            let lines = coupon::get_coupon_lines(&order.order_items, user_id, &transaction).await?;
+            match coupon::check_coupon(code, user_id, &lines, true, &transaction).await? {
+                CouponCheck::Valid(discount) => Some(discount),
+                CouponCheck::Invalid(message) => {
+                    transaction.rollback().await?;
+                    return Ok(OrderPlacement::CouponRejected(message));
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let order_id = add_order(order, user_id, currency_id, &transaction).await?;
+    if let Some(discount) = &coupon_discount {
+        coupon::redeem_coupon(discount, order_id, user_id, &transaction).await?;
+    }
+    if !update_stocks(&order.order_items, order_id, &transaction).await? {
+        transaction.rollback().await?;
+        return Ok(OrderPlacement::InsufficientStock);
+    }
+
+    transaction.commit().await?;
+    Ok(OrderPlacement::Placed(order_id))
+}
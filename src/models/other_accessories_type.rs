@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct OtherAccessoriesType {
@@ -8,7 +9,7 @@ pub struct OtherAccessoriesType {
 }
 
 pub async fn get_other_accessories_types(
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<OtherAccessoriesType>, Error> {
     let rows=  client.query("select other_accessories_type_id, description from other_accessories_types where deleted_at is null order by description", &[]).await?;
 
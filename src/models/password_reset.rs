@@ -0,0 +1,180 @@
+use bcrypt::{hash, verify, DEFAULT_COST};
+use deadpool_postgres::GenericClient;
+use rand::Rng;
+
+use crate::utils::setting::get_password_reset_code_expires_in_minutes;
+
+use super::user;
+
+// A code is thrown away after this many wrong guesses and a new one has to be requested
+const MAX_RESET_CODE_ATTEMPTS: i32 = 5;
+
+fn generate_reset_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+// Replaces any code the user still has open, only the hash is stored and the returned
+// code is sent to the user once
+pub async fn add_reset_code(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let code = generate_reset_code();
+    let code_hash =
+        hash(&code, DEFAULT_COST).map_err(|e| format!("Failed to hash reset code: {}", e))?;
+    client
+        .execute(
+            "update password_reset_codes set used_at = CURRENT_TIMESTAMP where user_id = $1 and used_at is null",
+            &[&user_id],
+        )
+        .await?;
+    client
+        .execute(
+            "insert into password_reset_codes (user_id, code_hash, expires_at) values ($1, $2, CURRENT_TIMESTAMP + make_interval(mins => $3))",
+            &[&user_id, &code_hash, &get_password_reset_code_expires_in_minutes()],
+        )
+        .await?;
+    Ok(code)
+}
+
+pub enum PasswordReset {
+    Reset,
+    InvalidCode,
+}
+
+// An open code of the user as stored, only the hash of the code is kept
+struct ResetCode {
+    reset_code_id: i32,
+    code_hash: String,
+    attempts: i32,
+    expired: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum CodeCheck {
+    Matches,
+    // The wrong guess counts against this code
+    Wrong(i32),
+    NoOpenCode,
+}
+
+// Only the newest code can be used, asking for a code again closes the ones before it.
+// Guesses at an older code count against the newest one.
+fn check_reset_code(codes: &[ResetCode], code: &str) -> CodeCheck {
+    let newest = match codes.first() {
+        Some(newest) if !newest.expired && newest.attempts < MAX_RESET_CODE_ATTEMPTS => newest,
+        _ => return CodeCheck::NoOpenCode,
+    };
+    if verify(code, &newest.code_hash).unwrap_or(false) {
+        CodeCheck::Matches
+    } else {
+        CodeCheck::Wrong(newest.reset_code_id)
+    }
+}
+
+// Sets the new password when the code matches the user's open code. Changing the
+// password also logs out every session of the user.
+pub async fn reset_password(
+    user_id: i32,
+    code: &str,
+    new_password: &str,
+    client: &mut impl GenericClient,
+) -> Result<PasswordReset, Box<dyn std::error::Error>> {
+    let transaction = client.transaction().await?;
+    let codes: Vec<ResetCode> = transaction
+        .query(
+            "select reset_code_id, code_hash, attempts, expires_at <= CURRENT_TIMESTAMP as expired from password_reset_codes where user_id = $1 and used_at is null order by created_at desc for update",
+            &[&user_id],
+        )
+        .await?
+        .iter()
+        .map(|row| ResetCode {
+            reset_code_id: row.get("reset_code_id"),
+            code_hash: row.get("code_hash"),
+            attempts: row.get("attempts"),
+            expired: row.get("expired"),
+        })
+        .collect();
+
+    match check_reset_code(&codes, code) {
+        CodeCheck::Matches => {}
+        CodeCheck::Wrong(reset_code_id) => {
+            transaction
+                .execute(
+                    "update password_reset_codes set attempts = attempts + 1 where reset_code_id = $1",
+                    &[&reset_code_id],
+                )
+                .await?;
+            transaction.commit().await?;
+            return Ok(PasswordReset::InvalidCode);
+        }
+        CodeCheck::NoOpenCode => {
+            transaction.rollback().await?;
+            return Ok(PasswordReset::InvalidCode);
+        }
+    }
+
+    // Every open code is used up, not just the one that matched
+    transaction
+        .execute(
+            "update password_reset_codes set used_at = CURRENT_TIMESTAMP where user_id = $1 and used_at is null",
+            &[&user_id],
+        )
+        .await?;
+    user::change_password(user_id, new_password, &transaction).await?;
+    transaction.commit().await?;
+    Ok(PasswordReset::Reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The lowest cost bcrypt takes, the default one is needlessly slow here
+    const TEST_COST: u32 = 4;
+
+    fn open_code(reset_code_id: i32, code: &str) -> ResetCode {
+        ResetCode {
+            reset_code_id,
+            code_hash: hash(code, TEST_COST).unwrap(),
+            attempts: 0,
+            expired: false,
+        }
+    }
+
+    #[test]
+    fn matching_code_resets() {
+        let codes = [open_code(1, "123456")];
+        assert_eq!(check_reset_code(&codes, "123456"), CodeCheck::Matches);
+    }
+
+    #[test]
+    fn code_is_closed_after_the_maximum_wrong_attempts() {
+        let mut codes = [open_code(1, "123456")];
+        for _ in 0..MAX_RESET_CODE_ATTEMPTS {
+            assert_eq!(check_reset_code(&codes, "654321"), CodeCheck::Wrong(1));
+            codes[0].attempts += 1;
+        }
+        // Not even the right code opens it any more
+        assert_eq!(check_reset_code(&codes, "123456"), CodeCheck::NoOpenCode);
+    }
+
+    #[test]
+    fn code_before_the_newest_is_refused() {
+        let codes = [open_code(2, "222222"), open_code(1, "111111")];
+        assert_eq!(check_reset_code(&codes, "111111"), CodeCheck::Wrong(2));
+        assert_eq!(check_reset_code(&codes, "222222"), CodeCheck::Matches);
+    }
+
+    #[test]
+    fn expired_code_is_refused() {
+        let mut codes = [open_code(1, "123456")];
+        codes[0].expired = true;
+        assert_eq!(check_reset_code(&codes, "123456"), CodeCheck::NoOpenCode);
+    }
+
+    #[test]
+    fn no_code_is_refused() {
+        assert_eq!(check_reset_code(&[], "123456"), CodeCheck::NoOpenCode);
+    }
+}
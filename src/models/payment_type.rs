@@ -1,8 +1,12 @@
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
 use crate::utils::setting;
 
-pub async fn get_payment_types(amount: f64, client: &Client) -> Result<Vec<String>, Error> {
+pub async fn get_payment_types(
+    amount: f64,
+    client: &impl GenericClient,
+) -> Result<Vec<String>, Error> {
     let max_cash_on_delivery_amount = setting::get_max_cash_on_delivery_amount();
 
     let mut query = "select description from payment_types where deleted_at is null".to_string();
@@ -0,0 +1,526 @@
+use chrono::{Local, NaiveDateTime};
+use deadpool_postgres::GenericClient;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::{Error, Row};
+
+pub const NO_DISCOUNT: &str = "No Discount";
+pub const DISCOUNT_BY_PERCENTAGE: &str = "Discount by Specific Percentage";
+pub const DISCOUNT_BY_AMOUNT: &str = "Discount by Specific Amount";
+
+// The product columns `PricingContext::product_price` reads
+pub const PRICE_COLUMNS: &str = "p.shop_id, p.brand_id, p.category_id, p.price::text, p.discount_percent::text, p.discounted_price::text, p.discount_type, p.discount_expiration, p.discount_reason, p.discount_updated_by, p.coupon_code";
+
+// Where a discount was set, declared in order of precedence: a product's own discount
+// wins over its brand's, then its category's, then a shop wide one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscountScope {
+    Product,
+    Brand,
+    Category,
+    All,
+}
+
+impl DiscountScope {
+    // Reads `discount_for` of a rule or `discount_updated_by` of a product
+    pub fn from_discount_for(discount_for: &str) -> Self {
+        match discount_for {
+            "brand" => DiscountScope::Brand,
+            "category" => DiscountScope::Category,
+            "all" => DiscountScope::All,
+            _ => DiscountScope::Product,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Discount {
+    pub rule_id: Option<i32>,
+    pub scope: DiscountScope,
+    pub discount_type: String,
+    pub discount_percent: f64,
+    pub amount_off: f64,
+    pub starts_at: Option<NaiveDateTime>,
+    pub discount_expiration: Option<NaiveDateTime>,
+    pub discount_reason: String,
+    pub coupon_code: Option<String>,
+}
+
+impl Discount {
+    // Coupon discounts only count once the user has applied the coupon
+    fn is_available(&self, applied_coupons: &[String], now: NaiveDateTime) -> bool {
+        if self.discount_type == NO_DISCOUNT {
+            return false;
+        }
+        if let Some(starts_at) = self.starts_at {
+            if now < starts_at {
+                return false;
+            }
+        }
+        if let Some(expiration) = self.discount_expiration {
+            if now >= expiration {
+                return false;
+            }
+        }
+        match &self.coupon_code {
+            Some(code) => applied_coupons.contains(code),
+            None => true,
+        }
+    }
+
+    fn amount_off(&self, price: f64) -> f64 {
+        if self.discount_type == DISCOUNT_BY_AMOUNT {
+            self.amount_off
+        } else {
+            price * self.discount_percent / 100.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedDiscount {
+    pub rule_id: Option<i32>,
+    pub scope: DiscountScope,
+    pub discount_type: String,
+    pub discount_percent: f64,
+    pub discount_expiration: Option<NaiveDateTime>,
+    pub discount_reason: String,
+    pub coupon_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceBreakdown {
+    pub original_price: f64,
+    pub discount_amount: f64,
+    pub final_price: f64,
+    pub applied_discount: Option<AppliedDiscount>,
+}
+
+fn round_amount(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+// Applies the highest precedence discount the user is eligible for. Discounts don't
+// stack: between discounts of the same scope the larger one wins, and on a tie the
+// one listed first. A discount never takes the price below zero.
+pub fn calculate_price(
+    price: f64,
+    discounts: &[Discount],
+    applied_coupons: &[String],
+    now: NaiveDateTime,
+) -> PriceBreakdown {
+    let discount = discounts
+        .iter()
+        .filter(|d| d.is_available(applied_coupons, now))
+        .min_by(|a, b| {
+            a.scope
+                .cmp(&b.scope)
+                .then(b.amount_off(price).total_cmp(&a.amount_off(price)))
+        });
+
+    match discount {
+        Some(d) => {
+            let discount_amount = round_amount(d.amount_off(price).clamp(0.0, price));
+            PriceBreakdown {
+                original_price: price,
+                discount_amount,
+                final_price: round_amount(price - discount_amount),
+                applied_discount: Some(AppliedDiscount {
+                    rule_id: d.rule_id,
+                    scope: d.scope,
+                    discount_type: d.discount_type.clone(),
+                    discount_percent: d.discount_percent,
+                    discount_expiration: d.discount_expiration,
+                    discount_reason: d.discount_reason.clone(),
+                    coupon_code: d.coupon_code.clone(),
+                }),
+            }
+        }
+        None => PriceBreakdown {
+            original_price: price,
+            discount_amount: 0.0,
+            final_price: price,
+            applied_discount: None,
+        },
+    }
+}
+
+// The discount stored on a product row. Amount discounts keep the final price in
+// `discounted_price`, so the amount off is taken from the difference.
+pub fn product_discount(price: f64, row: &Row) -> Discount {
+    let discount_type: String = row.get("discount_type");
+    let discount_percent: String = row.get("discount_percent");
+    let discounted_price: String = row.get("discounted_price");
+    let discounted_price: f64 = discounted_price.parse().unwrap();
+    let discount_updated_by: &str = row.get("discount_updated_by");
+
+    Discount {
+        rule_id: None,
+        scope: DiscountScope::from_discount_for(discount_updated_by),
+        amount_off: if discount_type == DISCOUNT_BY_AMOUNT {
+            price - discounted_price
+        } else {
+            0.0
+        },
+        discount_type,
+        discount_percent: discount_percent.parse().unwrap(),
+        starts_at: None,
+        discount_expiration: row.get("discount_expiration"),
+        discount_reason: row.get("discount_reason"),
+        coupon_code: row.get("coupon_code"),
+    }
+}
+
+// A discount rule of a shop, matched against products when they are priced
+#[derive(Debug, Clone)]
+pub struct RuleDiscount {
+    pub shop_id: i32,
+    pub discount_for_id: i32,
+    pub discount: Discount,
+}
+
+impl RuleDiscount {
+    fn applies_to(&self, product_id: i32, shop_id: i32, brand_id: i32, category_id: i32) -> bool {
+        if self.shop_id != shop_id {
+            return false;
+        }
+        match self.discount.scope {
+            DiscountScope::Product => self.discount_for_id == product_id,
+            DiscountScope::Brand => self.discount_for_id == brand_id,
+            DiscountScope::Category => self.discount_for_id == category_id,
+            DiscountScope::All => true,
+        }
+    }
+}
+
+// What a user's prices depend on, loaded once and reused for every product priced
+#[derive(Default)]
+pub struct PricingContext {
+    pub applied_coupons: Vec<String>,
+    pub rules: Vec<RuleDiscount>,
+}
+
+impl PricingContext {
+    // Prices a product row selected with its `product_id` and `PRICE_COLUMNS`, its own
+    // discount competing with the rules of its shop
+    pub fn product_price(&self, row: &Row) -> PriceBreakdown {
+        let price: String = row.get("price");
+        let price: f64 = price.parse().unwrap();
+        let product_id: i32 = row.get("product_id");
+        let shop_id: Option<i32> = row.get("shop_id");
+        let brand_id: Option<i32> = row.get("brand_id");
+        let category_id: Option<i32> = row.get("category_id");
+
+        let mut discounts = vec![product_discount(price, row)];
+        discounts.extend(
+            self.rules
+                .iter()
+                .filter(|r| {
+                    r.applies_to(
+                        product_id,
+                        shop_id.unwrap_or_default(),
+                        brand_id.unwrap_or_default(),
+                        category_id.unwrap_or_default(),
+                    )
+                })
+                .map(|r| r.discount.clone()),
+        );
+        calculate_price(
+            price,
+            &discounts,
+            &self.applied_coupons,
+            Local::now().naive_local(),
+        )
+    }
+}
+
+pub async fn get_pricing_context(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<PricingContext, Error> {
+    Ok(PricingContext {
+        applied_coupons: get_applied_coupons(user_id, client).await?,
+        rules: get_rule_discounts(client).await?,
+    })
+}
+
+// Rules are never written into products, so deleting or expiring one takes its
+// discount away everywhere at once. Sales that haven't started yet are loaded too and
+// left out by `calculate_price` until their start time.
+pub async fn get_rule_discounts(client: &impl GenericClient) -> Result<Vec<RuleDiscount>, Error> {
+    let rows = client
+        .query(
+            "select rule_id, discount_for, coalesce(discount_for_id, 0) as discount_for_id, coalesce(discount_percent, 0)::text as discount_percent, starts_at, coalesce(ends_at, discount_expiration) as ends_at, coalesce(discount_reason, '') as discount_reason, coalesce(discounted_price, 0)::text as discounted_price, coalesce(discount_type, 'No Discount') as discount_type, coupon_code, shop_id from discount_rules where deleted_at is null and shop_id is not null and discount_for in ('product', 'brand', 'category', 'all') and (coalesce(ends_at, discount_expiration) is null or coalesce(ends_at, discount_expiration) > CURRENT_TIMESTAMP) order by rule_id",
+            &[],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let discount_for: &str = row.get("discount_for");
+            let discount_type: String = row.get("discount_type");
+            let discount_percent: &str = row.get("discount_percent");
+            // Amount rules keep the amount off in `discounted_price`
+            let discounted_price: &str = row.get("discounted_price");
+
+            RuleDiscount {
+                shop_id: row.get("shop_id"),
+                discount_for_id: row.get("discount_for_id"),
+                discount: Discount {
+                    rule_id: Some(row.get("rule_id")),
+                    scope: DiscountScope::from_discount_for(discount_for),
+                    amount_off: if discount_type == DISCOUNT_BY_AMOUNT {
+                        discounted_price.parse().unwrap()
+                    } else {
+                        0.0
+                    },
+                    discount_type,
+                    discount_percent: discount_percent.parse().unwrap(),
+                    starts_at: row.get("starts_at"),
+                    discount_expiration: row.get("ends_at"),
+                    discount_reason: row.get("discount_reason"),
+                    coupon_code: row.get("coupon_code"),
+                },
+            }
+        })
+        .collect())
+}
+
+pub async fn get_applied_coupons(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<String>, Error> {
+    let rows = client
+        .query(
+            "select coupon_code from used_coupons where user_id = $1 and coupon_code is not null and deleted_at is null",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get("coupon_code")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, 15)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn percent(scope: DiscountScope, discount_percent: f64) -> Discount {
+        Discount {
+            rule_id: None,
+            scope,
+            discount_type: DISCOUNT_BY_PERCENTAGE.to_string(),
+            discount_percent,
+            amount_off: 0.0,
+            starts_at: None,
+            discount_expiration: None,
+            discount_reason: String::new(),
+            coupon_code: None,
+        }
+    }
+
+    fn amount(scope: DiscountScope, amount_off: f64) -> Discount {
+        Discount {
+            discount_type: DISCOUNT_BY_AMOUNT.to_string(),
+            discount_percent: 0.0,
+            amount_off,
+            ..percent(scope, 0.0)
+        }
+    }
+
+    fn applied_scope(breakdown: &PriceBreakdown) -> Option<DiscountScope> {
+        breakdown.applied_discount.as_ref().map(|d| d.scope)
+    }
+
+    #[test]
+    fn percentage_discount() {
+        let breakdown =
+            calculate_price(200.0, &[percent(DiscountScope::Product, 15.0)], &[], now());
+        assert_eq!(breakdown.discount_amount, 30.0);
+        assert_eq!(breakdown.final_price, 170.0);
+    }
+
+    #[test]
+    fn amount_discount() {
+        let breakdown = calculate_price(200.0, &[amount(DiscountScope::Product, 25.5)], &[], now());
+        assert_eq!(breakdown.discount_amount, 25.5);
+        assert_eq!(breakdown.final_price, 174.5);
+    }
+
+    #[test]
+    fn amount_discount_never_goes_below_zero() {
+        let breakdown = calculate_price(20.0, &[amount(DiscountScope::Product, 50.0)], &[], now());
+        assert_eq!(breakdown.discount_amount, 20.0);
+        assert_eq!(breakdown.final_price, 0.0);
+    }
+
+    #[test]
+    fn no_discount_keeps_the_price() {
+        let discount = Discount {
+            discount_type: NO_DISCOUNT.to_string(),
+            ..percent(DiscountScope::Product, 50.0)
+        };
+        let breakdown = calculate_price(100.0, &[discount], &[], now());
+        assert_eq!(breakdown.final_price, 100.0);
+        assert!(breakdown.applied_discount.is_none());
+    }
+
+    #[test]
+    fn expired_discount_is_ignored() {
+        let discount = Discount {
+            discount_expiration: Some(now() - chrono::Duration::minutes(1)),
+            ..percent(DiscountScope::Product, 50.0)
+        };
+        let breakdown = calculate_price(100.0, &[discount], &[], now());
+        assert_eq!(breakdown.final_price, 100.0);
+        assert!(breakdown.applied_discount.is_none());
+    }
+
+    #[test]
+    fn discount_applies_until_it_expires() {
+        let discount = Discount {
+            discount_expiration: Some(now() + chrono::Duration::minutes(1)),
+            ..percent(DiscountScope::Product, 50.0)
+        };
+        assert_eq!(
+            calculate_price(100.0, &[discount], &[], now()).final_price,
+            50.0
+        );
+    }
+
+    #[test]
+    fn discount_waits_for_its_start_time() {
+        let discount = Discount {
+            starts_at: Some(now() + chrono::Duration::minutes(1)),
+            ..percent(DiscountScope::Product, 50.0)
+        };
+        assert_eq!(
+            calculate_price(100.0, &[discount], &[], now()).final_price,
+            100.0
+        );
+    }
+
+    #[test]
+    fn coupon_discount_needs_the_used_coupon() {
+        let discount = Discount {
+            coupon_code: Some("WATCH10".to_string()),
+            ..percent(DiscountScope::Product, 10.0)
+        };
+        let discounts = [discount];
+
+        let breakdown = calculate_price(100.0, &discounts, &[], now());
+        assert!(breakdown.applied_discount.is_none());
+
+        let breakdown = calculate_price(100.0, &discounts, &["OTHER".to_string()], now());
+        assert!(breakdown.applied_discount.is_none());
+
+        let breakdown = calculate_price(100.0, &discounts, &["WATCH10".to_string()], now());
+        assert_eq!(breakdown.final_price, 90.0);
+        assert_eq!(
+            breakdown.applied_discount.unwrap().coupon_code.as_deref(),
+            Some("WATCH10")
+        );
+    }
+
+    #[test]
+    fn narrower_scope_wins_even_when_smaller() {
+        let discounts = [
+            percent(DiscountScope::All, 40.0),
+            percent(DiscountScope::Category, 30.0),
+            percent(DiscountScope::Brand, 20.0),
+            percent(DiscountScope::Product, 10.0),
+        ];
+        let breakdown = calculate_price(100.0, &discounts, &[], now());
+        assert_eq!(applied_scope(&breakdown), Some(DiscountScope::Product));
+        assert_eq!(breakdown.final_price, 90.0);
+
+        let breakdown = calculate_price(100.0, &discounts[..3], &[], now());
+        assert_eq!(applied_scope(&breakdown), Some(DiscountScope::Brand));
+
+        let breakdown = calculate_price(100.0, &discounts[..2], &[], now());
+        assert_eq!(applied_scope(&breakdown), Some(DiscountScope::Category));
+
+        let breakdown = calculate_price(100.0, &discounts[..1], &[], now());
+        assert_eq!(applied_scope(&breakdown), Some(DiscountScope::All));
+    }
+
+    #[test]
+    fn unavailable_discount_falls_back_to_the_next_scope() {
+        let product = Discount {
+            coupon_code: Some("WATCH10".to_string()),
+            ..percent(DiscountScope::Product, 10.0)
+        };
+        let discounts = [product, amount(DiscountScope::Brand, 5.0)];
+        let breakdown = calculate_price(100.0, &discounts, &[], now());
+        assert_eq!(applied_scope(&breakdown), Some(DiscountScope::Brand));
+        assert_eq!(breakdown.final_price, 95.0);
+    }
+
+    #[test]
+    fn larger_discount_wins_within_a_scope() {
+        let discounts = [
+            percent(DiscountScope::Brand, 10.0),
+            amount(DiscountScope::Brand, 15.0),
+            percent(DiscountScope::Brand, 12.0),
+        ];
+        let breakdown = calculate_price(100.0, &discounts, &[], now());
+        assert_eq!(breakdown.final_price, 85.0);
+    }
+
+    #[test]
+    fn equal_discounts_keep_the_first_listed() {
+        let first = Discount {
+            rule_id: Some(1),
+            ..percent(DiscountScope::Category, 10.0)
+        };
+        let second = Discount {
+            rule_id: Some(2),
+            ..amount(DiscountScope::Category, 10.0)
+        };
+        let breakdown = calculate_price(100.0, &[first, second], &[], now());
+        assert_eq!(breakdown.applied_discount.unwrap().rule_id, Some(1));
+    }
+
+    #[test]
+    fn discount_scope_reads_discount_for() {
+        assert_eq!(
+            DiscountScope::from_discount_for("brand"),
+            DiscountScope::Brand
+        );
+        assert_eq!(
+            DiscountScope::from_discount_for("category"),
+            DiscountScope::Category
+        );
+        assert_eq!(DiscountScope::from_discount_for("all"), DiscountScope::All);
+        assert_eq!(
+            DiscountScope::from_discount_for("product"),
+            DiscountScope::Product
+        );
+    }
+
+    #[test]
+    fn rules_match_products_of_their_shop_and_scope() {
+        let rule = |scope, discount_for_id| RuleDiscount {
+            shop_id: 1,
+            discount_for_id,
+            discount: percent(scope, 10.0),
+        };
+        // product 7 of shop 1, brand 3, category 5
+        assert!(rule(DiscountScope::Product, 7).applies_to(7, 1, 3, 5));
+        assert!(!rule(DiscountScope::Product, 8).applies_to(7, 1, 3, 5));
+        assert!(rule(DiscountScope::Brand, 3).applies_to(7, 1, 3, 5));
+        assert!(!rule(DiscountScope::Brand, 5).applies_to(7, 1, 3, 5));
+        assert!(rule(DiscountScope::Category, 5).applies_to(7, 1, 3, 5));
+        assert!(!rule(DiscountScope::Category, 3).applies_to(7, 1, 3, 5));
+        assert!(rule(DiscountScope::All, 0).applies_to(7, 1, 3, 5));
+        assert!(!rule(DiscountScope::All, 0).applies_to(7, 2, 3, 5));
+    }
+}
@@ -7,8 +7,14 @@ use crate::utils::{
     vector_finder::add_vector,
 };
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
+
+use super::{
+    inventory_ledger,
+    pricing::{self, PriceBreakdown, PRICE_COLUMNS},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Product {
@@ -64,6 +70,7 @@ pub struct Product {
     pub level: i32,
     pub is_auction_product: bool,
     pub created_at: NaiveDateTime,
+    pub price_breakdown: PriceBreakdown,
 }
 
 pub async fn get_products(
@@ -83,7 +90,7 @@ pub async fn get_products(
     role: &str,
     user_id: i32,
     version: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Product>, Error> {
     let mut base_query = "from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where p.deleted_at is null and b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -199,25 +206,9 @@ pub async fn get_products(
         "p.created_at desc".to_string()
     };
 
-    let select_columns = format!("p.product_id, b.brand_id, b.name brand_name, p.model, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, p.price::text, p.discount_percent::text, CASE 
-    WHEN p.discount_type = 'No Discount' THEN 
-        p.price::text 
-    WHEN (p.coupon_code IS NULL OR (p.coupon_code IS NOT NULL AND p.coupon_code in (select coupon_code from used_coupons where user_id = {user_id} and deleted_at is null))) AND p.discount_type != 'No Discount' THEN
-        CASE 
-            WHEN p.discount_type = 'Discount by Specific Amount' AND (p.discount_expiration IS NULL OR now()::timestamp < p.discount_expiration) THEN 
-                p.discounted_price::text 
-            WHEN p.discount_expiration IS NULL THEN 
-                (p.price - (p.price * p.discount_percent / 100))::text 
-            WHEN now()::timestamp >= p.discount_expiration THEN 
-                p.price::text 
-            ELSE 
-                (p.price - (p.price * p.discount_percent / 100))::text 
-        END 
-    ELSE 
-        p.price::text
-END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_quantity, p.is_top_model, c.category_id, c.name category_name, s.shop_id, s.name shop_name, p.condition, p.warranty_type_id, wt.description warranty_type_description, p.dial_glass_type_id, dgt.description dial_glass_type_description, p.other_accessories_type_id, oat.description other_accessories_type_description, p.gender_id, g.description gender_description, p.waiting_time, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, coalesce(p.creator_id, 0) as creator_id, p.discount_expiration, p.discount_reason, p.discount_type, p.discount_updated_by, p.coupon_code, p.level, p.is_auction_product, p.created_at");
+    let select_columns = "p.product_id, b.brand_id, b.name brand_name, p.model, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, p.price::text, p.discount_percent::text, p.discounted_price::text, p.currency_id, cur.currency_code, cur.symbol, p.stock_quantity, p.is_top_model, c.category_id, c.name category_name, s.shop_id, s.name shop_name, p.condition, p.warranty_type_id, wt.description warranty_type_description, p.dial_glass_type_id, dgt.description dial_glass_type_description, p.other_accessories_type_id, oat.description other_accessories_type_description, p.gender_id, g.description gender_description, p.waiting_time, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, coalesce(p.creator_id, 0) as creator_id, p.discount_expiration, p.discount_reason, p.discount_type, p.discount_updated_by, p.coupon_code, p.level, p.is_auction_product, p.created_at";
     let result = generate_pagination_query(PaginationOptions {
-        select_columns: &select_columns,
+        select_columns,
         base_query: &base_query,
         search_columns: vec![
             "b.name",
@@ -264,6 +255,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
     }
 
     let rows = client.query(&result.query, &params_slice[..]).await?;
+    let pricing_context = pricing::get_pricing_context(user_id, client).await?;
 
     let mut products: Vec<Product> = Vec::new();
 
@@ -284,8 +276,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
         let discount_percent: String = row.get("discount_percent");
         let discount_percent = discount_percent.parse().unwrap();
 
-        let discounted_price: String = row.get("discounted_price");
-        let discounted_price = discounted_price.parse().unwrap();
+        let price_breakdown = pricing_context.product_price(row);
 
         products.push(Product {
             product_id,
@@ -302,7 +293,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
             dimensions: row.get("dimensions"),
             price,
             discount_percent,
-            discounted_price,
+            discounted_price: price_breakdown.final_price,
             stock_quantity: row.get("stock_quantity"),
             is_top_model: row.get("is_top_model"),
             product_images,
@@ -340,6 +331,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
             level: row.get("level"),
             is_auction_product: row.get("is_auction_product"),
             created_at: row.get("created_at"),
+            price_breakdown,
         });
     }
 
@@ -356,7 +348,7 @@ pub async fn get_models(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<String>, Error> {
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
     let result = generate_pagination_query(PaginationOptions {
@@ -448,7 +440,7 @@ pub async fn add_product(
     currency_id: i32,
     creator_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<i32, Error> {
     let mut condition = "".to_string();
     if let Some(c) = &data.condition {
@@ -584,6 +576,16 @@ pub async fn add_product(
         )
         .await?;
     let product_id: i32 = result.get("product_id");
+    if data.stock_quantity != 0 {
+        inventory_ledger::add_ledger_entry(
+            product_id,
+            None,
+            data.stock_quantity,
+            "initial_stock",
+            client,
+        )
+        .await?;
+    }
     for product_image in &data.product_images {
         client
             .execute(
@@ -615,25 +617,13 @@ pub async fn add_product(
     Ok(product_id)
 }
 
-pub async fn get_product_by_id(product_id: i32, user_id: i32, client: &Client) -> Option<Product> {
-    let statement = format!("select p.product_id, b.brand_id, b.name brand_name, p.model, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, p.price::text, p.discount_percent::text, CASE 
-    WHEN p.discount_type = 'No Discount' THEN 
-        p.price::text 
-    WHEN (p.coupon_code IS NULL OR (p.coupon_code IS NOT NULL AND p.coupon_code in (select coupon_code from used_coupons where user_id = {user_id} and deleted_at is null))) AND p.discount_type != 'No Discount' THEN
-        CASE 
-            WHEN p.discount_type = 'Discount by Specific Amount' AND (p.discount_expiration IS NULL OR now()::timestamp < p.discount_expiration) THEN 
-                p.discounted_price::text 
-            WHEN p.discount_expiration IS NULL THEN 
-                (p.price - (p.price * p.discount_percent / 100))::text 
-            WHEN now()::timestamp >= p.discount_expiration THEN 
-                p.price::text 
-            ELSE 
-                (p.price - (p.price * p.discount_percent / 100))::text 
-        END 
-    ELSE 
-        p.price::text
-END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_quantity, p.is_top_model, c.category_id, c.name category_name, s.shop_id, s.name shop_name, p.condition, p.warranty_type_id, wt.description warranty_type_description, p.dial_glass_type_id, dgt.description dial_glass_type_description, p.other_accessories_type_id, oat.description other_accessories_type_description, p.gender_id, g.description gender_description, p.waiting_time, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, coalesce(p.creator_id, 0) as creator_id, p.discount_expiration, p.discount_reason, p.discount_type, p.discount_updated_by, p.coupon_code, p.level, p.is_auction_product, p.created_at from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where p.deleted_at is null and b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null and p.product_id = $1");
-    let result = client.query_one(&statement, &[&product_id]).await;
+pub async fn get_product_by_id(
+    product_id: i32,
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Option<Product> {
+    let statement = "select p.product_id, b.brand_id, b.name brand_name, p.model, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, p.price::text, p.discount_percent::text, p.discounted_price::text, p.currency_id, cur.currency_code, cur.symbol, p.stock_quantity, p.is_top_model, c.category_id, c.name category_name, s.shop_id, s.name shop_name, p.condition, p.warranty_type_id, wt.description warranty_type_description, p.dial_glass_type_id, dgt.description dial_glass_type_description, p.other_accessories_type_id, oat.description other_accessories_type_description, p.gender_id, g.description gender_description, p.waiting_time, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, coalesce(p.creator_id, 0) as creator_id, p.discount_expiration, p.discount_reason, p.discount_type, p.discount_updated_by, p.coupon_code, p.level, p.is_auction_product, p.created_at from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where p.deleted_at is null and b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null and p.product_id = $1";
+    let result = client.query_one(statement, &[&product_id]).await;
 
     let product_images: Vec<String> = match client
         .query(
@@ -645,6 +635,9 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
         Ok(image_rows) => image_rows.iter().map(|r| r.get("image_url")).collect(),
         Err(_) => vec![],
     };
+    let pricing_context = pricing::get_pricing_context(user_id, client)
+        .await
+        .unwrap_or_default();
     match result {
         Ok(row) => {
             let price: String = row.get("price");
@@ -653,8 +646,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
             let discount_percent: String = row.get("discount_percent");
             let discount_percent = discount_percent.parse().unwrap();
 
-            let discounted_price: String = row.get("discounted_price");
-            let discounted_price = discounted_price.parse().unwrap();
+            let price_breakdown = pricing_context.product_price(&row);
 
             Some(Product {
                 product_id: row.get("product_id"),
@@ -671,7 +663,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
                 dimensions: row.get("dimensions"),
                 price,
                 discount_percent,
-                discounted_price,
+                discounted_price: price_breakdown.final_price,
                 stock_quantity: row.get("stock_quantity"),
                 is_top_model: row.get("is_top_model"),
                 product_images,
@@ -709,6 +701,7 @@ END AS discounted_price, p.currency_id, cur.currency_code, cur.symbol, p.stock_q
                 level: row.get("level"),
                 is_auction_product: row.get("is_auction_product"),
                 created_at: row.get("created_at"),
+                price_breakdown,
             })
         }
         Err(err) => {
@@ -724,7 +717,7 @@ pub async fn update_product(
     currency_id: i32,
     old_product: &Product,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let mut condition = "".to_string();
     if let Some(c) = &data.condition {
@@ -867,6 +860,7 @@ pub async fn update_product(
         old_product.level
     };
     // println!("discount_updated_by: {discount_updated_by}");
+    inventory_ledger::record_stock_adjustment(product_id, data.stock_quantity, client).await?;
     let query = format!("update products set shop_id = $1, category_id = $2, brand_id = $3, model = $4, description = $5, color = $6, strap_material = $7, strap_color = $8, case_material = $9, dial_color = $10, movement_type = $11, water_resistance = $12, warranty_period = $13, dimensions = $14, price = {}, discount_percent = {}, stock_quantity = $15, is_top_model = $16, currency_id = $17, condition = $18, warranty_type_id = $19, dial_glass_type_id = $20, other_accessories_type_id = $21, gender_id = $22, waiting_time = $23, case_diameter = $24, case_depth = $25, case_width = $26, is_preorder = $27, movement_caliber = $28, movement_country = $29, discount_expiration = {discount_expiration}, discount_reason = $30, discount_type = $31, discounted_price = {discounted_price}, discount_updated_by = '{discount_updated_by}', level = $32, is_auction_product = $33 where product_id = $34", &data.price, &discount_percent);
     client
         .execute(
@@ -970,7 +964,7 @@ pub async fn update_product(
 pub async fn delete_product(
     product_id: i32,
     old_product_images: &Vec<String>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -1014,7 +1008,7 @@ pub struct ProductAndShopName {
 
 pub async fn get_product_and_shop_names(
     product_id_list: &Vec<i32>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<ProductAndShopName>, Error> {
     if product_id_list.is_empty() {
         return Ok(vec![]);
@@ -1033,7 +1027,7 @@ pub async fn get_product_and_shop_names(
 
 pub async fn get_recommended_products_for_product(
     product_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<i32>, Error> {
     let query = "
         WITH UsersWhoBoughtThisProduct AS (
@@ -1062,7 +1056,7 @@ pub async fn get_recommended_products_for_product(
     Ok(product_ids)
 }
 
-// pub async fn get_top_products_for_user(client: &Client, user_id: i32) -> Result<Vec<i32>, Error> {
+// pub async fn get_top_products_for_user(client: &impl GenericClient, user_id: i32) -> Result<Vec<i32>, Error> {
 //     let query = "
 //         SELECT product_id
 //         FROM order_items
@@ -1080,7 +1074,7 @@ pub async fn get_recommended_products_for_product(
 
 pub async fn get_recommended_products_for_user(
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<i32>, Error> {
     let query = "
         WITH UserProducts AS (
@@ -1115,7 +1109,7 @@ pub async fn get_recommended_products_for_user(
     Ok(product_ids)
 }
 
-pub async fn get_product_creator_id(product_id: i32, client: &Client) -> Option<i32> {
+pub async fn get_product_creator_id(product_id: i32, client: &impl GenericClient) -> Option<i32> {
     match client
         .query_one(
             "select creator_id from products where product_id = $1 and creator_id is not null",
@@ -1128,7 +1122,10 @@ pub async fn get_product_creator_id(product_id: i32, client: &Client) -> Option<
     }
 }
 
-pub async fn get_product_creator_id_from_order_id(order_id: i32, client: &Client) -> i32 {
+pub async fn get_product_creator_id_from_order_id(
+    order_id: i32,
+    client: &impl GenericClient,
+) -> i32 {
     match  client.query_one("select p.creator_id from products p join order_items oi on oi.product_id = p.product_id where oi.order_id = $1 and deleted_at is null limit 1", &[&order_id]).await {
         Ok(row) => row.get("creator_id"),
         Err(err) => {
@@ -1138,7 +1135,11 @@ pub async fn get_product_creator_id_from_order_id(order_id: i32, client: &Client
     }
 }
 
-pub async fn is_products_exist(key: &str, id: i32, client: &Client) -> Result<bool, Error> {
+pub async fn is_products_exist(
+    key: &str,
+    id: i32,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
     let query =
         format!("select count(*) as total from products where {key} = $1 and deleted_at is null");
     let row = client.query_one(&query, &[&id]).await?;
@@ -1146,7 +1147,21 @@ pub async fn is_products_exist(key: &str, id: i32, client: &Client) -> Result<bo
     Ok(total > 0)
 }
 
-pub async fn get_product_images(client: &Client) -> Vec<String> {
+pub async fn is_product_in_shop(
+    product_id: i32,
+    shop_id: i32,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            "select exists (select 1 from products where product_id = $1 and shop_id = $2 and deleted_at is null) as found",
+            &[&product_id, &shop_id],
+        )
+        .await?;
+    Ok(row.get("found"))
+}
+
+pub async fn get_product_images(client: &impl GenericClient) -> Vec<String> {
     match client
         .query("select image_url from product_images", &[])
         .await
@@ -1194,29 +1209,30 @@ pub struct ProductForHtml {
     pub discount_type: String,
 }
 
+// Formats like the product pages always have, e.g. 1,250,000.00
+fn format_price(amount: f64) -> String {
+    let amount = format!("{:.2}", amount);
+    let (whole, cents) = amount.split_at(amount.len() - 3);
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped}{cents}")
+}
+
 pub async fn get_product_for_html(
     product_id: i32,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Option<ProductForHtml> {
-    let statement = format!("select p.product_id, (b.name || ' ' || p.model) as product_name, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, to_char(p.price, 'FM999,999,999.00') as price, p.discount_percent::text, CASE 
-    WHEN p.discount_type = 'No Discount' THEN 
-        to_char(p.price, 'FM999,999,999.00') 
-    WHEN (p.coupon_code IS NULL OR (p.coupon_code IS NOT NULL AND p.coupon_code in (select coupon_code from used_coupons where user_id = {user_id} and deleted_at is null))) AND p.discount_type != 'No Discount' THEN
-        CASE 
-            WHEN p.discount_type = 'Discount by Specific Amount' AND (p.discount_expiration IS NULL OR now()::timestamp < p.discount_expiration) THEN 
-                to_char(p.discounted_price, 'FM999,999,999.00')
-            WHEN p.discount_expiration IS NULL THEN 
-                to_char(p.price - (p.price * p.discount_percent / 100), 'FM999,999,999.00')
-            WHEN now()::timestamp >= p.discount_expiration THEN 
-                to_char(p.price, 'FM999,999,999.00') 
-            ELSE 
-                to_char(p.price - (p.price * p.discount_percent / 100), 'FM999,999,999.00')
-        END 
-    ELSE 
-       to_char(p.price, 'FM999,999,999.00') 
-END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.description warranty_type, dgt.description dial_glass_type, oat.description other_accessories_type, g.description gender, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, p.discount_reason, p.discount_type from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null and p.product_id = $1");
+    let statement = format!("select p.product_id, (b.name || ' ' || p.model) as product_name, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, {PRICE_COLUMNS}, cur.symbol, p.stock_quantity, p.condition, wt.description warranty_type, dgt.description dial_glass_type, oat.description other_accessories_type, g.description gender, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null and p.product_id = $1");
     let result = client.query_one(&statement, &[&product_id]).await;
+    let pricing_context = pricing::get_pricing_context(user_id, client)
+        .await
+        .unwrap_or_default();
 
     let product_images: Vec<String> = match client
         .query(
@@ -1232,6 +1248,7 @@ END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.descripti
         Ok(row) => {
             let discount_percent: String = row.get("discount_percent");
             let discount_percent = discount_percent.parse().unwrap();
+            let price_breakdown = pricing_context.product_price(&row);
 
             let product_name: String = row.get("product_name");
             let unique_id = format!(
@@ -1275,10 +1292,10 @@ END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.descripti
                 other_accessories_type: row.get("other_accessories_type"),
                 gender: row.get("gender"),
                 is_preorder,
-                discounted_price: row.get("discounted_price"),
+                discounted_price: format_price(price_breakdown.final_price),
                 discount_percent,
                 symbol: row.get("symbol"),
-                price: row.get("price"),
+                price: format_price(price_breakdown.original_price),
                 discount_reason: row.get("discount_reason"),
                 discount_type: row.get("discount_type"),
             })
@@ -1292,26 +1309,11 @@ END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.descripti
 
 pub async fn get_products_for_html(
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<Vec<ProductForHtml>, Error> {
-    let statement = format!("select p.product_id, (b.name || ' ' || p.model) as product_name, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, to_char(p.price, 'FM999,999,999.00') as price, p.discount_percent::text, CASE 
-    WHEN p.discount_type = 'No Discount' THEN 
-        to_char(p.price, 'FM999,999,999.00') 
-    WHEN (p.coupon_code IS NULL OR (p.coupon_code IS NOT NULL AND p.coupon_code in (select coupon_code from used_coupons where user_id = {user_id} and deleted_at is null))) AND p.discount_type != 'No Discount' THEN
-        CASE 
-            WHEN p.discount_type = 'Discount by Specific Amount' AND (p.discount_expiration IS NULL OR now()::timestamp < p.discount_expiration) THEN 
-                to_char(p.discounted_price, 'FM999,999,999.00')
-            WHEN p.discount_expiration IS NULL THEN 
-                to_char(p.price - (p.price * p.discount_percent / 100), 'FM999,999,999.00')
-            WHEN now()::timestamp >= p.discount_expiration THEN 
-                to_char(p.price, 'FM999,999,999.00') 
-            ELSE 
-                to_char(p.price - (p.price * p.discount_percent / 100), 'FM999,999,999.00')
-        END 
-    ELSE 
-       to_char(p.price, 'FM999,999,999.00') 
-END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.description warranty_type, dgt.description dial_glass_type, oat.description other_accessories_type, g.description gender, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder, p.discount_reason, p.discount_type from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where p.deleted_at is null and b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null");
+    let statement = format!("select p.product_id, (b.name || ' ' || p.model) as product_name, p.description, p.color, p.strap_material, p.strap_color, p.case_material, p.dial_color, p.movement_type, p.water_resistance, p.warranty_period, p.dimensions, {PRICE_COLUMNS}, cur.symbol, p.stock_quantity, p.condition, wt.description warranty_type, dgt.description dial_glass_type, oat.description other_accessories_type, g.description gender, p.case_diameter, p.case_depth, p.case_width, p.movement_caliber, p.movement_country, p.is_preorder from products p inner join brands b on b.brand_id = p.brand_id inner join categories c on p.category_id = c.category_id inner join shops s on s.shop_id = p.shop_id inner join currencies cur on cur.currency_id = p.currency_id inner join warranty_types wt on wt.warranty_type_id = p.warranty_type_id inner join dial_glass_types dgt on dgt.dial_glass_type_id = p.dial_glass_type_id inner join other_accessories_types oat on oat.other_accessories_type_id = p.other_accessories_type_id inner join genders g on g.gender_id = p.gender_id where p.deleted_at is null and b.deleted_at is null and c.deleted_at is null and s.deleted_at is null and cur.deleted_at is null and wt.deleted_at is null and dgt.deleted_at is null and oat.deleted_at is null and g.deleted_at is null");
     let rows = client.query(&statement, &[]).await?;
+    let pricing_context = pricing::get_pricing_context(user_id, client).await?;
 
     let mut products = vec![];
     for row in &rows {
@@ -1330,6 +1332,7 @@ END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.descripti
 
         let discount_percent: String = row.get("discount_percent");
         let discount_percent = discount_percent.parse().unwrap();
+        let price_breakdown = pricing_context.product_price(row);
 
         let product_name: String = row.get("product_name");
         let unique_id = format!(
@@ -1373,10 +1376,10 @@ END AS discounted_price, cur.symbol, p.stock_quantity, p.condition, wt.descripti
             other_accessories_type: row.get("other_accessories_type"),
             gender: row.get("gender"),
             is_preorder,
-            discounted_price: row.get("discounted_price"),
+            discounted_price: format_price(price_breakdown.final_price),
             discount_percent,
             symbol: row.get("symbol"),
-            price: row.get("price"),
+            price: format_price(price_breakdown.original_price),
             discount_reason: row.get("discount_reason"),
             discount_type: row.get("discount_type"),
         });
@@ -1460,7 +1463,10 @@ pub fn generate_product_html(
     Ok(product.unique_id.to_string())
 }
 
-pub async fn delete_product_html(product_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_product_html(
+    product_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     if let Some(product) = get_product_for_html(product_id, 0, client).await {
         match fs::remove_file(&format!("./products/{}.html", product.unique_id)) {
             Ok(_) => println!("Product html deleted successfully!"),
@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct ReasonType {
@@ -7,7 +8,7 @@ pub struct ReasonType {
     pub description: String,
 }
 
-pub async fn get_reason_types(client: &Client) -> Result<Vec<ReasonType>, Error> {
+pub async fn get_reason_types(client: &impl GenericClient) -> Result<Vec<ReasonType>, Error> {
     let rows=  client.query("select reason_type_id, description from reason_types where deleted_at is null order by description", &[]).await?;
 
     Ok(rows
@@ -0,0 +1,141 @@
+use deadpool_postgres::GenericClient;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use tokio_postgres::Error;
+
+use crate::utils::setting::get_refresh_token_expires_in_days;
+
+fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn generate_refresh_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+// Only the hash is stored, the returned token is handed to the client once
+async fn insert_refresh_token(
+    user_id: i32,
+    session_id: &str,
+    client: &impl GenericClient,
+) -> Result<String, Error> {
+    let token = generate_refresh_token();
+    client
+        .execute(
+            "insert into refresh_tokens (user_id, session_id, token_hash, expires_at) values ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(days => $4))",
+            &[
+                &user_id,
+                &session_id,
+                &hash_refresh_token(&token),
+                &get_refresh_token_expires_in_days(),
+            ],
+        )
+        .await?;
+    Ok(token)
+}
+
+// Starts a new session on login
+pub async fn add_refresh_token(user_id: i32, client: &impl GenericClient) -> Result<String, Error> {
+    let session_id = uuid::Uuid::new_v4().to_string();
+    insert_refresh_token(user_id, &session_id, client).await
+}
+
+pub enum RefreshTokenRotation {
+    Rotated { user_id: i32, refresh_token: String },
+    Invalid,
+}
+
+// Every refresh token can be used once. Presenting one that was already rotated means
+// it was copied, so the whole session is revoked and both holders have to log in again.
+pub async fn rotate_refresh_token(
+    token: &str,
+    client: &mut impl GenericClient,
+) -> Result<RefreshTokenRotation, Error> {
+    let transaction = client.transaction().await?;
+    let row = match transaction
+        .query_opt(
+            "select refresh_token_id, user_id, session_id, revoked_at is not null as revoked, expires_at < CURRENT_TIMESTAMP as expired from refresh_tokens where token_hash = $1 for update",
+            &[&hash_refresh_token(token)],
+        )
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(RefreshTokenRotation::Invalid);
+        }
+    };
+    let refresh_token_id: i32 = row.get("refresh_token_id");
+    let user_id: i32 = row.get("user_id");
+    let session_id: String = row.get("session_id");
+    let revoked: bool = row.get("revoked");
+    let expired: bool = row.get("expired");
+
+    if revoked {
+        revoke_session(&session_id, &transaction).await?;
+        transaction.commit().await?;
+        return Ok(RefreshTokenRotation::Invalid);
+    }
+    if expired {
+        transaction.rollback().await?;
+        return Ok(RefreshTokenRotation::Invalid);
+    }
+
+    transaction
+        .execute(
+            "update refresh_tokens set revoked_at = CURRENT_TIMESTAMP where refresh_token_id = $1",
+            &[&refresh_token_id],
+        )
+        .await?;
+    let refresh_token = insert_refresh_token(user_id, &session_id, &transaction).await?;
+    transaction.commit().await?;
+    Ok(RefreshTokenRotation::Rotated {
+        user_id,
+        refresh_token,
+    })
+}
+
+async fn revoke_session(session_id: &str, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update refresh_tokens set revoked_at = CURRENT_TIMESTAMP where session_id = $1 and revoked_at is null",
+            &[&session_id],
+        )
+        .await?;
+    Ok(())
+}
+
+// Logs out the session the token belongs to, false when the token is unknown
+pub async fn revoke_refresh_token(token: &str, client: &impl GenericClient) -> Result<bool, Error> {
+    let row = client
+        .query_opt(
+            "select session_id from refresh_tokens where token_hash = $1",
+            &[&hash_refresh_token(token)],
+        )
+        .await?;
+    match row {
+        Some(row) => {
+            let session_id: String = row.get("session_id");
+            revoke_session(&session_id, client).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+pub async fn revoke_user_refresh_tokens(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "update refresh_tokens set revoked_at = CURRENT_TIMESTAMP where user_id = $1 and revoked_at is null",
+            &[&user_id],
+        )
+        .await?;
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
-use super::order;
+use super::order::{self, OrderStatusUpdate};
 
 #[derive(Deserialize)]
 pub struct RefundReasonRequest {
@@ -19,7 +20,10 @@ pub struct RefundReason {
     created_at: NaiveDateTime,
 }
 
-pub async fn get_refund_reason_by_order_id(order_id: i32, client: &Client) -> Option<RefundReason> {
+pub async fn get_refund_reason_by_order_id(
+    order_id: i32,
+    client: &impl GenericClient,
+) -> Option<RefundReason> {
     match client.query_one("select u.name customer_name, rt.description reason_type_description, rs.comment, rs.created_at from refund_reasons rs join users u on u.user_id = rs.user_id join reason_types rt on rt.reason_type_id = rs.reason_type_id where rs.order_id = $1 and rs.deleted_at is null and rt.deleted_at is null and u.deleted_at is null", &[&order_id]).await {
     Ok(row) => Some(RefundReason {
         customer_name: row.get("customer_name"),
@@ -37,20 +41,39 @@ pub async fn get_refund_reason_by_order_id(order_id: i32, client: &Client) -> Op
 pub async fn add_refund_reason(
     data: &RefundReasonRequest,
     user_id: i32,
-    client: &Client,
-) -> Result<(), Error> {
-    client.execute("BEGIN", &[]).await?;
+    client: &mut impl GenericClient,
+) -> Result<OrderStatusUpdate, Error> {
+    let transaction = client.transaction().await?;
 
-    let row = client
-        .query_one(
+    let row = match transaction
+        .query_opt(
             "select status from orders where order_id = $1 and deleted_at is null for update",
             &[&data.order_id],
         )
-        .await?;
-    let old_status: &str = row.get("status");
+        .await?
+    {
+        Some(row) => row,
+        None => {
+            transaction.rollback().await?;
+            return Ok(OrderStatusUpdate::NotFound);
+        }
+    };
+    let old_status: String = row.get("status");
     if old_status != "Returned" && old_status != "Refunded" {
-        order::update_order(data.order_id, "Returned", client).await?;
-        client
+        if !order::can_change_order_status(&old_status, "Returned", false) {
+            transaction.rollback().await?;
+            return Ok(OrderStatusUpdate::InvalidTransition(old_status));
+        }
+        order::change_order_status(
+            data.order_id,
+            &old_status,
+            "Returned",
+            user_id,
+            &data.comment,
+            &transaction,
+        )
+        .await?;
+        transaction
             .execute(
                 "insert into refund_reasons (order_id, reason_type_id, comment, user_id) values ($1, $2, $3, $4)",
                 &[&data.order_id, &data.reason_type_id, &data.comment, &user_id],
@@ -58,6 +81,6 @@ pub async fn add_refund_reason(
             .await?;
     }
 
-    client.execute("COMMIT", &[]).await?;
-    Ok(())
+    transaction.commit().await?;
+    Ok(OrderStatusUpdate::Updated)
 }
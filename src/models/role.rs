@@ -0,0 +1,177 @@
+use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Error;
+
+// Listings are scoped by one of these, see roles.base_role
+pub const BASE_ROLES: [&str; 3] = ["admin", "agent", "user"];
+
+#[derive(Serialize)]
+pub struct Permission {
+    pub permission_id: i32,
+    pub name: String,
+    pub description: String,
+}
+
+pub async fn get_permissions(client: &impl GenericClient) -> Result<Vec<Permission>, Error> {
+    let rows = client
+        .query(
+            "select permission_id, name, description from permissions order by name",
+            &[],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| Permission {
+            permission_id: row.get("permission_id"),
+            name: row.get("name"),
+            description: row.get("description"),
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+pub struct Role {
+    pub role_id: i32,
+    pub name: String,
+    pub description: String,
+    pub base_role: String,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub user_counts: i64,
+    pub created_at: NaiveDateTime,
+}
+
+const ROLE_COLUMNS: &str = "r.role_id, r.name, r.description, r.base_role, r.is_system, r.created_at, array(select p.name::text from role_permissions rp inner join permissions p on p.permission_id = rp.permission_id where rp.role_id = r.role_id order by p.name) as permissions, (select count(*) from users u where u.role = r.name and u.deleted_at is null) as user_counts";
+
+fn to_role(row: &tokio_postgres::Row) -> Role {
+    Role {
+        role_id: row.get("role_id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        base_role: row.get("base_role"),
+        is_system: row.get("is_system"),
+        permissions: row.get("permissions"),
+        user_counts: row.get("user_counts"),
+        created_at: row.get("created_at"),
+    }
+}
+
+pub async fn get_roles(client: &impl GenericClient) -> Result<Vec<Role>, Error> {
+    let query = format!("select {ROLE_COLUMNS} from roles r order by r.is_system desc, r.name");
+    let rows = client.query(&query, &[]).await?;
+    Ok(rows.iter().map(to_role).collect())
+}
+
+pub async fn get_role_by_id(
+    role_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<Role>, Error> {
+    let query = format!("select {ROLE_COLUMNS} from roles r where r.role_id = $1");
+    let row = client.query_opt(&query, &[&role_id]).await?;
+    Ok(row.as_ref().map(to_role))
+}
+
+pub async fn role_exists(name: &str, client: &impl GenericClient) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            "select count(*) as total from roles where name = $1",
+            &[&name],
+        )
+        .await?;
+    let total: i64 = row.get("total");
+    Ok(total > 0)
+}
+
+// Names in `permissions` that are not in the permissions table
+pub async fn get_unknown_permissions(
+    permissions: &[String],
+    client: &impl GenericClient,
+) -> Result<Vec<String>, Error> {
+    let row = client
+        .query_one(
+            "select array(select name from unnest($1::text[]) as name where name not in (select name from permissions)) as unknown",
+            &[&permissions],
+        )
+        .await?;
+    Ok(row.get("unknown"))
+}
+
+#[derive(Deserialize)]
+pub struct RoleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub base_role: String,
+    pub permissions: Vec<String>,
+}
+
+async fn set_role_permissions(
+    role_id: i32,
+    permissions: &[String],
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "delete from role_permissions where role_id = $1",
+            &[&role_id],
+        )
+        .await?;
+    client
+        .execute(
+            "insert into role_permissions (role_id, permission_id) select $1, permission_id from permissions where name = any($2)",
+            &[&role_id, &permissions],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn add_role(data: &RoleRequest, client: &mut impl GenericClient) -> Result<i32, Error> {
+    let description = data.description.as_deref().unwrap_or("");
+    let transaction = client.transaction().await?;
+    let row = transaction
+        .query_one(
+            "insert into roles (name, description, base_role) values ($1, $2, $3) returning role_id",
+            &[&data.name, &description, &data.base_role],
+        )
+        .await?;
+    let role_id: i32 = row.get("role_id");
+    set_role_permissions(role_id, &data.permissions, &transaction).await?;
+    transaction.commit().await?;
+    Ok(role_id)
+}
+
+// Users keep the role by name, so a rename moves them along with it. Their tokens carry
+// the old name and are retired.
+pub async fn update_role(
+    role_id: i32,
+    old_name: &str,
+    data: &RoleRequest,
+    client: &mut impl GenericClient,
+) -> Result<(), Error> {
+    let description = data.description.as_deref().unwrap_or("");
+    let transaction = client.transaction().await?;
+    transaction
+        .execute(
+            "update roles set name = $1, description = $2, base_role = $3 where role_id = $4",
+            &[&data.name, &description, &data.base_role, &role_id],
+        )
+        .await?;
+    if old_name != data.name {
+        transaction
+            .execute(
+                "update users set role = $1, token_version = token_version + 1 where role = $2",
+                &[&data.name, &old_name],
+            )
+            .await?;
+    }
+    set_role_permissions(role_id, &data.permissions, &transaction).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+pub async fn delete_role(role_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute("delete from roles where role_id = $1", &[&role_id])
+        .await?;
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use std::fs;
 
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
-pub async fn save_seller_agreement_contract(file_path: &str, client: &Client) -> Result<(), Error> {
+pub async fn save_seller_agreement_contract(
+    file_path: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     let result = client
         .query_one(
             "select file_path from seller_agreement_contract limit 1",
@@ -39,7 +43,7 @@ pub async fn save_seller_agreement_contract(file_path: &str, client: &Client) ->
     Ok(())
 }
 
-pub async fn get_seller_agreement_contract(client: &Client) -> Option<String> {
+pub async fn get_seller_agreement_contract(client: &impl GenericClient) -> Option<String> {
     match client
         .query_one(
             "select file_path from seller_agreement_contract limit 1",
@@ -0,0 +1,238 @@
+use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
+use serde::Serialize;
+use tokio_postgres::{types::ToSql, Error};
+
+use crate::utils::{
+    common_struct::PaginationResult,
+    sql::{generate_pagination_query, PaginationOptions},
+};
+
+use super::seller_information::{get_seller_information, SellerInformation};
+
+pub const APPROVED: &str = "Approved";
+pub const REJECTED: &str = "Rejected";
+pub const DOCUMENTS_REQUESTED: &str = "Documents Requested";
+
+pub fn is_valid_decision(decision: &str) -> bool {
+    [APPROVED, REJECTED, DOCUMENTS_REQUESTED].contains(&decision)
+}
+
+// A user waiting to become a seller, either a new agent registration (account still
+// pending) or a buyer who asked to upgrade. The last decision shows whether documents
+// were already asked for.
+#[derive(Serialize)]
+pub struct SellerApplication {
+    pub user_id: i32,
+    pub name: String,
+    pub username: String,
+    pub email: String,
+    pub phone: String,
+    pub profile_image: String,
+    pub role: String,
+    pub account_status: String,
+    pub last_decision: Option<String>,
+    pub last_decision_reason: Option<String>,
+    pub last_decided_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub seller_information: Option<SellerInformation>,
+}
+
+#[derive(Serialize)]
+pub struct SellerApplicationDecision {
+    pub decision_id: i32,
+    pub user_id: i32,
+    pub decision: String,
+    pub reason: String,
+    pub decided_by: i32,
+    pub decided_by_name: String,
+    pub shop_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+const APPLICATION_COLUMNS: &str = "u.user_id, u.name, u.username, coalesce(u.email, '') as email, coalesce(u.phone, '') as phone, coalesce(u.profile_image, '') as profile_image, u.role, u.account_status, ld.decision as last_decision, ld.reason as last_decision_reason, ld.created_at as last_decided_at, u.created_at";
+
+const APPLICATION_QUERY: &str = "from users u left join lateral (select decision, reason, created_at from seller_application_decisions d where d.user_id = u.user_id order by decision_id desc limit 1) ld on true where u.deleted_at is null and u.request_to_agent = true";
+
+async fn to_seller_application(
+    row: &tokio_postgres::Row,
+    client: &impl GenericClient,
+) -> SellerApplication {
+    let user_id: i32 = row.get("user_id");
+    SellerApplication {
+        user_id,
+        name: row.get("name"),
+        username: row.get("username"),
+        email: row.get("email"),
+        phone: row.get("phone"),
+        profile_image: row.get("profile_image"),
+        role: row.get("role"),
+        account_status: row.get("account_status"),
+        last_decision: row.get("last_decision"),
+        last_decision_reason: row.get("last_decision_reason"),
+        last_decided_at: row.get("last_decided_at"),
+        created_at: row.get("created_at"),
+        seller_information: get_seller_information(user_id, client).await,
+    }
+}
+
+pub async fn get_seller_applications(
+    search: &Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    client: &impl GenericClient,
+) -> Result<PaginationResult<SellerApplication>, Error> {
+    let params: Vec<Box<dyn ToSql + Sync>> = vec![];
+
+    let result = generate_pagination_query(PaginationOptions {
+        select_columns: APPLICATION_COLUMNS,
+        base_query: APPLICATION_QUERY,
+        search_columns: vec!["u.name", "u.username", "u.email", "u.phone"],
+        search: search.as_deref(),
+        order_options: Some("u.created_at"),
+        page,
+        per_page,
+    });
+
+    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
+
+    let row = client.query_one(&result.count_query, &params_slice).await?;
+    let total: i64 = row.get("total");
+
+    let mut page_counts = 0;
+    let mut current_page = 0;
+    let mut limit = 0;
+    if let (Some(p), Some(pp)) = (page, per_page) {
+        current_page = p;
+        limit = pp;
+        page_counts = (total as f64 / limit as f64).ceil() as usize;
+    }
+
+    let rows = client.query(&result.query, &params_slice[..]).await?;
+    let mut applications = vec![];
+    for row in &rows {
+        applications.push(to_seller_application(row, client).await);
+    }
+
+    Ok(PaginationResult {
+        data: applications,
+        total,
+        page: current_page,
+        per_page: limit,
+        page_counts,
+    })
+}
+
+pub async fn get_seller_application(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<SellerApplication>, Error> {
+    let query = format!(
+        "select {} {} and u.user_id = $1",
+        APPLICATION_COLUMNS, APPLICATION_QUERY
+    );
+    match client.query_opt(&query, &[&user_id]).await? {
+        Some(row) => Ok(Some(to_seller_application(&row, client).await)),
+        None => Ok(None),
+    }
+}
+
+pub async fn get_decisions(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<SellerApplicationDecision>, Error> {
+    let rows = client
+        .query(
+            "select d.decision_id, d.user_id, d.decision, d.reason, d.decided_by, u.name as decided_by_name, d.shop_id, d.created_at from seller_application_decisions d join users u on u.user_id = d.decided_by where d.user_id = $1 order by d.decision_id desc",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| SellerApplicationDecision {
+            decision_id: row.get("decision_id"),
+            user_id: row.get("user_id"),
+            decision: row.get("decision"),
+            reason: row.get("reason"),
+            decided_by: row.get("decided_by"),
+            decided_by_name: row.get("decided_by_name"),
+            shop_id: row.get("shop_id"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+// Makes the applicant an active agent and opens a shop for them from their seller
+// information, unless they already have one. Returns the new shop.
+async fn approve(user_id: i32, client: &impl GenericClient) -> Result<Option<i32>, Error> {
+    client
+        .execute(
+            "update users set role = 'agent', account_status = 'active', request_to_agent = false, token_version = token_version + 1 where user_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    let row = client
+        .query_opt(
+            "insert into shops (name, description, cover_image, address, city, state, postal_code, country, phone, email, website_url, operating_hours, status, creator_id) select coalesce(nullif(si.shop_or_page_name, ''), nullif(si.company_name, ''), u.name), '', coalesce(si.facebook_page_image, ''), coalesce(si.address, ''), '', '', '', '', coalesce(nullif(si.bussiness_phone, ''), u.phone, ''), coalesce(u.email, ''), '', '', 'Active', u.user_id from users u left join seller_informations si on si.user_id = u.user_id and si.deleted_at is null where u.user_id = $1 and not exists (select 1 from shops s where s.creator_id = u.user_id and s.deleted_at is null) limit 1 returning shop_id",
+            &[&user_id],
+        )
+        .await?;
+    Ok(row.map(|row| row.get("shop_id")))
+}
+
+// A rejected applicant keeps, or for a new registration gets, an active buyer account
+// and may apply again later
+async fn reject(user_id: i32, client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .execute(
+            "update users set role = 'user', account_status = 'active', request_to_agent = false, token_version = token_version + (case when (role, account_status) is distinct from ('user', 'active') then 1 else 0 end) where user_id = $1",
+            &[&user_id],
+        )
+        .await?;
+    Ok(())
+}
+
+pub enum Decided {
+    Recorded { shop_id: Option<i32> },
+    // The user has no open application, e.g. another admin decided first
+    NotPending,
+}
+
+// Records the decision and applies it, asking for documents leaves the application
+// open. Approval reports the starter shop it created.
+pub async fn decide(
+    user_id: i32,
+    decision: &str,
+    reason: &str,
+    decided_by: i32,
+    client: &mut impl GenericClient,
+) -> Result<Decided, Error> {
+    let transaction = client.transaction().await?;
+    let pending = transaction
+        .query_opt(
+            "select user_id from users where user_id = $1 and request_to_agent = true and deleted_at is null for update",
+            &[&user_id],
+        )
+        .await?;
+    if pending.is_none() {
+        transaction.rollback().await?;
+        return Ok(Decided::NotPending);
+    }
+
+    let shop_id = match decision {
+        APPROVED => approve(user_id, &transaction).await?,
+        REJECTED => {
+            reject(user_id, &transaction).await?;
+            None
+        }
+        _ => None,
+    };
+    transaction
+        .execute(
+            "insert into seller_application_decisions (user_id, decision, reason, decided_by, shop_id) values ($1, $2, $3, $4, $5)",
+            &[&user_id, &decision, &reason, &decided_by, &shop_id],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(Decided::Recorded { shop_id })
+}
@@ -1,5 +1,5 @@
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::Client;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SellerInformation {
@@ -60,7 +60,10 @@ pub struct SellerInformationRequest {
     pub monthly_transaction_screenshot: Option<String>,
 }
 
-pub async fn get_seller_information(user_id: i32, client: &Client) -> Option<SellerInformation> {
+pub async fn get_seller_information(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Option<SellerInformation> {
     let product_counts_query =
         "select count(*) from products where creator_id = $1 and deleted_at is null";
     let sold_product_counts_query = "select coalesce(sum(oi.quantity), 0) from order_items oi inner join products p on p.product_id = oi.product_id inner join orders o on o.order_id = oi.order_id where oi.deleted_at is null and p.deleted_at is null and o.deleted_at is null and o.status = 'Completed' and p.creator_id = $2";
@@ -1,6 +1,7 @@
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -23,7 +24,7 @@ pub async fn get_seller_registration_fees(
     page: Option<usize>,
     per_page: Option<usize>,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<SellerRegistrationFee>, Error> {
     let base_query = "from seller_registration_fees f join currencies c on c.currency_id = f.currency_id where f.deleted_at is null".to_string();
     let params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -97,7 +98,7 @@ pub struct SellerRegistrationFeeRequest {
 
 pub async fn add_seller_registration_fee(
     data: &SellerRegistrationFeeRequest,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let query = format!("insert into seller_registration_fees (description, amount, is_percent, currency_id) values ($1, {}, $2, $3)", data.amount);
     client
@@ -112,7 +113,7 @@ pub async fn add_seller_registration_fee(
 pub async fn update_seller_registration_fee(
     data: &SellerRegistrationFeeRequest,
     fee_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let query = format!("update seller_registration_fees set description = $1, amount = {}, is_percent = $2, currency_id = $3 where fee_id = $4 and deleted_at is null", data.amount);
     client
@@ -131,7 +132,7 @@ pub async fn update_seller_registration_fee(
 
 pub async fn get_seller_registration_fee_by_id(
     fee_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Option<SellerRegistrationFee> {
     let result = client
         .query_one(
@@ -158,7 +159,10 @@ pub async fn get_seller_registration_fee_by_id(
     }
 }
 
-pub async fn delete_seller_registration_fee(fee_id: i32, client: &Client) -> Result<(), Error> {
+pub async fn delete_seller_registration_fee(
+    fee_id: i32,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     client.execute(
         "update seller_registration_fees set deleted_at = CURRENT_TIMESTAMP where fee_id = $1 and deleted_at is null",
         &[&fee_id],
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -18,7 +19,7 @@ pub struct ReportSubject {
     pub description: String,
 }
 
-pub async fn get_report_subjects(client: &Client) -> Result<Vec<ReportSubject>, Error> {
+pub async fn get_report_subjects(client: &impl GenericClient) -> Result<Vec<ReportSubject>, Error> {
     let rows = client
         .query(
             "select subject_id, description from report_subjects where deleted_at is null",
@@ -45,7 +46,7 @@ pub struct SellerReportRequest {
 pub async fn add_seller_report(
     data: &SellerReportRequest,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let row= client
         .query_one(
@@ -67,7 +68,7 @@ pub async fn add_seller_report(
         Value::String("seller-report-detail".to_string()),
     );
     map.insert("id".to_string(), Value::Number(report_id.into()));
-    match notification::add_notification_to_admins(&title, &message, &Some(map), &client).await {
+    match notification::add_notification_to_admins(&title, &message, &Some(map), client).await {
         Ok(()) => {
             println!("Notification added successfully.");
         }
@@ -94,7 +95,7 @@ pub async fn get_seller_reports(
     search: &Option<String>,
     page: Option<usize>,
     per_page: Option<usize>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<SellerReport>, Error> {
     let base_query =
         "from seller_reports sr join users u1 on u1.user_id = sr.user_id join users u2 on u2.user_id = sr.seller_id join report_subjects rs on rs.subject_id = sr.subject_id where sr.deleted_at is null"
@@ -151,7 +152,10 @@ pub async fn get_seller_reports(
     })
 }
 
-pub async fn get_seller_report_by_id(report_id: i32, client: &Client) -> Option<SellerReport> {
+pub async fn get_seller_report_by_id(
+    report_id: i32,
+    client: &impl GenericClient,
+) -> Option<SellerReport> {
     match client.query_one("select report_id, u1.name user_name, u2.name seller_name, rs.description subject, sr.message, sr.phone, sr.created_at from seller_reports sr join users u1 on u1.user_id = sr.user_id join users u2 on u2.user_id = sr.seller_id join report_subjects rs on rs.subject_id = sr.subject_id where sr.deleted_at is null and sr.report_id = $1", &[&report_id]).await {
         Ok(row) => Some(SellerReport {
             report_id: row.get("report_id"),
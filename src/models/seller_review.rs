@@ -1,6 +1,7 @@
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Deserialize)]
 pub struct SellerReviewRequest {
@@ -12,7 +13,7 @@ pub struct SellerReviewRequest {
 pub async fn add_review(
     data: &SellerReviewRequest,
     user_id: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     match client.query_one("select review_id from seller_reviews where user_id = $1 and shop_id = $2 and deleted_at is null", &[&user_id, &data.shop_id]).await {
         Ok(row) => {
@@ -38,7 +39,10 @@ pub struct SellerReview {
     pub review_date: NaiveDateTime,
 }
 
-pub async fn get_seller_reviews(shop_id: i32, client: &Client) -> Result<Vec<SellerReview>, Error> {
+pub async fn get_seller_reviews(
+    shop_id: i32,
+    client: &impl GenericClient,
+) -> Result<Vec<SellerReview>, Error> {
     let rows= client.query(
         "select r.review_id, u.name, u.profile_image, r.rating::text, r.comment, r.review_date from seller_reviews r inner join users u on u.user_id = r.user_id where r.deleted_at is null and u.deleted_at is null and r.shop_id = $1",
         &[&shop_id],
@@ -60,7 +64,11 @@ pub async fn get_seller_reviews(shop_id: i32, client: &Client) -> Result<Vec<Sel
         .collect())
 }
 
-pub async fn is_user_already_review(shop_id: i32, user_id: i32, client: &Client) -> bool {
+pub async fn is_user_already_review(
+    shop_id: i32,
+    user_id: i32,
+    client: &impl GenericClient,
+) -> bool {
     match client.query_one("select count(*) as total from seller_reviews where user_id = $1 and shop_id = $2 and deleted_at is null", &[&user_id, &shop_id]).await {
     Ok(row) => {
         let total: i64 = row.get("total");
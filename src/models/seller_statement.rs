@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use deadpool_postgres::GenericClient;
+use serde::Serialize;
+use serde_json::Value;
+use tokio_postgres::{types::ToSql, Error};
+
+use crate::utils::{
+    common_struct::PaginationResult,
+    setting::{get_seller_statement_due_days, get_seller_statement_review_days},
+    sql::{generate_pagination_query, PaginationOptions},
+};
+
+use super::notification::{add_notification, add_notification_to_admins};
+
+pub const UNPAID: &str = "Unpaid";
+pub const PAID: &str = "Paid";
+
+#[derive(Serialize)]
+pub struct SellerStatement {
+    pub statement_id: i32,
+    pub seller_id: i32,
+    pub seller_name: String,
+    pub fee_id: Option<i32>,
+    pub fee_description: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub fee_amount: f64,
+    pub is_percent: bool,
+    pub sales_total: f64,
+    pub amount_due: f64,
+    pub symbol: String,
+    pub status: String,
+    pub is_overdue: bool,
+    pub due_at: NaiveDateTime,
+    pub payslip_screenshot_path: String,
+    pub payment_submitted_at: Option<NaiveDateTime>,
+    pub paid_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+// An unpaid statement is overdue from its due date. A payslip waiting for review only
+// holds that off for the review days, so a payslip nobody accepts cannot keep a shop
+// open. `alias` is the table alias with its dot, or empty.
+fn overdue_condition(alias: &str) -> String {
+    format!(
+        "({alias}status = 'Unpaid' and {alias}due_at < now() or {alias}status = 'Payment Submitted' and {alias}due_at + make_interval(days => {}) < now())",
+        get_seller_statement_review_days()
+    )
+}
+
+fn statement_columns() -> String {
+    format!("s.statement_id, s.seller_id, u.name seller_name, s.fee_id, coalesce(f.description, '') fee_description, s.period_start, s.period_end, s.fee_amount::text, s.is_percent, s.sales_total::text, s.amount_due::text, cur.symbol, s.status, {} is_overdue, s.due_at, coalesce(s.payslip_screenshot_path, '') payslip_screenshot_path, s.payment_submitted_at, s.paid_at, s.created_at", overdue_condition("s."))
+}
+
+const STATEMENT_QUERY: &str = "from seller_statements s inner join users u on u.user_id = s.seller_id left join seller_registration_fees f on f.fee_id = s.fee_id inner join currencies cur on cur.currency_id = s.currency_id where 1 = 1";
+
+fn to_seller_statement(row: &tokio_postgres::Row) -> SellerStatement {
+    let fee_amount: String = row.get("fee_amount");
+    let sales_total: String = row.get("sales_total");
+    let amount_due: String = row.get("amount_due");
+    SellerStatement {
+        statement_id: row.get("statement_id"),
+        seller_id: row.get("seller_id"),
+        seller_name: row.get("seller_name"),
+        fee_id: row.get("fee_id"),
+        fee_description: row.get("fee_description"),
+        period_start: row.get("period_start"),
+        period_end: row.get("period_end"),
+        fee_amount: fee_amount.parse().unwrap(),
+        is_percent: row.get("is_percent"),
+        sales_total: sales_total.parse().unwrap(),
+        amount_due: amount_due.parse().unwrap(),
+        symbol: row.get("symbol"),
+        status: row.get("status"),
+        is_overdue: row.get("is_overdue"),
+        due_at: row.get("due_at"),
+        payslip_screenshot_path: row.get("payslip_screenshot_path"),
+        payment_submitted_at: row.get("payment_submitted_at"),
+        paid_at: row.get("paid_at"),
+        created_at: row.get("created_at"),
+    }
+}
+
+// `seller_id` limits the list to one seller, sellers only ever see their own statements
+pub async fn get_seller_statements(
+    page: Option<usize>,
+    per_page: Option<usize>,
+    seller_id: Option<i32>,
+    status: &Option<String>,
+    overdue: Option<bool>,
+    client: &impl GenericClient,
+) -> Result<PaginationResult<SellerStatement>, Error> {
+    let mut base_query = STATEMENT_QUERY.to_string();
+    let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
+
+    if let Some(id) = seller_id {
+        params.push(Box::new(id));
+        base_query = format!("{base_query} and s.seller_id = ${}", params.len());
+    }
+    if let Some(s) = status {
+        params.push(Box::new(s));
+        base_query = format!("{base_query} and s.status = ${}", params.len());
+    }
+    if let Some(true) = overdue {
+        base_query = format!("{base_query} and {}", overdue_condition("s."));
+    }
+
+    let select_columns = statement_columns();
+    let result = generate_pagination_query(PaginationOptions {
+        select_columns: &select_columns,
+        base_query: &base_query,
+        search_columns: vec![],
+        search: None,
+        order_options: Some("s.period_start desc, u.name"),
+        page,
+        per_page,
+    });
+
+    let params_slice: Vec<&(dyn ToSql + Sync)> = params.iter().map(AsRef::as_ref).collect();
+
+    let row = client.query_one(&result.count_query, &params_slice).await?;
+    let total: i64 = row.get("total");
+
+    let mut page_counts = 0;
+    let mut current_page = 0;
+    let mut limit = 0;
+    if let (Some(p), Some(pp)) = (page, per_page) {
+        current_page = p;
+        limit = pp;
+        page_counts = (total as f64 / limit as f64).ceil() as usize;
+    }
+
+    let statements = client
+        .query(&result.query, &params_slice[..])
+        .await?
+        .iter()
+        .map(to_seller_statement)
+        .collect();
+
+    Ok(PaginationResult {
+        data: statements,
+        total,
+        page: current_page,
+        per_page: limit,
+        page_counts,
+    })
+}
+
+pub async fn get_seller_statement_by_id(
+    statement_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<SellerStatement>, Error> {
+    let query = format!(
+        "select {} {} and s.statement_id = $1",
+        statement_columns(),
+        STATEMENT_QUERY
+    );
+    Ok(client
+        .query_opt(&query, &[&statement_id])
+        .await?
+        .map(|row| to_seller_statement(&row)))
+}
+
+struct NewStatement {
+    statement_id: i32,
+    seller_id: i32,
+    period_start: NaiveDate,
+    amount_due: String,
+    status: String,
+}
+
+// Bills last month for every active seller on a plan who joined before it ended. Sales
+// count order items of the seller's products in orders completed during the month.
+// Running it again in the same month does nothing, a statement with nothing to pay is
+// created as paid.
+async fn generate_monthly_statements(
+    client: &impl GenericClient,
+) -> Result<Vec<NewStatement>, Error> {
+    let due_days = get_seller_statement_due_days();
+    let rows = client
+        .query(
+            "with period as (select (date_trunc('month', now()) - interval '1 month')::date period_start, date_trunc('month', now())::date period_end), sellers as (select u.user_id, f.fee_id, f.amount, f.is_percent, f.currency_id from users u inner join roles r on r.name = u.role inner join seller_informations si on si.user_id = u.user_id and si.deleted_at is null inner join seller_registration_fees f on f.fee_id = si.fee_id and f.deleted_at is null, period where u.deleted_at is null and u.account_status = 'active' and r.base_role = 'agent' and u.created_at < period.period_end), completed_orders as (select o.order_id, coalesce((select max(h.created_at) from order_status_history h where h.order_id = o.order_id and h.to_status = 'Completed'), o.created_at) completed_at from orders o where o.deleted_at is null and o.status = 'Completed'), sales as (select p.creator_id seller_id, sum(oi.price * oi.quantity) total from order_items oi inner join products p on p.product_id = oi.product_id inner join completed_orders co on co.order_id = oi.order_id, period where oi.deleted_at is null and co.completed_at >= period.period_start and co.completed_at < period.period_end group by p.creator_id) insert into seller_statements (seller_id, fee_id, period_start, period_end, fee_amount, is_percent, sales_total, amount_due, currency_id, status, due_at, paid_at) select s.user_id, s.fee_id, period.period_start, period.period_end, s.amount, s.is_percent, coalesce(sa.total, 0), d.amount_due, s.currency_id, case when d.amount_due = 0 then 'Paid' else 'Unpaid' end, greatest(period.period_end, now()::timestamp) + make_interval(days => $1), case when d.amount_due = 0 then now() end from sellers s cross join period left join sales sa on sa.seller_id = s.user_id cross join lateral (select round(case when s.is_percent then coalesce(sa.total, 0) * s.amount / 100 else s.amount end, 2) amount_due) d on conflict (seller_id, period_start) do nothing returning statement_id, seller_id, period_start, amount_due::text, status",
+            &[&due_days],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| NewStatement {
+            statement_id: row.get("statement_id"),
+            seller_id: row.get("seller_id"),
+            period_start: row.get("period_start"),
+            amount_due: row.get("amount_due"),
+            status: row.get("status"),
+        })
+        .collect())
+}
+
+// Suspends the active shops of sellers with an overdue statement. Returns the sellers
+// whose shops closed.
+async fn suspend_overdue_shops(client: &impl GenericClient) -> Result<Vec<i32>, Error> {
+    let query = format!(
+        "update shops set status = 'Suspended', billing_suspended_at = CURRENT_TIMESTAMP where deleted_at is null and status = 'Active' and creator_id in (select seller_id from seller_statements where {}) returning creator_id",
+        overdue_condition("")
+    );
+    let rows = client.query(&query, &[]).await?;
+    let mut seller_ids: Vec<i32> = rows.iter().map(|row| row.get("creator_id")).collect();
+    seller_ids.sort_unstable();
+    seller_ids.dedup();
+    Ok(seller_ids)
+}
+
+// Returns false unless the statement is the seller's and still unpaid
+pub async fn submit_payment(
+    statement_id: i32,
+    seller_id: i32,
+    payslip_screenshot_path: &str,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let count = client
+        .execute(
+            "update seller_statements set payslip_screenshot_path = $1, status = 'Payment Submitted', payment_submitted_at = CURRENT_TIMESTAMP where statement_id = $2 and seller_id = $3 and status in ('Unpaid', 'Payment Submitted')",
+            &[&payslip_screenshot_path, &statement_id, &seller_id],
+        )
+        .await?;
+    Ok(count > 0)
+}
+
+// Sends a statement with a payslip that did not check out back to unpaid, it counts as
+// overdue again from its due date. Returns the seller, or None unless a payment was
+// waiting for review.
+pub async fn reject_payment(
+    statement_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<i32>, Error> {
+    let row = client
+        .query_opt(
+            "update seller_statements set status = 'Unpaid', payslip_screenshot_path = '', payment_submitted_at = null where statement_id = $1 and status = 'Payment Submitted' returning seller_id",
+            &[&statement_id],
+        )
+        .await?;
+    Ok(row.map(|row| row.get("seller_id")))
+}
+
+pub enum MarkedPaid {
+    // Whether the seller's shops suspended by billing were opened again
+    Paid { shops_reopened: bool },
+    AlreadyPaid,
+}
+
+// Reopens the shops billing suspended once the seller has nothing overdue left
+pub async fn mark_paid(
+    statement_id: i32,
+    paid_by: i32,
+    client: &mut impl GenericClient,
+) -> Result<Option<MarkedPaid>, Error> {
+    let transaction = client.transaction().await?;
+    let row = match transaction
+        .query_opt(
+            "select seller_id, status from seller_statements where statement_id = $1 for update",
+            &[&statement_id],
+        )
+        .await?
+    {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let seller_id: i32 = row.get("seller_id");
+    let status: String = row.get("status");
+    if status == PAID {
+        return Ok(Some(MarkedPaid::AlreadyPaid));
+    }
+
+    transaction
+        .execute(
+            "update seller_statements set status = 'Paid', paid_at = CURRENT_TIMESTAMP, paid_by = $1 where statement_id = $2",
+            &[&paid_by, &statement_id],
+        )
+        .await?;
+    let query = format!(
+        "update shops set status = 'Active', billing_suspended_at = null where creator_id = $1 and billing_suspended_at is not null and status = 'Suspended' and not exists (select 1 from seller_statements where seller_id = $1 and {})",
+        overdue_condition("")
+    );
+    let reopened = transaction.execute(&query, &[&seller_id]).await?;
+    transaction.commit().await?;
+    Ok(Some(MarkedPaid::Paid {
+        shops_reopened: reopened > 0,
+    }))
+}
+
+fn statement_payload(statement_id: i32) -> Option<HashMap<String, Value>> {
+    let mut map = HashMap::new();
+    map.insert(
+        "redirect".to_string(),
+        Value::String("seller-statement".to_string()),
+    );
+    map.insert("id".to_string(), Value::Number(statement_id.into()));
+    Some(map)
+}
+
+// Run periodically: bills last month once the month is over and closes the shops of
+// sellers who let a statement go overdue
+pub async fn run_billing(client: &impl GenericClient) {
+    match generate_monthly_statements(client).await {
+        Ok(statements) => {
+            for statement in statements.iter().filter(|s| s.status == UNPAID) {
+                let message = format!(
+                    "Your statement for {} is ready. Amount due: {}. Please upload your payment screenshot before the due date to keep your shop open.",
+                    statement.period_start.format("%B %Y"),
+                    statement.amount_due
+                );
+                if let Err(err) = add_notification(
+                    statement.seller_id,
+                    "Monthly Statement",
+                    &message,
+                    &statement_payload(statement.statement_id),
+                    client,
+                )
+                .await
+                {
+                    println!("Error adding notification: {:?}", err);
+                }
+            }
+        }
+        Err(err) => println!("Error generating seller statements: {:?}", err),
+    }
+
+    match suspend_overdue_shops(client).await {
+        Ok(seller_ids) => {
+            for seller_id in seller_ids {
+                println!("Shops of seller #{seller_id} suspended for an overdue statement.");
+                if let Err(err) = add_notification(
+                    seller_id,
+                    "Shop Suspended",
+                    "Your shop has been suspended because a monthly statement is overdue. It will reopen once the statement is paid.",
+                    &None,
+                    client,
+                )
+                .await
+                {
+                    println!("Error adding notification: {:?}", err);
+                }
+                let mut map = HashMap::new();
+                map.insert(
+                    "redirect".to_string(),
+                    Value::String("user-detail".to_string()),
+                );
+                map.insert("id".to_string(), Value::Number(seller_id.into()));
+                if let Err(err) = add_notification_to_admins(
+                    "Shop Suspended",
+                    &format!(
+                        "The shops of seller #{} were suspended for an overdue statement.",
+                        seller_id
+                    ),
+                    &Some(map),
+                    client,
+                )
+                .await
+                {
+                    println!("Error adding notification: {:?}", err);
+                }
+            }
+        }
+        Err(err) => println!("Error suspending overdue shops: {:?}", err),
+    }
+}
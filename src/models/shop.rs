@@ -1,8 +1,9 @@
 use std::{fs, path::Path};
 
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
@@ -41,7 +42,7 @@ pub async fn get_shops(
     role: &str,
     user_id: i32,
     version: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<Shop>, Error> {
     let mut base_query = "from shops where deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -172,7 +173,7 @@ pub async fn add_shop(
     data: &ShopRequest,
     creator_id: i32,
     role: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<i32, Error> {
     let mut status = data.status.as_str();
     if role == "agent" {
@@ -213,7 +214,7 @@ pub async fn add_shop(
     Ok(row.get("shop_id"))
 }
 
-pub async fn get_shop_by_id(shop_id: i32, client: &Client) -> Option<Shop> {
+pub async fn get_shop_by_id(shop_id: i32, client: &impl GenericClient) -> Option<Shop> {
     let result = client
         .query_one(
             "select shop_id, name, description, cover_image, address, city, state, postal_code, country, phone, email, website_url, operating_hours, status, creator_id, level, created_at from shops where deleted_at is null and shop_id = $1",
@@ -251,7 +252,7 @@ pub async fn update_shop(
     data: &ShopRequest,
     role: &str,
     old_level: i32,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     let mut status = data.status.as_str();
     if role == "agent" {
@@ -268,7 +269,7 @@ pub async fn update_shop(
     };
     client
         .execute(
-            "update shops set name = $1, description = $2, cover_image = $3, address = $4, city = $5, state = $6, postal_code = $7, country = $8, phone = $9, email = $10, website_url = $11, operating_hours = $12, status = $13, level = $14 where shop_id = $15",
+            "update shops set name = $1, description = $2, cover_image = $3, address = $4, city = $5, state = $6, postal_code = $7, country = $8, phone = $9, email = $10, website_url = $11, operating_hours = $12, status = $13, billing_suspended_at = (case when $13 = 'Suspended' then billing_suspended_at end), level = $14 where shop_id = $15",
             &[
                 &data.name,
                 &data.description,
@@ -314,7 +315,7 @@ pub async fn update_shop(
 pub async fn delete_shop(
     shop_id: i32,
     old_cover_image: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client
         .execute(
@@ -342,7 +343,7 @@ pub async fn delete_shop(
     Ok(())
 }
 
-pub async fn get_cover_images(client: &Client) -> Vec<String> {
+pub async fn get_cover_images(client: &impl GenericClient) -> Vec<String> {
     match client.query("select cover_image from shops", &[]).await {
         Ok(rows) => rows.iter().map(|row| row.get("cover_image")).collect(),
         Err(err) => {
@@ -352,7 +353,7 @@ pub async fn get_cover_images(client: &Client) -> Vec<String> {
     }
 }
 
-pub async fn get_creator_id_from_shop(shop_id: i32, client: &Client) -> Option<i32> {
+pub async fn get_creator_id_from_shop(shop_id: i32, client: &impl GenericClient) -> Option<i32> {
     match client
         .query_one(
             "select creator_id from shops where shop_id = $1",
@@ -360,7 +361,8 @@ pub async fn get_creator_id_from_shop(shop_id: i32, client: &Client) -> Option<i
         )
         .await
     {
-        Ok(row) => Some(row.get("creator_id")),
+        // Shops set up by the platform have no creator
+        Ok(row) => row.get("creator_id"),
         Err(err) => {
             println!("{:?}", err);
             None
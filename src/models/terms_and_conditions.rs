@@ -1,6 +1,10 @@
-use tokio_postgres::{Client, Error};
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
 
-pub async fn add_terms_and_conditions(content: &str, client: &Client) -> Result<(), Error> {
+pub async fn add_terms_and_conditions(
+    content: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
     let rows = client
         .query("select id from terms_and_conditions limit 1", &[])
         .await?;
@@ -24,7 +28,7 @@ pub async fn add_terms_and_conditions(content: &str, client: &Client) -> Result<
     Ok(())
 }
 
-pub async fn get_terms_and_conditions(client: &Client) -> String {
+pub async fn get_terms_and_conditions(client: &impl GenericClient) -> String {
     match client
         .query_one("select content from terms_and_conditions limit 1", &[])
         .await
@@ -2,15 +2,20 @@ use std::{fs, path::Path};
 
 use bcrypt::{hash, DEFAULT_COST};
 use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{types::ToSql, Client, Error};
+use tokio_postgres::{types::ToSql, Error};
 
 use crate::utils::{
     common_struct::PaginationResult,
+    identity_provider::Provider,
     sql::{generate_pagination_query, PaginationOptions},
 };
 
-use super::seller_information::{SellerInformation, SellerInformationRequest};
+use super::{
+    refresh_token::revoke_user_refresh_tokens,
+    seller_information::{SellerInformation, SellerInformationRequest},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
@@ -30,7 +35,7 @@ pub struct User {
     pub seller_information: Option<SellerInformation>,
 }
 
-pub async fn user_exists(username: &str, client: &Client) -> Result<bool, Error> {
+pub async fn user_exists(username: &str, client: &impl GenericClient) -> Result<bool, Error> {
     // Execute a query to check if the username exists in the users table
     let row = client
         .query_one(
@@ -43,7 +48,7 @@ pub async fn user_exists(username: &str, client: &Client) -> Result<bool, Error>
     Ok(row.is_ok())
 }
 
-pub async fn get_user_by_id(user_id: i32, client: &Client) -> Option<User> {
+pub async fn get_user_by_id(user_id: i32, client: &impl GenericClient) -> Option<User> {
     let result = client
         .query_one(
             "select u.user_id, u.username, u.password, u.role, u.name, u.profile_image, u.email, u.phone, u.account_status, u.can_modify_order_status, u.can_view_address, u.can_view_phone, u.created_at, coalesce(si.company_name, '') as company_name, coalesce(si.professional_title, '') as professional_title, coalesce(si.active_since_year, 0) as active_since_year, coalesce(si.location, '') as location, coalesce(si.offline_trader, false) as offline_trader, coalesce(si.facebook_profile_image, '') as facebook_profile_image, coalesce(si.shop_or_page_name, '') as shop_or_page_name, coalesce(si.facebook_page_image, '') as facebook_page_image, coalesce(si.bussiness_phone, '') as bussiness_phone, coalesce(si.address, '') as address, coalesce(si.nrc, '') as nrc, coalesce(si.nrc_front_image, '') as nrc_front_image, coalesce(si.nrc_back_image, '') as nrc_back_image, coalesce(si.bank_code, '') as bank_code, coalesce(si.bank_account, '') as bank_account, coalesce(si.bank_account_image, '') as bank_account_image, coalesce(si.wallet_type, '') as wallet_type, coalesce(si.wallet_account, '') as wallet_account, coalesce(si.fee_id, 0) as fee_id, coalesce(si.monthly_transaction_screenshot, '') as monthly_transaction_screenshot, coalesce(si.passport_image, '') as passport_image, coalesce(si.driving_licence_image, '') as driving_licence_image, coalesce(si.signature_image, '') as signature_image from users u left join seller_informations si on u.user_id = si.user_id and si.deleted_at is null where u.user_id = $1 and u.deleted_at is null",
@@ -116,18 +121,31 @@ pub async fn add_user(
     can_view_address: bool,
     can_view_phone: bool,
     seller_information: &Option<SellerInformationRequest>,
-    google_id: &Option<String>,
+    provider_id: &Option<(Provider, String)>,
     request_to_agent: bool,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<i32, Box<dyn std::error::Error>> {
     println!("request_to_agent: {request_to_agent}");
     let hashed_password =
         hash(&password, DEFAULT_COST).map_err(|e| format!("Failed to hash password: {}", e))?;
 
+    // Only the column of the provider the user signed up with gets the id
+    let id_for = |provider: Provider| {
+        provider_id
+            .as_ref()
+            .filter(|(p, _)| *p == provider)
+            .map(|(_, id)| id.as_str())
+    };
+    let google_id = id_for(Provider::Google);
+    let apple_id = id_for(Provider::Apple);
+    let facebook_id = id_for(Provider::Facebook);
+    // The password sent along with a provider sign up isn't one the user picked
+    let password_set = provider_id.is_none();
+
     // Insert the new user into the database
     let row =client.query_one(
-        "INSERT INTO users (name, username, password, email, phone, profile_image, role, account_status, can_modify_order_status, can_view_address, can_view_phone, google_id, request_to_agent) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) returning user_id",
-        &[&name, &username, &hashed_password, &email, &phone, &profile_image, &role, &account_status, &can_modify_order_status, &can_view_address, &can_view_phone, &google_id, &request_to_agent],
+        "INSERT INTO users (name, username, password, email, phone, profile_image, role, account_status, can_modify_order_status, can_view_address, can_view_phone, google_id, apple_id, facebook_id, request_to_agent, password_set) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16) returning user_id",
+        &[&name, &username, &hashed_password, &email, &phone, &profile_image, &role, &account_status, &can_modify_order_status, &can_view_address, &can_view_phone, &google_id, &apple_id, &facebook_id, &request_to_agent, &password_set],
     ).await?;
     let user_id: i32 = row.get("user_id");
     if role == "agent" {
@@ -244,7 +262,7 @@ pub async fn update_user(
     can_view_phone: bool,
     seller_information: &Option<SellerInformationRequest>,
     request_to_agent: bool,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if profile_image != old_profile_image {
         match fs::remove_file(old_profile_image) {
@@ -274,7 +292,7 @@ pub async fn update_user(
 
     // Insert the new user into the database
     client.execute(
-        "update users set name = $1, password = $2, email = $3, phone = $4, profile_image = $5, role = $6, account_status = $7, can_modify_order_status = $8, can_view_address = $9, can_view_phone = $10, request_to_agent = $11 where user_id = $12 and deleted_at is null",
+        "update users set name = $1, password = $2, password_set = password_set or password is distinct from $2, email = $3, phone = $4, profile_image = $5, role = $6, account_status = $7, can_modify_order_status = $8, can_view_address = $9, can_view_phone = $10, request_to_agent = $11, token_version = token_version + (case when (role, account_status, can_modify_order_status, can_view_address, can_view_phone, password) is distinct from ($6, $7, $8, $9, $10, $2) then 1 else 0 end) where user_id = $12 and deleted_at is null",
         &[&name, &hashed_password, &email, &phone, &profile_image, &role, &account_status, &can_modify_order_status, &can_view_address, &can_view_phone, &request_to_agent, &user_id],
     ).await?;
 
@@ -419,7 +437,7 @@ pub async fn update_user(
 pub async fn delete_user(
     user_id: i32,
     old_profile_image: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     match fs::remove_file(old_profile_image) {
         Ok(_) => println!("File deleted successfully!"),
@@ -439,14 +457,15 @@ pub async fn delete_user(
         Err(e) => println!("Error deleting original file: {}", e),
     };
     client.execute(
-        "update users set deleted_at = CURRENT_TIMESTAMP where user_id = $1 and deleted_at is null",
+        "update users set deleted_at = CURRENT_TIMESTAMP, token_version = token_version + 1 where user_id = $1 and deleted_at is null",
         &[&user_id],
     ).await?;
     client.execute("update seller_informations set deleted_at = CURRENT_TIMESTAMP where user_id = $1 and deleted_at is null", &[&user_id]).await?;
+    revoke_user_refresh_tokens(user_id, client).await?;
     Ok(())
 }
 
-pub async fn get_user(username: &str, client: &Client) -> Option<User> {
+pub async fn get_user(username: &str, client: &impl GenericClient) -> Option<User> {
     // Here we fetch the user from the database using tokio-postgres
     // In a real-world scenario, handle errors gracefully
     let result = client
@@ -477,15 +496,15 @@ pub async fn get_user(username: &str, client: &Client) -> Option<User> {
     }
 }
 
-pub async fn get_user_by_google_id(google_id: &str, client: &Client) -> Option<User> {
+pub async fn get_user_by_provider_id(
+    provider: Provider,
+    provider_id: &str,
+    client: &impl GenericClient,
+) -> Option<User> {
     // Here we fetch the user from the database using tokio-postgres
     // In a real-world scenario, handle errors gracefully
-    let result = client
-        .query_one(
-            "select user_id, username, password, role, name, profile_image, email, phone, account_status, can_modify_order_status, can_view_address, can_view_phone, created_at from users where google_id = $1 and deleted_at is null",
-            &[&google_id],
-        )
-        .await;
+    let query = format!("select user_id, username, password, role, name, profile_image, email, phone, account_status, can_modify_order_status, can_view_address, can_view_phone, created_at from users where {} = $1 and deleted_at is null", provider.id_column());
+    let result = client.query_one(&query, &[&provider_id]).await;
 
     match result {
         Ok(row) => Some(User {
@@ -515,7 +534,7 @@ pub async fn get_users(
     role: &Option<String>,
     account_status: &Option<String>,
     request_to_agent: Option<bool>,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<PaginationResult<User>, Error> {
     let mut base_query = "from users where deleted_at is null".to_string();
     let mut params: Vec<Box<dyn ToSql + Sync>> = vec![];
@@ -599,16 +618,17 @@ pub async fn get_users(
 pub async fn change_password(
     user_id: i32,
     new_password: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let hashed_password =
         hash(new_password, DEFAULT_COST).map_err(|e| format!("Failed to hash password: {}", e))?;
     client
         .execute(
-            "update users set password = $1 where user_id = $2",
+            "update users set password = $1, password_set = true, token_version = token_version + 1 where user_id = $2",
             &[&hashed_password, &user_id],
         )
         .await?;
+    revoke_user_refresh_tokens(user_id, client).await?;
     Ok(())
 }
 
@@ -620,7 +640,7 @@ pub struct UserProfile {
     pub profile_image: String,
 }
 
-pub async fn get_user_profile(user_id: i32, client: &Client) -> Option<UserProfile> {
+pub async fn get_user_profile(user_id: i32, client: &impl GenericClient) -> Option<UserProfile> {
     let result = client.query_one("select name, email, phone, profile_image from users where deleted_at is null and user_id = $1", &[&user_id]).await;
     match result {
         Ok(row) => Some(UserProfile {
@@ -637,7 +657,7 @@ pub async fn update_user_profile(
     user_id: i32,
     data: &UserProfile,
     old_profile_image: &str,
-    client: &Client,
+    client: &impl GenericClient,
 ) -> Result<(), Error> {
     client.execute("update users set name = $1, email = $2, phone = $3, profile_image = $4 where user_id = $5", &[&data.name, &data.email, &data.phone, &data.profile_image, &user_id]).await?;
     if data.profile_image != old_profile_image {
@@ -649,7 +669,7 @@ pub async fn update_user_profile(
     Ok(())
 }
 
-pub async fn get_admin_user_ids(client: &Client) -> Result<Vec<i32>, Error> {
+pub async fn get_admin_user_ids(client: &impl GenericClient) -> Result<Vec<i32>, Error> {
     Ok(client
         .query(
             "select user_id from users where role = 'admin' and deleted_at is null",
@@ -661,7 +681,7 @@ pub async fn get_admin_user_ids(client: &Client) -> Result<Vec<i32>, Error> {
         .collect())
 }
 
-pub async fn get_user_name(user_id: i32, client: &Client) -> Option<String> {
+pub async fn get_user_name(user_id: i32, client: &impl GenericClient) -> Option<String> {
     match client
         .query_one(
             "select name from users where user_id = $1 and deleted_at is null",
@@ -674,7 +694,7 @@ pub async fn get_user_name(user_id: i32, client: &Client) -> Option<String> {
     }
 }
 
-pub async fn get_profile_images(client: &Client) -> Vec<String> {
+pub async fn get_profile_images(client: &impl GenericClient) -> Vec<String> {
     match client.query("select profile_image from users", &[]).await {
         Ok(rows) => rows.iter().map(|row| row.get("profile_image")).collect(),
         Err(err) => {
@@ -684,23 +704,7 @@ pub async fn get_profile_images(client: &Client) -> Vec<String> {
     }
 }
 
-pub async fn can_modify_order_status(user_id: i32, client: &Client) -> bool {
-    match client
-        .query_one(
-            "select can_modify_order_status from users where user_id = $1 and deleted_at is null",
-            &[&user_id],
-        )
-        .await
-    {
-        Ok(row) => row.get("can_modify_order_status"),
-        Err(err) => {
-            println!("{:?}", err);
-            false
-        }
-    }
-}
-
-pub async fn is_phone_existed(phone: &str, client: &Client) -> bool {
+pub async fn is_phone_existed(phone: &str, client: &impl GenericClient) -> bool {
     let total: i64=  match client.query_one(
         "select count(*) as total from users where phone = $1 and deleted_at is null and account_status = 'active'",
         &[&phone],
@@ -714,40 +718,127 @@ pub async fn is_phone_existed(phone: &str, client: &Client) -> bool {
     total > 0
 }
 
-// pub async fn user_exists_by(vendor: &str, id: &str, client: &Client) -> Result<bool, Error> {
-//     let column = if vendor == "google" {
-//         "google_id"
-//     } else if vendor == "facebook" {
-//         "facebook_id"
-//     } else {
-//         "apple_id"
-//     };
-//     let query = format!("select username from users where {column} = $1 and deleted_at is null");
-//     let row = client.query_one(&query, &[&id]).await;
-//     // Return whether the user exists
-//     Ok(row.is_ok())
-// }
-
 #[derive(Serialize)]
-pub struct UserPermission {
-    pub can_modify_order_status: bool,
-    pub can_view_address: bool,
-    pub can_view_phone: bool,
+pub struct LinkedProviders {
+    pub google: bool,
+    pub apple: bool,
+    pub facebook: bool,
 }
 
-pub async fn get_user_permission(user_id: i32, client: &Client) -> UserPermission {
-    match client.query_one("select can_modify_order_status, can_view_address, can_view_phone from users where user_id = $1 and deleted_at is null", &[&user_id]).await {
-        Ok(row) => UserPermission {
-            can_modify_order_status: row.get("can_modify_order_status"),
-            can_view_address: row.get("can_view_address"),
-            can_view_phone: row.get("can_view_phone")
-        }, Err(err) => {
-            println!("{:?}", err);
-            UserPermission {
-                can_modify_order_status: false,
-                can_view_address: false,
-                can_view_phone: false
-            }
-        }
-    }
+pub async fn get_linked_providers(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<LinkedProviders>, Error> {
+    let row = client
+        .query_opt(
+            "select google_id is not null as google, apple_id is not null as apple, facebook_id is not null as facebook from users where user_id = $1 and deleted_at is null",
+            &[&user_id],
+        )
+        .await?;
+    Ok(row.map(|row| LinkedProviders {
+        google: row.get("google"),
+        apple: row.get("apple"),
+        facebook: row.get("facebook"),
+    }))
+}
+
+// Links the provider account to the user
+pub async fn set_provider_id(
+    user_id: i32,
+    provider: Provider,
+    provider_id: &str,
+    client: &impl GenericClient,
+) -> Result<(), Error> {
+    let query = format!(
+        "update users set {} = $1 where user_id = $2",
+        provider.id_column()
+    );
+    client.execute(&query, &[&provider_id, &user_id]).await?;
+    Ok(())
+}
+
+// Unlinks the provider account unless it is the user's last way to sign in, that is no
+// other provider is linked and the user never set a password. False when it is kept.
+pub async fn unset_provider_id(
+    user_id: i32,
+    provider: Provider,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let others: Vec<String> = [Provider::Google, Provider::Apple, Provider::Facebook]
+        .iter()
+        .filter(|&&other| other != provider)
+        .map(|other| format!("{} is not null", other.id_column()))
+        .collect();
+    let query = format!(
+        "update users set {} = null where user_id = $1 and (password_set or {})",
+        provider.id_column(),
+        others.join(" or ")
+    );
+    Ok(client.execute(&query, &[&user_id]).await? > 0)
+}
+
+pub struct TokenUser {
+    pub user_id: i32,
+    pub role: String,
+    pub token_version: i32,
+}
+
+// Current role and token version to sign an access token with, None when the user was
+// deleted or is not active
+pub async fn get_token_user(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<TokenUser>, Error> {
+    let row = client.query_opt("select user_id, role, token_version from users where user_id = $1 and account_status = 'active' and deleted_at is null", &[&user_id]).await?;
+    Ok(row.map(|row| TokenUser {
+        user_id: row.get("user_id"),
+        role: row.get("role"),
+        token_version: row.get("token_version"),
+    }))
+}
+
+pub struct AuthState {
+    pub token_version: i32,
+    pub base_role: String,
+    pub permissions: Vec<String>,
+}
+
+// Read on every request so role and permission changes apply right away. The per-user
+// flags admins set on agents grant the matching permissions on top of the role's.
+pub async fn get_auth_state(
+    user_id: i32,
+    client: &impl GenericClient,
+) -> Result<Option<AuthState>, Error> {
+    let row = client
+        .query_opt(
+            "select u.token_version, coalesce(r.base_role, u.role) as base_role, array(
+                select p.name::text from role_permissions rp inner join permissions p on p.permission_id = rp.permission_id where rp.role_id = r.role_id
+                union select 'orders.update_status' where u.can_modify_order_status
+                union select 'users.view_address' where u.can_view_address
+                union select 'users.view_phone' where u.can_view_phone
+            ) as permissions
+            from users u left join roles r on r.name = u.role where u.user_id = $1 and u.deleted_at is null",
+            &[&user_id],
+        )
+        .await?;
+    Ok(row.map(|row| AuthState {
+        token_version: row.get("token_version"),
+        base_role: row.get("base_role"),
+        permissions: row.get("permissions"),
+    }))
+}
+
+// Assigning a role changes the token's role claim, so older tokens are retired
+pub async fn set_user_role(
+    user_id: i32,
+    role: &str,
+    client: &impl GenericClient,
+) -> Result<bool, Error> {
+    let updated = client
+        .execute(
+            "update users set role = $1, token_version = token_version + 1 where user_id = $2 and deleted_at is null",
+            &[&role, &user_id],
+        )
+        .await?;
+    Ok(updated > 0)
 }
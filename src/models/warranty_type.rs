@@ -1,5 +1,6 @@
+use deadpool_postgres::GenericClient;
 use serde::Serialize;
-use tokio_postgres::{Client, Error};
+use tokio_postgres::Error;
 
 #[derive(Serialize)]
 pub struct WarrantyType {
@@ -7,7 +8,7 @@ pub struct WarrantyType {
     pub description: String,
 }
 
-pub async fn get_warranty_types(client: &Client) -> Result<Vec<WarrantyType>, Error> {
+pub async fn get_warranty_types(client: &impl GenericClient) -> Result<Vec<WarrantyType>, Error> {
     let rows = client
         .query(
             "select warranty_type_id, description from warranty_types where deleted_at is null order by description",
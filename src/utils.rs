@@ -1,10 +1,17 @@
+pub mod auth;
+pub mod code_delivery;
 pub mod comma_number;
 pub mod common_struct;
+pub mod db;
 pub mod fcm;
 pub mod google;
+pub mod identity_provider;
 pub mod image;
+pub mod jwks;
 pub mod jwt;
-pub mod report_forge;
+pub mod migration;
+pub mod pdf;
+pub mod permission;
 pub mod setting;
 pub mod socketio;
 pub mod sql;
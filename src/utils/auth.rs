@@ -0,0 +1,180 @@
+use std::{fmt, future::Future, marker::PhantomData, ops::Deref, pin::Pin};
+
+use actix_web::{
+    dev::Payload, http::StatusCode, web, FromRequest, HttpRequest, HttpResponse, ResponseError,
+};
+use deadpool_postgres::Pool;
+
+use crate::models::user;
+
+use super::{
+    common_struct::BaseResponse, jwt::verify_token_and_get_claims, permission::Permission,
+};
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    Forbidden,
+    Unavailable,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            AuthError::MissingToken => "Authorization header missing",
+            AuthError::InvalidToken => "Invalid token",
+            AuthError::Forbidden => "Permission denied!",
+            AuthError::Unavailable => "Database is not available at the moment!",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden => StatusCode::FORBIDDEN,
+            AuthError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(BaseResponse {
+            code: self.status_code().as_u16(),
+            message: self.to_string(),
+        })
+    }
+}
+
+fn get_bearer_token(req: &HttpRequest) -> Option<String> {
+    let value = req.headers().get("Authorization")?.to_str().ok()?;
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() == 2 && parts[0] == "Bearer" {
+        Some(parts[1].to_string())
+    } else {
+        None
+    }
+}
+
+async fn authenticate(token: &str, pool: Option<web::Data<Pool>>) -> Result<AuthUser, AuthError> {
+    let claims = verify_token_and_get_claims(token).ok_or(AuthError::InvalidToken)?;
+    let pool = pool.ok_or(AuthError::Unavailable)?;
+    let client = pool.get().await.map_err(|err| {
+        println!("Database connection error: {:?}", err);
+        AuthError::Unavailable
+    })?;
+    // Deleting a user or changing their role, permissions or password bumps the
+    // version, so older tokens stop working right away
+    let state = match user::get_auth_state(claims.user_id, &client).await {
+        Ok(Some(state)) if state.token_version <= claims.token_version => state,
+        Ok(_) => return Err(AuthError::InvalidToken),
+        Err(err) => {
+            println!("Error reading auth state: {:?}", err);
+            return Err(AuthError::Unavailable);
+        }
+    };
+    Ok(AuthUser {
+        user_id: claims.user_id,
+        role: state.base_role,
+        permissions: state.permissions,
+    })
+}
+
+// Signed in user taken from the Bearer token, rejects the request with 401 when the
+// token is missing or invalid.
+pub struct AuthUser {
+    pub user_id: i32,
+    // admin, agent or user. Custom roles report the role they are based on, which is
+    // what decides the records they see in listings.
+    pub role: String,
+    pub permissions: Vec<String>,
+}
+
+impl AuthUser {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = get_bearer_token(req);
+        let pool = req.app_data::<web::Data<Pool>>().cloned();
+        Box::pin(async move {
+            let token = token.ok_or(AuthError::MissingToken)?;
+            authenticate(&token, pool).await
+        })
+    }
+}
+
+// For routes guests can use too. A request without a Bearer token is a guest, one with
+// an invalid token is still rejected with 401.
+pub struct OptionalAuthUser(pub Option<AuthUser>);
+
+impl OptionalAuthUser {
+    pub fn user_id(&self) -> i32 {
+        self.0.as_ref().map(|auth| auth.user_id).unwrap_or(0)
+    }
+
+    pub fn role(&self) -> &str {
+        self.0
+            .as_ref()
+            .map(|auth| auth.role.as_str())
+            .unwrap_or("user")
+    }
+}
+
+impl FromRequest for OptionalAuthUser {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let token = get_bearer_token(req);
+        let pool = req.app_data::<web::Data<Pool>>().cloned();
+        Box::pin(async move {
+            match token {
+                Some(token) => Ok(OptionalAuthUser(Some(authenticate(&token, pool).await?))),
+                None => Ok(OptionalAuthUser(None)),
+            }
+        })
+    }
+}
+
+// Routes that need a named permission, e.g. `Authorized<CatalogManage>`. Users without
+// it get 403.
+pub struct Authorized<P: Permission>(pub AuthUser, PhantomData<P>);
+
+impl<P: Permission> Deref for Authorized<P> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.0
+    }
+}
+
+impl<P: Permission + 'static> FromRequest for Authorized<P> {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth = AuthUser::from_request(req, payload);
+        Box::pin(async move {
+            let auth = auth.await?;
+            if auth.has_permission(P::NAME) {
+                Ok(Authorized(auth, PhantomData))
+            } else {
+                Err(AuthError::Forbidden)
+            }
+        })
+    }
+}
+
+// Ownership checks inside handlers use the same 403 response as the permission guards
+pub fn forbidden() -> HttpResponse {
+    AuthError::Forbidden.error_response()
+}
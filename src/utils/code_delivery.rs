@@ -0,0 +1,101 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use reqwest;
+use serde_json::json;
+
+use super::setting::{
+    get_email_gateway_url, get_message_gateway_auth, get_password_reset_delivery,
+    get_sms_gateway_url,
+};
+
+pub enum DeliveryChannel {
+    Sms,
+    Email,
+}
+
+type DeliveryResult<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + 'a>>;
+
+// Sends one-time codes to users, the implementation is picked once at startup
+pub trait CodeDelivery: Send + Sync {
+    fn deliver<'a>(
+        &'a self,
+        channel: &'a DeliveryChannel,
+        destination: &'a str,
+        subject: &'a str,
+        message: &'a str,
+    ) -> DeliveryResult<'a>;
+}
+
+// Posts SMS and email messages to the configured gateways
+pub struct GatewayCodeDelivery {
+    sms_url: String,
+    email_url: String,
+    auth: String,
+}
+
+impl CodeDelivery for GatewayCodeDelivery {
+    fn deliver<'a>(
+        &'a self,
+        channel: &'a DeliveryChannel,
+        destination: &'a str,
+        subject: &'a str,
+        message: &'a str,
+    ) -> DeliveryResult<'a> {
+        Box::pin(async move {
+            let (url, payload) = match channel {
+                DeliveryChannel::Sms => (
+                    &self.sms_url,
+                    json!({ "to": destination, "message": message }),
+                ),
+                DeliveryChannel::Email => (
+                    &self.email_url,
+                    json!({ "to": destination, "subject": subject, "body": message }),
+                ),
+            };
+            let client = reqwest::Client::new();
+            client
+                .post(url)
+                .header("Authorization", &self.auth)
+                .header("Content-Type", "application/json")
+                .json(&payload)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+// Only prints the message, for local development
+pub struct LogCodeDelivery;
+
+impl CodeDelivery for LogCodeDelivery {
+    fn deliver<'a>(
+        &'a self,
+        channel: &'a DeliveryChannel,
+        destination: &'a str,
+        subject: &'a str,
+        message: &'a str,
+    ) -> DeliveryResult<'a> {
+        Box::pin(async move {
+            let channel = match channel {
+                DeliveryChannel::Sms => "sms",
+                DeliveryChannel::Email => "email",
+            };
+            println!("[{channel}] to: {destination}, subject: {subject}, message: {message}");
+            Ok(())
+        })
+    }
+}
+
+pub fn create_code_delivery() -> Arc<dyn CodeDelivery> {
+    match get_password_reset_delivery().as_str() {
+        "log" => Arc::new(LogCodeDelivery),
+        _ => Arc::new(GatewayCodeDelivery {
+            sms_url: get_sms_gateway_url(),
+            email_url: get_email_gateway_url(),
+            auth: get_message_gateway_auth(),
+        }),
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use actix_web::HttpResponse;
+use deadpool_postgres::{Client, Manager, ManagerConfig, Pool, RecyclingMethod, Runtime};
+use tokio_postgres::NoTls;
+
+use super::{
+    common_struct::BaseResponse,
+    setting::{get_db_pool_size, get_db_pool_timeout},
+};
+
+// Connections are checked with a test query before they are handed out again, so one
+// that died with the server is dropped and replaced by a fresh connection.
+pub fn create_pool(conn: &str) -> Pool {
+    let pg_config: tokio_postgres::Config = conn.parse().expect("Invalid DB_CONNECTION");
+    let manager = Manager::from_config(
+        pg_config,
+        NoTls,
+        ManagerConfig {
+            recycling_method: RecyclingMethod::Verified,
+        },
+    );
+    let timeout = Some(Duration::from_secs(get_db_pool_timeout()));
+    Pool::builder(manager)
+        .max_size(get_db_pool_size())
+        .wait_timeout(timeout)
+        .create_timeout(timeout)
+        .recycle_timeout(timeout)
+        .runtime(Runtime::Tokio1)
+        .build()
+        .expect("Failed to create database pool")
+}
+
+pub async fn get_client(pool: &Pool) -> Result<Client, HttpResponse> {
+    pool.get().await.map_err(|err| {
+        println!("Database connection error: {:?}", err);
+        HttpResponse::ServiceUnavailable().json(BaseResponse {
+            code: 503,
+            message: String::from("Database is not available at the moment!"),
+        })
+    })
+}
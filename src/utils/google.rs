@@ -1,54 +1,132 @@
-use std::collections::HashMap;
-
-use reqwest;
+use jsonwebtoken::{decode, decode_header, Algorithm, Validation};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+
+use super::jwks::JwksKeySource;
+
+const GOOGLE_ISSUERS: [&str; 2] = ["accounts.google.com", "https://accounts.google.com"];
 
 #[derive(Serialize, Deserialize)]
 pub struct GoogleTokenInfo {
     pub aud: String,
     pub sub: String,
-    pub email: String,
-    pub name: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
     pub picture: Option<String>,
     pub given_name: Option<String>,
     pub family_name: Option<String>,
 }
 
-pub async fn verify_id_token_with_google_api_original(
+// Checks the ID token locally: RS256 signature against Google's keys, issued by Google,
+// meant for one of our client ids and not expired
+pub async fn verify_id_token(
     id_token: &str,
-) -> Result<HashMap<String, Value>, reqwest::Error> {
-    let url = format!(
-        "https://oauth2.googleapis.com/tokeninfo?id_token={}",
-        id_token
-    );
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true) // Only if you're sure about the security implications
-        .build()?;
-    let res = client
-        .get(&url)
-        .send()
-        .await?
-        .json::<HashMap<String, Value>>()
-        .await?;
-    Ok(res)
+    client_ids: &[String],
+    keys: &JwksKeySource,
+) -> Result<GoogleTokenInfo, Box<dyn std::error::Error>> {
+    if client_ids.is_empty() {
+        return Err("Google sign in is not configured".into());
+    }
+    let kid = decode_header(id_token)?
+        .kid
+        .ok_or("Google token has no key id")?;
+    let key = keys.get_decoding_key(&kid).await?;
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.set_issuer(&GOOGLE_ISSUERS);
+    validation.set_audience(client_ids);
+    Ok(decode::<GoogleTokenInfo>(id_token, &key, &validation)?.claims)
 }
 
-pub async fn verify_id_token_with_google_api(
-    id_token: &str,
-) -> Result<GoogleTokenInfo, reqwest::Error> {
-    let url = format!(
-        "https://oauth2.googleapis.com/tokeninfo?id_token={}",
-        id_token
-    );
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true) // Only if you're sure about the security implications
-        .build()?;
-    let res = client
-        .get(&url)
-        .send()
-        .await?
-        .json::<GoogleTokenInfo>()
-        .await?;
-    Ok(res)
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::utils::jwks::test_keys::{key_set, now, sign};
+
+    fn client_ids() -> Vec<String> {
+        vec!["client-1".to_string(), "client-2".to_string()]
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": "https://accounts.google.com",
+            "aud": "client-2",
+            "sub": "google-user-1",
+            "email": "buyer@example.com",
+            "email_verified": true,
+            "iat": now(),
+            "exp": now() + 3600,
+        })
+    }
+
+    async fn verify(token: &str) -> Result<GoogleTokenInfo, Box<dyn std::error::Error>> {
+        verify_id_token(
+            token,
+            &client_ids(),
+            &JwksKeySource::from_key_set(key_set()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn valid_token_is_accepted() {
+        let info = verify(&sign("test-key-1", "test-key-1", &claims()))
+            .await
+            .unwrap();
+        assert_eq!(info.sub, "google-user-1");
+        assert_eq!(info.aud, "client-2");
+        assert_eq!(info.email.as_deref(), Some("buyer@example.com"));
+        assert!(info.email_verified);
+    }
+
+    #[tokio::test]
+    async fn token_for_another_client_is_rejected() {
+        let mut claims = claims();
+        claims["aud"] = json!("someone-elses-client");
+        assert!(verify(&sign("test-key-1", "test-key-1", &claims))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn token_from_another_issuer_is_rejected() {
+        let mut claims = claims();
+        claims["iss"] = json!("https://accounts.example.com");
+        assert!(verify(&sign("test-key-1", "test-key-1", &claims))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() {
+        let mut claims = claims();
+        claims["iat"] = json!(now() - 7200);
+        claims["exp"] = json!(now() - 3600);
+        assert!(verify(&sign("test-key-1", "test-key-1", &claims))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn token_with_unknown_key_is_rejected() {
+        assert!(verify(&sign("test-key-1", "retired-key", &claims()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn token_signed_by_another_key_is_rejected() {
+        assert!(verify(&sign("test-key-2", "test-key-1", &claims()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn nothing_is_accepted_without_client_ids() {
+        let token = sign("test-key-1", "test-key-1", &claims());
+        let keys = JwksKeySource::from_key_set(key_set());
+        assert!(verify_id_token(&token, &[], &keys).await.is_err());
+    }
 }
@@ -0,0 +1,372 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+use jsonwebtoken::{decode, decode_header, Algorithm, Validation};
+use reqwest;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    google,
+    jwks::JwksKeySource,
+    setting::{
+        get_apple_client_ids, get_apple_jwks, get_apple_jwks_url, get_facebook_app_id,
+        get_facebook_app_secret, get_google_client_ids, get_google_jwks, get_google_jwks_url,
+        get_jwks_cache_seconds,
+    },
+};
+
+const APPLE_ISSUER: &str = "https://appleid.apple.com";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Provider {
+    Google,
+    Apple,
+    Facebook,
+}
+
+impl Provider {
+    // Login and register keep their `method` field, "password" or anything unknown is None
+    pub fn from_method(method: &str) -> Option<Provider> {
+        match method {
+            "google" => Some(Provider::Google),
+            "apple" => Some(Provider::Apple),
+            "facebook" => Some(Provider::Facebook),
+            _ => None,
+        }
+    }
+
+    // Column in users holding the provider's id for the user
+    pub fn id_column(&self) -> &'static str {
+        match self {
+            Provider::Google => "google_id",
+            Provider::Apple => "apple_id",
+            Provider::Facebook => "facebook_id",
+        }
+    }
+}
+
+// What a provider vouches for after checking the token. Apple and Facebook do not
+// always share the email or name.
+#[derive(Serialize)]
+pub struct ProviderIdentity {
+    #[serde(rename = "sub")]
+    pub subject: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+}
+
+type VerifyResult<'a> =
+    Pin<Box<dyn Future<Output = Result<ProviderIdentity, Box<dyn std::error::Error>>> + 'a>>;
+
+pub trait IdentityProvider: Send + Sync {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyResult<'a>;
+}
+
+pub struct GoogleIdentityProvider {
+    pub client_ids: Vec<String>,
+    pub keys: JwksKeySource,
+}
+
+impl IdentityProvider for GoogleIdentityProvider {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyResult<'a> {
+        Box::pin(async move {
+            let info = google::verify_id_token(token, &self.client_ids, &self.keys).await?;
+            Ok(ProviderIdentity {
+                subject: info.sub,
+                // An unverified address could belong to someone else
+                email: info.email.filter(|_| info.email_verified),
+                name: info.name,
+                picture: info.picture,
+            })
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct AppleClaims {
+    sub: String,
+    email: Option<String>,
+}
+
+// Checks the identity token's signature against Apple's public keys, then the issuer,
+// audience (our bundle or service ids) and expiry
+pub struct AppleIdentityProvider {
+    pub client_ids: Vec<String>,
+    pub keys: JwksKeySource,
+}
+
+impl IdentityProvider for AppleIdentityProvider {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyResult<'a> {
+        Box::pin(async move {
+            if self.client_ids.is_empty() {
+                return Err("Apple sign in is not configured".into());
+            }
+            let kid = decode_header(token)?
+                .kid
+                .ok_or("Apple token has no key id")?;
+            let key = self.keys.get_decoding_key(&kid).await?;
+            let mut validation = Validation::new(Algorithm::RS256);
+            validation.set_issuer(&[APPLE_ISSUER]);
+            validation.set_audience(&self.client_ids);
+            let claims = decode::<AppleClaims>(token, &key, &validation)?.claims;
+            Ok(ProviderIdentity {
+                subject: claims.sub,
+                email: claims.email,
+                name: None,
+                picture: None,
+            })
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct FacebookDebugToken {
+    data: FacebookDebugTokenData,
+}
+
+#[derive(Deserialize)]
+struct FacebookDebugTokenData {
+    app_id: Option<String>,
+    is_valid: bool,
+    user_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FacebookPictureData {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct FacebookPicture {
+    data: FacebookPictureData,
+}
+
+#[derive(Deserialize)]
+struct FacebookProfile {
+    id: String,
+    name: Option<String>,
+    email: Option<String>,
+    picture: Option<FacebookPicture>,
+}
+
+// Facebook hands out access tokens rather than ID tokens, so the token is checked with
+// the Graph API and must have been issued to our app
+pub struct FacebookIdentityProvider {
+    pub app_id: String,
+    pub app_secret: String,
+}
+
+impl IdentityProvider for FacebookIdentityProvider {
+    fn verify<'a>(&'a self, token: &'a str) -> VerifyResult<'a> {
+        Box::pin(async move {
+            if self.app_id.is_empty() || self.app_secret.is_empty() {
+                return Err("Facebook sign in is not configured".into());
+            }
+            let client = reqwest::Client::new();
+            let app_token = format!("{}|{}", self.app_id, self.app_secret);
+            let debug = client
+                .get("https://graph.facebook.com/debug_token")
+                .query(&[("input_token", token), ("access_token", &app_token)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<FacebookDebugToken>()
+                .await?
+                .data;
+            if !debug.is_valid || debug.app_id.as_deref() != Some(self.app_id.as_str()) {
+                return Err("Facebook token was not issued to this app".into());
+            }
+
+            let profile = client
+                .get("https://graph.facebook.com/me")
+                .query(&[("fields", "id,name,email,picture"), ("access_token", token)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<FacebookProfile>()
+                .await?;
+            if debug.user_id.as_deref() != Some(profile.id.as_str()) {
+                return Err("Facebook token does not belong to the profile".into());
+            }
+            Ok(ProviderIdentity {
+                subject: profile.id,
+                email: profile.email,
+                name: profile.name,
+                picture: profile.picture.map(|picture| picture.data.url),
+            })
+        })
+    }
+}
+
+pub struct IdentityProviders {
+    pub google: Box<dyn IdentityProvider>,
+    pub apple: Box<dyn IdentityProvider>,
+    pub facebook: Box<dyn IdentityProvider>,
+}
+
+impl IdentityProviders {
+    pub fn get(&self, provider: Provider) -> &dyn IdentityProvider {
+        match provider {
+            Provider::Google => self.google.as_ref(),
+            Provider::Apple => self.apple.as_ref(),
+            Provider::Facebook => self.facebook.as_ref(),
+        }
+    }
+}
+
+pub fn create_identity_providers() -> IdentityProviders {
+    IdentityProviders {
+        google: Box::new(GoogleIdentityProvider {
+            client_ids: get_google_client_ids(),
+            keys: JwksKeySource::from_config(
+                &get_google_jwks_url(),
+                &get_google_jwks(),
+                Duration::from_secs(get_jwks_cache_seconds()),
+            ),
+        }),
+        apple: Box::new(AppleIdentityProvider {
+            client_ids: get_apple_client_ids(),
+            keys: JwksKeySource::from_config(
+                &get_apple_jwks_url(),
+                &get_apple_jwks(),
+                Duration::from_secs(get_jwks_cache_seconds()),
+            ),
+        }),
+        facebook: Box::new(FacebookIdentityProvider {
+            app_id: get_facebook_app_id(),
+            app_secret: get_facebook_app_secret(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::utils::jwks::test_keys::{key_set, key_set_of, now, sign, JwksServer};
+
+    fn apple() -> AppleIdentityProvider {
+        AppleIdentityProvider {
+            client_ids: vec!["com.watchwonder.app".to_string()],
+            keys: JwksKeySource::from_key_set(key_set()),
+        }
+    }
+
+    fn apple_claims() -> Value {
+        json!({
+            "iss": APPLE_ISSUER,
+            "aud": "com.watchwonder.app",
+            "sub": "apple-user-1",
+            "email": "relay@privaterelay.appleid.com",
+            "iat": now(),
+            "exp": now() + 600,
+        })
+    }
+
+    fn google_claims() -> Value {
+        json!({
+            "iss": "accounts.google.com",
+            "aud": "client-1",
+            "sub": "google-user-1",
+            "email": "buyer@example.com",
+            "email_verified": true,
+            "name": "Buyer",
+            "iat": now(),
+            "exp": now() + 3600,
+        })
+    }
+
+    #[tokio::test]
+    async fn apple_accepts_a_valid_token() {
+        let token = sign("test-key-2", "test-key-2", &apple_claims());
+        let identity = apple().verify(&token).await.unwrap();
+        assert_eq!(identity.subject, "apple-user-1");
+        assert_eq!(
+            identity.email.as_deref(),
+            Some("relay@privaterelay.appleid.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn apple_rejects_a_token_for_another_app() {
+        let mut claims = apple_claims();
+        claims["aud"] = json!("com.example.other");
+        let token = sign("test-key-2", "test-key-2", &claims);
+        assert!(apple().verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn apple_rejects_a_token_from_another_issuer() {
+        let mut claims = apple_claims();
+        claims["iss"] = json!("https://accounts.google.com");
+        let token = sign("test-key-2", "test-key-2", &claims);
+        assert!(apple().verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn apple_rejects_an_expired_token() {
+        let mut claims = apple_claims();
+        claims["iat"] = json!(now() - 7200);
+        claims["exp"] = json!(now() - 3600);
+        let token = sign("test-key-2", "test-key-2", &claims);
+        assert!(apple().verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn apple_rejects_an_unknown_key() {
+        let token = sign("test-key-2", "retired-key", &apple_claims());
+        assert!(apple().verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn apple_picks_up_a_rotated_key() {
+        let server = JwksServer::start(key_set_of(&["test-key-1"]), "max-age=3600");
+        let provider = AppleIdentityProvider {
+            client_ids: vec!["com.watchwonder.app".to_string()],
+            keys: JwksKeySource::from_url(&server.url, Duration::from_secs(3600))
+                .with_min_refresh_interval(Duration::ZERO),
+        };
+        let token = sign("test-key-1", "test-key-1", &apple_claims());
+        assert!(provider.verify(&token).await.is_ok());
+
+        // A token naming a key we have not seen makes us fetch the key set again
+        server.publish(key_set_of(&["test-key-1", "test-key-2"]));
+        let token = sign("test-key-2", "test-key-2", &apple_claims());
+        assert!(provider.verify(&token).await.is_ok());
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn apple_is_off_without_client_ids() {
+        let provider = AppleIdentityProvider {
+            client_ids: vec![],
+            keys: JwksKeySource::from_key_set(key_set()),
+        };
+        let token = sign("test-key-2", "test-key-2", &apple_claims());
+        assert!(provider.verify(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn google_shares_only_a_verified_email() {
+        let provider = GoogleIdentityProvider {
+            client_ids: vec!["client-1".to_string()],
+            keys: JwksKeySource::from_key_set(key_set()),
+        };
+        let identity = provider
+            .verify(&sign("test-key-1", "test-key-1", &google_claims()))
+            .await
+            .unwrap();
+        assert_eq!(identity.subject, "google-user-1");
+        assert_eq!(identity.email.as_deref(), Some("buyer@example.com"));
+        assert_eq!(identity.name.as_deref(), Some("Buyer"));
+
+        let mut claims = google_claims();
+        claims["email_verified"] = json!(false);
+        let identity = provider
+            .verify(&sign("test-key-1", "test-key-1", &claims))
+            .await
+            .unwrap();
+        assert_eq!(identity.email, None);
+    }
+}
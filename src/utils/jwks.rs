@@ -0,0 +1,329 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use jsonwebtoken::{jwk::JwkSet, DecodingKey};
+use reqwest::{self, header::CACHE_CONTROL};
+
+// An unknown key id triggers a refetch, but not more often than this so forged tokens
+// cannot make us hammer the provider. An expired key set is always fetched again.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+enum CachedKey {
+    Found(DecodingKey),
+    // Nothing cached yet or past its max-age
+    Expired,
+    Unknown,
+    // Not in a key set fetched less than the minimum refresh interval ago
+    UnknownRecentlyFetched,
+}
+
+struct CachedKeySet {
+    key_set: JwkSet,
+    fetched_at: Instant,
+    ttl: Duration,
+}
+
+// Public keys of an identity provider. Keys fetched from the provider's URL are cached
+// for as long as its Cache-Control allows (or `cache_ttl` without one) and refetched
+// when a token names a key we have not seen, which is how rotation shows up. A key set
+// given in config is used as is and never fetched.
+pub struct JwksKeySource {
+    url: Option<String>,
+    cache_ttl: Duration,
+    min_refresh_interval: Duration,
+    cached: Mutex<Option<CachedKeySet>>,
+}
+
+impl JwksKeySource {
+    pub fn from_url(url: &str, cache_ttl: Duration) -> Self {
+        JwksKeySource {
+            url: Some(url.to_string()),
+            cache_ttl,
+            min_refresh_interval: MIN_REFRESH_INTERVAL,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn from_key_set(key_set: JwkSet) -> Self {
+        JwksKeySource {
+            url: None,
+            cache_ttl: Duration::MAX,
+            min_refresh_interval: MIN_REFRESH_INTERVAL,
+            cached: Mutex::new(Some(CachedKeySet {
+                key_set,
+                fetched_at: Instant::now(),
+                ttl: Duration::MAX,
+            })),
+        }
+    }
+
+    // The url is used unless `key_set_json` holds a key set, which is how tests and
+    // offline setups supply their own keys
+    pub fn from_config(url: &str, key_set_json: &str, cache_ttl: Duration) -> Self {
+        if key_set_json.is_empty() {
+            return Self::from_url(url, cache_ttl);
+        }
+        match serde_json::from_str::<JwkSet>(key_set_json) {
+            Ok(key_set) => Self::from_key_set(key_set),
+            Err(err) => panic!("Invalid JWKS in config: {:?}", err),
+        }
+    }
+
+    // Tests rotate keys without waiting out the minimum refresh interval
+    #[cfg(test)]
+    pub fn with_min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    fn find_cached_key(&self, kid: &str) -> CachedKey {
+        let cached = self.cached.lock().unwrap();
+        let cached = match cached.as_ref() {
+            Some(cached) => cached,
+            None => return CachedKey::Expired,
+        };
+        if cached.fetched_at.elapsed() > cached.ttl {
+            return CachedKey::Expired;
+        }
+        match cached
+            .key_set
+            .find(kid)
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).ok())
+        {
+            Some(key) => CachedKey::Found(key),
+            None if cached.fetched_at.elapsed() > self.min_refresh_interval => CachedKey::Unknown,
+            None => CachedKey::UnknownRecentlyFetched,
+        }
+    }
+
+    pub async fn get_decoding_key(
+        &self,
+        kid: &str,
+    ) -> Result<DecodingKey, Box<dyn std::error::Error>> {
+        match self.find_cached_key(kid) {
+            CachedKey::Found(key) => return Ok(key),
+            CachedKey::UnknownRecentlyFetched => {
+                return Err(format!("Unknown signing key {}", kid).into())
+            }
+            CachedKey::Expired | CachedKey::Unknown => {}
+        }
+        let url = match &self.url {
+            Some(url) => url,
+            None => return Err(format!("Unknown signing key {}", kid).into()),
+        };
+
+        let res = reqwest::Client::new()
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?;
+        let ttl = res
+            .headers()
+            .get(CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(get_max_age)
+            .unwrap_or(self.cache_ttl);
+        let key_set = res.json::<JwkSet>().await?;
+        // Taken from the fresh key set, a max-age of 0 would leave the cache stale already
+        let key = key_set
+            .find(kid)
+            .and_then(|jwk| DecodingKey::from_jwk(jwk).ok());
+        *self.cached.lock().unwrap() = Some(CachedKeySet {
+            key_set,
+            fetched_at: Instant::now(),
+            ttl,
+        });
+        key.ok_or_else(|| format!("Unknown signing key {}", kid).into())
+    }
+}
+
+fn get_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+}
+
+// The RSA keys in tests/fixtures/jwks, whose public halves make up jwks.json, and a
+// local JWKS endpoint to fetch them from
+#[cfg(test)]
+pub mod test_keys {
+    use std::{
+        fs,
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use jsonwebtoken::{encode, jwk::JwkSet, Algorithm, EncodingKey, Header};
+    use serde::Serialize;
+
+    const FIXTURES_DIR: &str = "./tests/fixtures/jwks";
+
+    pub fn key_set() -> JwkSet {
+        serde_json::from_str(&fs::read_to_string(format!("{FIXTURES_DIR}/jwks.json")).unwrap())
+            .unwrap()
+    }
+
+    // Only the keys with these ids
+    pub fn key_set_of(kids: &[&str]) -> JwkSet {
+        let mut key_set = key_set();
+        key_set
+            .keys
+            .retain(|jwk| kids.contains(&jwk.common.key_id.as_deref().unwrap_or_default()));
+        key_set
+    }
+
+    // Signs with the private key `key` and names `kid` in the header, which only differ
+    // when a test needs a token for a key the provider does not publish
+    pub fn sign(key: &str, kid: &str, claims: &impl Serialize) -> String {
+        let pem = fs::read(format!("{FIXTURES_DIR}/{key}.pem")).unwrap();
+        let header = Header {
+            kid: Some(kid.to_string()),
+            ..Header::new(Algorithm::RS256)
+        };
+        encode(&header, claims, &EncodingKey::from_rsa_pem(&pem).unwrap()).unwrap()
+    }
+
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    // Serves `key_set` with `cache_control` until the test ends and counts the requests
+    pub struct JwksServer {
+        pub url: String,
+        requests: Arc<AtomicUsize>,
+        key_set: Arc<Mutex<JwkSet>>,
+    }
+
+    impl JwksServer {
+        pub fn start(key_set: JwkSet, cache_control: &str) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/keys", listener.local_addr().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+            let key_set = Arc::new(Mutex::new(key_set));
+            let cache_control = cache_control.to_string();
+
+            let (server_requests, server_key_set) = (requests.clone(), key_set.clone());
+            thread::spawn(move || {
+                for mut stream in listener.incoming().flatten() {
+                    let mut request = vec![];
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        match stream.read(&mut buffer) {
+                            Ok(0) | Err(_) => break,
+                            Ok(n) => request.extend_from_slice(&buffer[..n]),
+                        }
+                    }
+                    server_requests.fetch_add(1, Ordering::SeqCst);
+                    let body = serde_json::to_string(&*server_key_set.lock().unwrap()).unwrap();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nCache-Control: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        cache_control,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            });
+            JwksServer {
+                url,
+                requests,
+                key_set,
+            }
+        }
+
+        // What the provider publishes from now on, like after a key rotation
+        pub fn publish(&self, key_set: JwkSet) {
+            *self.key_set.lock().unwrap() = key_set;
+        }
+
+        pub fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_keys::*, *};
+
+    #[tokio::test]
+    async fn given_key_set_is_used_without_fetching() {
+        let keys = JwksKeySource::from_key_set(key_set());
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+        assert!(keys.get_decoding_key("test-key-2").await.is_ok());
+        assert!(keys.get_decoding_key("unknown-key").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fetched_keys_are_cached() {
+        let server = JwksServer::start(key_set(), "public, max-age=3600");
+        let keys = JwksKeySource::from_url(&server.url, Duration::from_secs(60));
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+        assert!(keys.get_decoding_key("test-key-2").await.is_ok());
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_key_refetches_the_key_set() {
+        let server = JwksServer::start(key_set_of(&["test-key-1"]), "public, max-age=3600");
+        let keys = JwksKeySource::from_url(&server.url, Duration::from_secs(60))
+            .with_min_refresh_interval(Duration::ZERO);
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+
+        server.publish(key_set_of(&["test-key-2"]));
+        assert!(keys.get_decoding_key("test-key-2").await.is_ok());
+        assert_eq!(server.requests(), 2);
+        // The rotated out key is gone with the old key set
+        assert!(keys.get_decoding_key("test-key-1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn unknown_keys_do_not_refetch_more_often_than_the_minimum_interval() {
+        let server = JwksServer::start(key_set_of(&["test-key-1"]), "public, max-age=3600");
+        let keys = JwksKeySource::from_url(&server.url, Duration::from_secs(60));
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+
+        server.publish(key_set_of(&["test-key-2"]));
+        assert!(keys.get_decoding_key("test-key-2").await.is_err());
+        assert!(keys.get_decoding_key("forged-key").await.is_err());
+        assert_eq!(server.requests(), 1);
+    }
+
+    // A max-age below the minimum refresh interval still gets the key set fetched again
+    #[tokio::test]
+    async fn stale_key_set_is_fetched_again() {
+        let server = JwksServer::start(key_set_of(&["test-key-1"]), "public, max-age=0");
+        let keys = JwksKeySource::from_url(&server.url, Duration::from_secs(3600));
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+        assert!(keys.get_decoding_key("test-key-1").await.is_ok());
+        assert_eq!(server.requests(), 2);
+
+        server.publish(key_set_of(&["test-key-2"]));
+        assert!(keys.get_decoding_key("test-key-2").await.is_ok());
+        assert_eq!(server.requests(), 3);
+    }
+
+    #[test]
+    fn max_age_is_read_from_cache_control() {
+        assert_eq!(
+            get_max_age("public, max-age=19845, must-revalidate, no-transform"),
+            Some(Duration::from_secs(19845))
+        );
+        assert_eq!(get_max_age("no-cache"), None);
+        assert_eq!(get_max_age("max-age=soon"), None);
+    }
+}
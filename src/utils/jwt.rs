@@ -1,13 +1,24 @@
-use std::env;
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use jsonwebtoken::{
     decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::models::user::TokenUser;
+
+use super::setting::get_access_token_expires_in;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String,
+    pub user_id: i32,
+    pub role: String,
+    // Compared with users.token_version, an older token is rejected
+    pub token_version: i32,
+    pub iat: usize,
     pub exp: usize,
 }
 
@@ -20,6 +31,21 @@ pub fn sign_token(claims: &Claims) -> Result<String, jsonwebtoken::errors::Error
     )
 }
 
+// Access tokens are short lived, clients renew them with their refresh token
+pub fn sign_access_token(user: &TokenUser) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as usize;
+    sign_token(&Claims {
+        user_id: user.user_id,
+        role: user.role.clone(),
+        token_version: user.token_version,
+        iat: now,
+        exp: now + get_access_token_expires_in() as usize,
+    })
+}
+
 // pub fn verify_token(token: &str) -> bool {
 //     let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
 
@@ -34,7 +60,7 @@ pub fn sign_token(claims: &Claims) -> Result<String, jsonwebtoken::errors::Error
 //     }
 // }
 
-pub fn verify_token_and_get_sub(token: &str) -> Option<String> {
+pub fn verify_token_and_get_claims(token: &str) -> Option<Claims> {
     let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
 
     let validation = Validation::new(Algorithm::HS256); // Assuming you're using HS256
@@ -43,7 +69,7 @@ pub fn verify_token_and_get_sub(token: &str) -> Option<String> {
         &DecodingKey::from_secret(secret.as_ref()),
         &validation,
     ) {
-        Ok(data) => Some(data.claims.sub),
+        Ok(data) => Some(data.claims),
         Err(err) => {
             match *err.kind() {
                 ErrorKind::InvalidToken => println!("Token is invalid"), // Example logging for invalid token
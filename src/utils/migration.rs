@@ -0,0 +1,318 @@
+use chrono::NaiveDateTime;
+use deadpool_postgres::GenericClient;
+use tokio_postgres::Error;
+
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub up: &'static str,
+    // None when the migration cannot be undone (baseline schema and seeds)
+    pub down: Option<&'static str>,
+}
+
+// Append new migrations at the end with the next version number and never edit one
+// that has already been released, existing deployments will not run it again.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        up: include_str!("../../database/migrations/0001_baseline.up.sql"),
+        down: None,
+    },
+    Migration {
+        version: 2,
+        name: "seed_data",
+        up: include_str!("../../database/migrations/0002_seed_data.up.sql"),
+        down: None,
+    },
+    Migration {
+        version: 3,
+        name: "order_history_and_auction_bidding",
+        up: include_str!("../../database/migrations/0003_order_history_and_auction_bidding.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0003_order_history_and_auction_bidding.down.sql"
+        )),
+    },
+    Migration {
+        version: 4,
+        name: "refresh_tokens",
+        up: include_str!("../../database/migrations/0004_refresh_tokens.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0004_refresh_tokens.down.sql"
+        )),
+    },
+    Migration {
+        version: 5,
+        name: "user_token_version",
+        up: include_str!("../../database/migrations/0005_user_token_version.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0005_user_token_version.down.sql"
+        )),
+    },
+    Migration {
+        version: 6,
+        name: "password_reset_codes",
+        up: include_str!("../../database/migrations/0006_password_reset_codes.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0006_password_reset_codes.down.sql"
+        )),
+    },
+    Migration {
+        version: 7,
+        name: "auth_throttles",
+        up: include_str!("../../database/migrations/0007_auth_throttles.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0007_auth_throttles.down.sql"
+        )),
+    },
+    Migration {
+        version: 8,
+        name: "roles_and_permissions",
+        up: include_str!("../../database/migrations/0008_roles_and_permissions.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0008_roles_and_permissions.down.sql"
+        )),
+    },
+    Migration {
+        version: 9,
+        name: "seller_applications",
+        up: include_str!("../../database/migrations/0009_seller_applications.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0009_seller_applications.down.sql"
+        )),
+    },
+    Migration {
+        version: 10,
+        name: "seller_statements",
+        up: include_str!("../../database/migrations/0010_seller_statements.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0010_seller_statements.down.sql"
+        )),
+    },
+    Migration {
+        version: 11,
+        name: "order_item_discounts",
+        up: include_str!("../../database/migrations/0011_order_item_discounts.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0011_order_item_discounts.down.sql"
+        )),
+    },
+    Migration {
+        version: 12,
+        name: "coupons",
+        up: include_str!("../../database/migrations/0012_coupons.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0012_coupons.down.sql"
+        )),
+    },
+    Migration {
+        version: 13,
+        name: "discount_rules_at_pricing",
+        up: include_str!("../../database/migrations/0013_discount_rules_at_pricing.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0013_discount_rules_at_pricing.down.sql"
+        )),
+    },
+    Migration {
+        version: 14,
+        name: "flash_sales",
+        up: include_str!("../../database/migrations/0014_flash_sales.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0014_flash_sales.down.sql"
+        )),
+    },
+    Migration {
+        version: 15,
+        name: "unique_provider_ids",
+        up: include_str!("../../database/migrations/0015_unique_provider_ids.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0015_unique_provider_ids.down.sql"
+        )),
+    },
+    Migration {
+        version: 16,
+        name: "user_password_set",
+        up: include_str!("../../database/migrations/0016_user_password_set.up.sql"),
+        down: Some(include_str!(
+            "../../database/migrations/0016_user_password_set.down.sql"
+        )),
+    },
+];
+
+// Databases created from the old db.sql already have these, they are recorded as
+// applied instead of being run.
+const BASELINE_VERSIONS: [i32; 2] = [1, 2];
+
+// Keeps two instances starting at the same time from running the same migration twice
+const MIGRATION_LOCK_ID: i64 = 7_270_010;
+
+pub struct MigrationStatus {
+    pub version: i32,
+    pub name: &'static str,
+    pub applied_at: Option<NaiveDateTime>,
+}
+
+async fn prepare_migrations_table(client: &impl GenericClient) -> Result<(), Error> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations
+            (
+                version INT PRIMARY KEY,
+                name VARCHAR(255) NOT NULL,
+                applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            )",
+        )
+        .await?;
+
+    let row = client
+        .query_one(
+            "select (select count(*) from schema_migrations) as applied, to_regclass('public.users') is not null as has_schema",
+            &[],
+        )
+        .await?;
+    let applied: i64 = row.get("applied");
+    let has_schema: bool = row.get("has_schema");
+    if applied == 0 && has_schema {
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|m| BASELINE_VERSIONS.contains(&m.version))
+        {
+            client
+                .execute(
+                    "insert into schema_migrations (version, name) values ($1, $2)",
+                    &[&migration.version, &migration.name],
+                )
+                .await?;
+        }
+        println!("Existing database found, baseline marked as applied");
+    }
+    Ok(())
+}
+
+async fn get_applied_versions(client: &impl GenericClient) -> Result<Vec<i32>, Error> {
+    let rows = client
+        .query(
+            "select version from schema_migrations order by version",
+            &[],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get("version")).collect())
+}
+
+async fn apply_pending_migrations(client: &mut impl GenericClient) -> Result<Vec<i32>, Error> {
+    prepare_migrations_table(client).await?;
+    let applied_versions = get_applied_versions(client).await?;
+
+    let mut applied = vec![];
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| !applied_versions.contains(&m.version))
+    {
+        let transaction = client.transaction().await?;
+        transaction.batch_execute(migration.up).await?;
+        transaction
+            .execute(
+                "insert into schema_migrations (version, name) values ($1, $2)",
+                &[&migration.version, &migration.name],
+            )
+            .await?;
+        transaction.commit().await?;
+        println!(
+            "Applied migration {:04}_{}",
+            migration.version, migration.name
+        );
+        applied.push(migration.version);
+    }
+    Ok(applied)
+}
+
+pub async fn migrate(client: &mut impl GenericClient) -> Result<Vec<i32>, Error> {
+    client
+        .execute("select pg_advisory_lock($1)", &[&MIGRATION_LOCK_ID])
+        .await?;
+    let result = apply_pending_migrations(client).await;
+    client
+        .execute("select pg_advisory_unlock($1)", &[&MIGRATION_LOCK_ID])
+        .await?;
+    result
+}
+
+async fn revert_migrations(
+    steps: usize,
+    client: &mut impl GenericClient,
+) -> Result<Vec<i32>, Box<dyn std::error::Error>> {
+    prepare_migrations_table(client).await?;
+    let applied_versions = get_applied_versions(client).await?;
+
+    let mut targets = vec![];
+    for version in applied_versions.iter().rev().take(steps) {
+        let migration = match MIGRATIONS.iter().find(|m| m.version == *version) {
+            Some(migration) => migration,
+            None => return Err(format!("Migration {} is not known to this build", version).into()),
+        };
+        match migration.down {
+            Some(down) => targets.push((migration, down)),
+            None => {
+                return Err(format!(
+                    "Migration {:04}_{} cannot be rolled back",
+                    migration.version, migration.name
+                )
+                .into())
+            }
+        }
+    }
+
+    let mut reverted = vec![];
+    for (migration, down) in targets {
+        let transaction = client.transaction().await?;
+        transaction.batch_execute(down).await?;
+        transaction
+            .execute(
+                "delete from schema_migrations where version = $1",
+                &[&migration.version],
+            )
+            .await?;
+        transaction.commit().await?;
+        println!(
+            "Rolled back migration {:04}_{}",
+            migration.version, migration.name
+        );
+        reverted.push(migration.version);
+    }
+    Ok(reverted)
+}
+
+pub async fn rollback(
+    steps: usize,
+    client: &mut impl GenericClient,
+) -> Result<Vec<i32>, Box<dyn std::error::Error>> {
+    client
+        .execute("select pg_advisory_lock($1)", &[&MIGRATION_LOCK_ID])
+        .await?;
+    let result = revert_migrations(steps, client).await;
+    client
+        .execute("select pg_advisory_unlock($1)", &[&MIGRATION_LOCK_ID])
+        .await?;
+    result
+}
+
+pub async fn get_migration_status(
+    client: &impl GenericClient,
+) -> Result<Vec<MigrationStatus>, Error> {
+    prepare_migrations_table(client).await?;
+    let rows = client
+        .query("select version, applied_at from schema_migrations", &[])
+        .await?;
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied_at: rows
+                .iter()
+                .find(|row| row.get::<_, i32>("version") == migration.version)
+                .map(|row| row.get("applied_at")),
+        })
+        .collect())
+}
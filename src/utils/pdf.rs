@@ -0,0 +1,683 @@
+use std::{collections::BTreeMap, fs};
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use pdf_writer::{
+    types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap},
+    Content, Filter, Finish, Name, Pdf, Rect, Ref, Str,
+};
+use rustybuzz::{ttf_parser::GlyphId, Face, UnicodeBuffer};
+
+pub type PdfResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+// A4 in points, coordinates given to `PdfDocument` start at the top left corner
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    fn rgb(self) -> (f32, f32, f32) {
+        (
+            self.0 as f32 / 255.0,
+            self.1 as f32 / 255.0,
+            self.2 as f32 / 255.0,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle<'s> {
+    pub font: &'s str,
+    pub size: f32,
+    pub color: Color,
+}
+
+// A TrueType font, named by its file name without the extension
+pub struct FontFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+// Reads the `.ttf` fonts of a directory in name order, which is also the order fonts
+// are tried in for characters the requested font doesn't have
+pub fn load_fonts(dir: &str) -> PdfResult<Vec<FontFile>> {
+    let mut fonts = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_ttf = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ttf"));
+        if !is_ttf {
+            continue;
+        }
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) => stem.to_string(),
+            None => continue,
+        };
+        fonts.push(FontFile {
+            name,
+            data: fs::read(&path)?,
+        });
+    }
+    fonts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fonts)
+}
+
+struct DocumentFont<'a> {
+    name: &'a str,
+    data: &'a [u8],
+    face: Face<'a>,
+    // Glyphs drawn so far and the text each one stands for, used to subset the font
+    // and to make the text searchable
+    glyphs: BTreeMap<u16, String>,
+}
+
+impl DocumentFont<'_> {
+    fn has_char(&self, c: char) -> bool {
+        self.face.glyph_index(c).is_some_and(|id| id.0 != 0)
+    }
+
+    fn units(&self, value: f32) -> f32 {
+        value * 1000.0 / self.face.units_per_em() as f32
+    }
+}
+
+struct ShapedGlyph {
+    font: usize,
+    id: u16,
+    text: String,
+    x: f32,
+    y_offset: f32,
+}
+
+struct ShapedText {
+    glyphs: Vec<ShapedGlyph>,
+    width: f32,
+}
+
+struct ImageData {
+    path: String,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    // Components of a JPEG embedded as is, otherwise the data is deflated RGB
+    jpeg_components: Option<u8>,
+    adobe_inverted: bool,
+    alpha: Option<Vec<u8>>,
+}
+
+pub struct PdfDocument<'a> {
+    fonts: Vec<DocumentFont<'a>>,
+    images: Vec<ImageData>,
+    pages: Vec<Content>,
+}
+
+impl<'a> PdfDocument<'a> {
+    pub fn new(fonts: &'a [FontFile]) -> PdfResult<Self> {
+        let mut document_fonts = vec![];
+        for font in fonts {
+            let face = Face::from_slice(&font.data, 0)
+                .ok_or_else(|| format!("Font {} could not be read", font.name))?;
+            document_fonts.push(DocumentFont {
+                name: &font.name,
+                data: &font.data,
+                face,
+                glyphs: BTreeMap::new(),
+            });
+        }
+        if document_fonts.is_empty() {
+            return Err("No fonts to render with".into());
+        }
+        Ok(PdfDocument {
+            fonts: document_fonts,
+            images: vec![],
+            pages: vec![Content::new()],
+        })
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(Content::new());
+    }
+
+    fn font_index(&self, name: &str) -> usize {
+        self.fonts.iter().position(|f| f.name == name).unwrap_or(0)
+    }
+
+    // Characters the requested font lacks, like Myanmar script in a Latin font, are
+    // set in the first font that has them
+    fn font_for(&self, preferred: usize, c: char) -> usize {
+        if self.fonts[preferred].has_char(c) {
+            return preferred;
+        }
+        self.fonts
+            .iter()
+            .position(|f| f.has_char(c))
+            .unwrap_or(preferred)
+    }
+
+    fn runs<'t>(&self, text: &'t str, preferred: usize) -> Vec<(usize, &'t str)> {
+        let mut runs: Vec<(usize, &str)> = vec![];
+        let mut start = 0;
+        let mut current: Option<usize> = None;
+        for (i, c) in text.char_indices() {
+            // Spaces and punctuation stay in the run they are in so a run isn't cut
+            // in the middle of a phrase
+            let font = match current {
+                Some(f)
+                    if (c.is_whitespace() || c.is_ascii_punctuation())
+                        && self.fonts[f].has_char(c) =>
+                {
+                    f
+                }
+                _ => self.font_for(preferred, c),
+            };
+            if let Some(f) = current {
+                if f != font {
+                    runs.push((f, &text[start..i]));
+                    start = i;
+                }
+            }
+            current = Some(font);
+        }
+        if let Some(f) = current {
+            runs.push((f, &text[start..]));
+        }
+        runs
+    }
+
+    fn shape(&self, text: &str, style: &TextStyle) -> ShapedText {
+        let preferred = self.font_index(style.font);
+        let mut glyphs = vec![];
+        let mut x = 0.0;
+        for (font_index, run) in self.runs(text, preferred) {
+            let font = &self.fonts[font_index];
+            let scale = style.size / font.face.units_per_em() as f32;
+
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(run);
+            buffer.guess_segment_properties();
+            let output = rustybuzz::shape(&font.face, &[], buffer);
+
+            let infos = output.glyph_infos();
+            let positions = output.glyph_positions();
+            let mut cluster_starts: Vec<usize> =
+                infos.iter().map(|info| info.cluster as usize).collect();
+            cluster_starts.sort_unstable();
+            cluster_starts.dedup();
+
+            let mut previous_cluster = None;
+            for (info, position) in infos.iter().zip(positions) {
+                let cluster = info.cluster as usize;
+                let end = cluster_starts
+                    .iter()
+                    .find(|&&start| start > cluster)
+                    .copied()
+                    .unwrap_or(run.len());
+                let cluster_text = &run[cluster..end];
+                // A glyph standing for one of the cluster's characters carries that
+                // character, so reordered glyphs like the Myanmar vowel sign E keep their
+                // own text. Otherwise the first glyph of a cluster carries all of it.
+                let own_char = cluster_text.chars().find(|&c| {
+                    font.face
+                        .glyph_index(c)
+                        .is_some_and(|id| id.0 as u32 == info.glyph_id)
+                });
+                let glyph_text = match own_char {
+                    Some(c) => c.to_string(),
+                    None if previous_cluster != Some(cluster) => cluster_text.to_string(),
+                    None => String::new(),
+                };
+                previous_cluster = Some(cluster);
+
+                glyphs.push(ShapedGlyph {
+                    font: font_index,
+                    id: info.glyph_id as u16,
+                    text: glyph_text,
+                    x: x + position.x_offset as f32 * scale,
+                    y_offset: position.y_offset as f32 * scale,
+                });
+                x += position.x_advance as f32 * scale;
+            }
+        }
+        ShapedText { glyphs, width: x }
+    }
+
+    pub fn measure(&self, text: &str, style: &TextStyle) -> f32 {
+        self.shape(text, style).width
+    }
+
+    pub fn line_height(&self, style: &TextStyle) -> f32 {
+        style.size * 1.35
+    }
+
+    // Draws a line of text with the top of its line box at `y`
+    pub fn text(&mut self, x: f32, y: f32, text: &str, style: &TextStyle) -> f32 {
+        let shaped = self.shape(text, style);
+        let font = &self.fonts[self.font_index(style.font)];
+        let ascent = font.face.ascender() as f32 / font.face.units_per_em() as f32;
+        let leading = (self.line_height(style) - style.size) / 2.0;
+        let baseline = PAGE_HEIGHT - (y + leading + ascent * style.size);
+
+        let (r, g, b) = style.color.rgb();
+        let content = self.pages.last_mut().unwrap();
+        content.set_fill_rgb(r, g, b);
+        content.begin_text();
+        let mut current_font = None;
+        for glyph in &shaped.glyphs {
+            if current_font != Some(glyph.font) {
+                content.set_font(Name(format!("F{}", glyph.font).as_bytes()), style.size);
+                current_font = Some(glyph.font);
+            }
+            content.set_text_matrix([1.0, 0.0, 0.0, 1.0, x + glyph.x, baseline + glyph.y_offset]);
+            content.show(Str(&glyph.id.to_be_bytes()));
+
+            let used = self.fonts[glyph.font].glyphs.entry(glyph.id).or_default();
+            // Characters no font has are drawn as the missing glyph and aren't mapped
+            if used.is_empty() && glyph.id != 0 {
+                used.push_str(&glyph.text);
+            }
+        }
+        content.end_text();
+        shaped.width
+    }
+
+    pub fn text_in(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        align: Align,
+        text: &str,
+        style: &TextStyle,
+    ) -> f32 {
+        let text_width = self.measure(text, style);
+        let x = match align {
+            Align::Left => x,
+            Align::Center => x + (width - text_width) / 2.0,
+            Align::Right => x + width - text_width,
+        };
+        self.text(x, y, text, style);
+        x
+    }
+
+    // Breaks text into lines that fit `width`, at spaces or, for Myanmar script which
+    // doesn't separate words, between syllables
+    pub fn wrap(&self, text: &str, style: &TextStyle, width: f32) -> Vec<String> {
+        let mut lines = vec![];
+        for paragraph in text.split('\n') {
+            let chars: Vec<(usize, char)> = paragraph.char_indices().collect();
+            let mut line_start = 0;
+            let mut last_break: Option<usize> = None;
+            for (i, &(index, c)) in chars.iter().enumerate() {
+                if index > line_start && is_break_before(&chars, i) {
+                    let candidate = paragraph[line_start..index].trim_end();
+                    if self.measure(candidate, style) > width {
+                        if let Some(break_at) = last_break {
+                            lines.push(paragraph[line_start..break_at].trim_end().to_string());
+                            line_start = break_at;
+                        }
+                    }
+                    last_break = Some(index);
+                }
+                if c == ' ' && index == line_start {
+                    line_start = index + 1;
+                }
+            }
+            let rest = paragraph[line_start..].trim_end();
+            if self.measure(rest, style) > width {
+                if let Some(break_at) = last_break.filter(|&b| b > line_start) {
+                    lines.push(paragraph[line_start..break_at].trim_end().to_string());
+                    line_start = break_at;
+                }
+            }
+            lines.push(paragraph[line_start..].trim().to_string());
+        }
+        lines
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        let (r, g, b) = color.rgb();
+        let content = self.pages.last_mut().unwrap();
+        content.set_fill_rgb(r, g, b);
+        content.rect(x, PAGE_HEIGHT - y - height, width, height);
+        content.fill_nonzero();
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color, line_width: f32) {
+        let (r, g, b) = color.rgb();
+        let content = self.pages.last_mut().unwrap();
+        content.set_stroke_rgb(r, g, b);
+        content.set_line_width(line_width);
+        content.move_to(x1, PAGE_HEIGHT - y1);
+        content.line_to(x2, PAGE_HEIGHT - y2);
+        content.stroke();
+    }
+
+    pub fn stroke_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Color,
+        line_width: f32,
+    ) {
+        let (r, g, b) = color.rgb();
+        let content = self.pages.last_mut().unwrap();
+        content.set_stroke_rgb(r, g, b);
+        content.set_line_width(line_width);
+        content.rect(x, PAGE_HEIGHT - y - height, width, height);
+        content.stroke();
+    }
+
+    // Images are read once per document however often they are drawn
+    pub fn image(&mut self, path: &str, x: f32, y: f32, width: f32, height: f32) -> PdfResult<()> {
+        let index = match self.images.iter().position(|image| image.path == path) {
+            Some(index) => index,
+            None => {
+                self.images.push(read_image(path)?);
+                self.images.len() - 1
+            }
+        };
+        let content = self.pages.last_mut().unwrap();
+        content.save_state();
+        content.transform([width, 0.0, 0.0, height, x, PAGE_HEIGHT - y - height]);
+        content.x_object(Name(format!("Im{}", index).as_bytes()));
+        content.restore_state();
+        Ok(())
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let mut pdf = Pdf::new();
+        let mut next_id = 1;
+        let mut alloc = || {
+            let id = Ref::new(next_id);
+            next_id += 1;
+            id
+        };
+
+        let catalog_id = alloc();
+        let page_tree_id = alloc();
+        let page_ids: Vec<Ref> = self.pages.iter().map(|_| alloc()).collect();
+        let content_ids: Vec<Ref> = self.pages.iter().map(|_| alloc()).collect();
+        // Fonts nothing was drawn with are left out of the file
+        let font_ids: Vec<Option<Ref>> = self
+            .fonts
+            .iter()
+            .map(|f| (!f.glyphs.is_empty()).then(&mut alloc))
+            .collect();
+        let image_ids: Vec<Ref> = self.images.iter().map(|_| alloc()).collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+
+        for (i, content) in self.pages.into_iter().enumerate() {
+            let mut page = pdf.page(page_ids[i]);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .parent(page_tree_id)
+                .contents(content_ids[i]);
+            let mut resources = page.resources();
+            let mut fonts = resources.fonts();
+            for (index, id) in font_ids.iter().enumerate() {
+                if let Some(id) = id {
+                    fonts.pair(Name(format!("F{}", index).as_bytes()), *id);
+                }
+            }
+            fonts.finish();
+            let mut x_objects = resources.x_objects();
+            for (index, id) in image_ids.iter().enumerate() {
+                x_objects.pair(Name(format!("Im{}", index).as_bytes()), *id);
+            }
+            x_objects.finish();
+            resources.finish();
+            page.finish();
+
+            let data = compress_to_vec_zlib(&content.finish(), 6);
+            pdf.stream(content_ids[i], &data)
+                .filter(Filter::FlateDecode);
+        }
+
+        for (font, id) in self.fonts.iter().zip(&font_ids) {
+            if let Some(id) = id {
+                write_font(&mut pdf, font, *id, &mut alloc);
+            }
+        }
+
+        for (image, id) in self.images.iter().zip(&image_ids) {
+            let mask_id = image.alpha.as_ref().map(|_| alloc());
+            let mut xobject = pdf.image_xobject(*id, &image.data);
+            xobject.width(image.width as i32);
+            xobject.height(image.height as i32);
+            xobject.bits_per_component(8);
+            match image.jpeg_components {
+                Some(components) => {
+                    xobject.filter(Filter::DctDecode);
+                    match components {
+                        1 => xobject.color_space().device_gray(),
+                        4 => xobject.color_space().device_cmyk(),
+                        _ => xobject.color_space().device_rgb(),
+                    }
+                    if components == 4 && image.adobe_inverted {
+                        xobject.decode([1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0]);
+                    }
+                }
+                None => {
+                    xobject.filter(Filter::FlateDecode);
+                    xobject.color_space().device_rgb();
+                }
+            }
+            if let Some(mask_id) = mask_id {
+                xobject.s_mask(mask_id);
+            }
+            xobject.finish();
+
+            if let (Some(mask_id), Some(alpha)) = (mask_id, &image.alpha) {
+                let mut mask = pdf.image_xobject(mask_id, alpha);
+                mask.filter(Filter::FlateDecode);
+                mask.width(image.width as i32);
+                mask.height(image.height as i32);
+                mask.color_space().device_gray();
+                mask.bits_per_component(8);
+            }
+        }
+
+        pdf.finish()
+    }
+}
+
+// Where a line may break: after a space, or before a Myanmar syllable. A Myanmar
+// consonant starts a syllable unless it is stacked under the previous one or killed
+// by an asat.
+fn is_break_before(chars: &[(usize, char)], i: usize) -> bool {
+    const VIRAMA: char = '\u{1039}';
+    const ASAT: char = '\u{103A}';
+    let c = chars[i].1;
+    let previous = if i > 0 { Some(chars[i - 1].1) } else { None };
+    if previous == Some(' ') {
+        return c != ' ';
+    }
+    let starts_syllable = ('\u{1000}'..='\u{102A}').contains(&c);
+    if !starts_syllable || previous.is_none() || previous == Some(VIRAMA) {
+        return false;
+    }
+    let next = chars.get(i + 1).map(|&(_, c)| c);
+    next != Some(ASAT) && next != Some(VIRAMA)
+}
+
+// Fonts are embedded as CID fonts addressed by glyph id, subset to the glyphs drawn
+fn write_font(pdf: &mut Pdf, font: &DocumentFont, type0_id: Ref, alloc: &mut impl FnMut() -> Ref) {
+    let cid_id = alloc();
+    let descriptor_id = alloc();
+    let cmap_id = alloc();
+    let data_id = alloc();
+
+    let base_font = format!(
+        "{}+{}",
+        subset_tag(&font.glyphs),
+        font.name.replace(' ', "")
+    );
+
+    pdf.type0_font(type0_id)
+        .base_font(Name(base_font.as_bytes()))
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_id)
+        .to_unicode(cmap_id);
+
+    let mut cid = pdf.cid_font(cid_id);
+    cid.subtype(CidFontType::Type2);
+    cid.base_font(Name(base_font.as_bytes()));
+    cid.system_info(SYSTEM_INFO);
+    cid.font_descriptor(descriptor_id);
+    cid.default_width(0.0);
+    cid.cid_to_gid_map_predefined(Name(b"Identity"));
+    let mut widths = cid.widths();
+    for &id in font.glyphs.keys() {
+        let advance = font.face.glyph_hor_advance(GlyphId(id)).unwrap_or(0);
+        widths.consecutive(id, [font.units(advance as f32)]);
+    }
+    widths.finish();
+    cid.finish();
+
+    let bbox = font.face.global_bounding_box();
+    let ascender = font.units(font.face.ascender() as f32);
+    let mut flags = FontFlags::empty();
+    flags.insert(FontFlags::SYMBOLIC);
+    flags.set(FontFlags::ITALIC, font.face.is_italic());
+    flags.set(FontFlags::FIXED_PITCH, font.face.is_monospaced());
+    pdf.font_descriptor(descriptor_id)
+        .name(Name(base_font.as_bytes()))
+        .flags(flags)
+        .bbox(Rect::new(
+            font.units(bbox.x_min as f32),
+            font.units(bbox.y_min as f32),
+            font.units(bbox.x_max as f32),
+            font.units(bbox.y_max as f32),
+        ))
+        .italic_angle(font.face.italic_angle().unwrap_or(0.0))
+        .ascent(ascender)
+        .descent(font.units(font.face.descender() as f32))
+        .cap_height(
+            font.face
+                .capital_height()
+                .map(|height| font.units(height as f32))
+                .unwrap_or(ascender),
+        )
+        .stem_v(10.0 + 0.244 * (font.face.weight().to_number() as f32 - 50.0))
+        .font_file2(data_id);
+
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+    for (&id, text) in &font.glyphs {
+        if !text.is_empty() {
+            cmap.pair_with_multiple(id, text.chars());
+        }
+    }
+    pdf.cmap(cmap_id, &cmap.finish());
+
+    let glyph_ids: Vec<u16> = font.glyphs.keys().copied().collect();
+    let data = match subsetter::subset(font.data, 0, subsetter::Profile::pdf(&glyph_ids)) {
+        Ok(data) => data,
+        Err(err) => {
+            println!("Error subsetting font {}: {:?}", font.name, err);
+            font.data.to_vec()
+        }
+    };
+    let data = compress_to_vec_zlib(&data, 6);
+    pdf.stream(data_id, &data).filter(Filter::FlateDecode);
+}
+
+// Subset fonts are named with a tag derived from their glyphs
+fn subset_tag(glyphs: &BTreeMap<u16, String>) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &id in glyphs.keys() {
+        for byte in id.to_be_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    (0..6)
+        .map(|_| {
+            let letter = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            letter
+        })
+        .collect()
+}
+
+fn read_image(path: &str) -> PdfResult<ImageData> {
+    let bytes = fs::read(path)?;
+    if let Some((width, height, components, adobe_inverted)) = read_jpeg_header(&bytes) {
+        return Ok(ImageData {
+            path: path.to_string(),
+            width,
+            height,
+            data: bytes,
+            jpeg_components: Some(components),
+            adobe_inverted,
+            alpha: None,
+        });
+    }
+
+    let image = ::image::load_from_memory(&bytes)?.to_rgba8();
+    let (width, height) = image.dimensions();
+    let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+    let mut alpha = Vec::with_capacity((width * height) as usize);
+    for pixel in image.pixels() {
+        rgb.extend_from_slice(&pixel.0[..3]);
+        alpha.push(pixel.0[3]);
+    }
+    let is_opaque = alpha.iter().all(|&a| a == 255);
+    Ok(ImageData {
+        path: path.to_string(),
+        width,
+        height,
+        data: compress_to_vec_zlib(&rgb, 6),
+        jpeg_components: None,
+        adobe_inverted: false,
+        alpha: (!is_opaque).then(|| compress_to_vec_zlib(&alpha, 6)),
+    })
+}
+
+// JPEGs are embedded without decoding, only their size and color components are read
+// from the frame header. CMYK JPEGs written by Adobe store their colors inverted.
+fn read_jpeg_header(bytes: &[u8]) -> Option<(u32, u32, u8, bool)> {
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+        return None;
+    }
+    let mut adobe = false;
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xFF {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        let length = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        if marker == 0xEE && bytes.get(i + 4..i + 9) == Some(b"Adobe") {
+            adobe = true;
+        }
+        let is_frame = (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker);
+        if is_frame {
+            let header = bytes.get(i + 5..i + 10)?;
+            let height = u16::from_be_bytes([header[0], header[1]]) as u32;
+            let width = u16::from_be_bytes([header[2], header[3]]) as u32;
+            return Some((width, height, header[4], adobe));
+        }
+        i += 2 + length;
+    }
+    None
+}
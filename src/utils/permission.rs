@@ -0,0 +1,106 @@
+// Permission names as stored in the permissions table. Roles get them through
+// role_permissions, handlers check them with `Authorized<..>` or `AuthUser::has_permission`.
+pub const CATALOG_VIEW: &str = "catalog.view";
+pub const CATALOG_MANAGE: &str = "catalog.manage";
+pub const CONTENT_MANAGE: &str = "content.manage";
+pub const PRODUCTS_MANAGE_OWN: &str = "products.manage_own";
+pub const PRODUCTS_MANAGE_ANY: &str = "products.manage_any";
+pub const AUCTIONS_MANAGE: &str = "auctions.manage";
+pub const SHOPS_MANAGE_OWN: &str = "shops.manage_own";
+pub const SHOPS_MANAGE_ANY: &str = "shops.manage_any";
+pub const DISCOUNTS_MANAGE: &str = "discounts.manage";
+pub const ORDERS_MANAGE_OWN: &str = "orders.manage_own";
+pub const ORDERS_UPDATE_STATUS: &str = "orders.update_status";
+pub const ORDERS_VIEW_ANY: &str = "orders.view_any";
+pub const USERS_MANAGE: &str = "users.manage";
+pub const USERS_VIEW_PHONE: &str = "users.view_phone";
+pub const USERS_VIEW_ADDRESS: &str = "users.view_address";
+pub const REPORTS_VIEW: &str = "reports.view";
+pub const NOTIFICATIONS_BROADCAST: &str = "notifications.broadcast";
+pub const CHAT_MODERATE: &str = "chat.moderate";
+pub const ROLES_MANAGE: &str = "roles.manage";
+pub const SELLERS_APPROVE: &str = "sellers.approve";
+pub const BILLING_MANAGE: &str = "billing.manage";
+
+// Marker for `Authorized<P>`, one per permission a route can require
+pub trait Permission {
+    const NAME: &'static str;
+}
+
+pub struct CatalogView;
+
+impl Permission for CatalogView {
+    const NAME: &'static str = CATALOG_VIEW;
+}
+
+pub struct CatalogManage;
+
+impl Permission for CatalogManage {
+    const NAME: &'static str = CATALOG_MANAGE;
+}
+
+pub struct ContentManage;
+
+impl Permission for ContentManage {
+    const NAME: &'static str = CONTENT_MANAGE;
+}
+
+pub struct ProductsManageOwn;
+
+impl Permission for ProductsManageOwn {
+    const NAME: &'static str = PRODUCTS_MANAGE_OWN;
+}
+
+pub struct AuctionsManage;
+
+impl Permission for AuctionsManage {
+    const NAME: &'static str = AUCTIONS_MANAGE;
+}
+
+pub struct ShopsManageOwn;
+
+impl Permission for ShopsManageOwn {
+    const NAME: &'static str = SHOPS_MANAGE_OWN;
+}
+
+pub struct DiscountsManage;
+
+impl Permission for DiscountsManage {
+    const NAME: &'static str = DISCOUNTS_MANAGE;
+}
+
+pub struct UsersManage;
+
+impl Permission for UsersManage {
+    const NAME: &'static str = USERS_MANAGE;
+}
+
+pub struct ReportsView;
+
+impl Permission for ReportsView {
+    const NAME: &'static str = REPORTS_VIEW;
+}
+
+pub struct NotificationsBroadcast;
+
+impl Permission for NotificationsBroadcast {
+    const NAME: &'static str = NOTIFICATIONS_BROADCAST;
+}
+
+pub struct RolesManage;
+
+impl Permission for RolesManage {
+    const NAME: &'static str = ROLES_MANAGE;
+}
+
+pub struct SellersApprove;
+
+impl Permission for SellersApprove {
+    const NAME: &'static str = SELLERS_APPROVE;
+}
+
+pub struct BillingManage;
+
+impl Permission for BillingManage {
+    const NAME: &'static str = BILLING_MANAGE;
+}
@@ -37,3 +37,209 @@ pub fn get_min_demo_version() -> i32 {
     let min_demo_version = std::env::var("MIN_DEMO_VERSION").unwrap_or("999.0.0".to_string());
     min_demo_version.replace(".", "").parse().unwrap()
 }
+
+pub fn get_auction_min_bid_increment() -> f64 {
+    std::env::var("AUCTION_MIN_BID_INCREMENT")
+        .unwrap_or("1".to_string())
+        .parse()
+        .unwrap_or(1.0)
+}
+
+pub fn get_auction_closer_interval() -> u64 {
+    std::env::var("AUCTION_CLOSER_INTERVAL_SECONDS")
+        .unwrap_or("60".to_string())
+        .parse()
+        .unwrap_or(60)
+}
+
+pub fn get_auction_closing_soon_minutes() -> i32 {
+    std::env::var("AUCTION_CLOSING_SOON_MINUTES")
+        .unwrap_or("10".to_string())
+        .parse()
+        .unwrap_or(10)
+}
+
+pub fn get_db_pool_size() -> usize {
+    std::env::var("DB_POOL_SIZE")
+        .unwrap_or("16".to_string())
+        .parse()
+        .unwrap_or(16)
+}
+
+pub fn get_db_pool_timeout() -> u64 {
+    std::env::var("DB_POOL_TIMEOUT_SECONDS")
+        .unwrap_or("5".to_string())
+        .parse()
+        .unwrap_or(5)
+}
+
+pub fn get_access_token_expires_in() -> u64 {
+    std::env::var("ACCESS_TOKEN_EXPIRES_IN_SECONDS")
+        .unwrap_or("900".to_string())
+        .parse()
+        .unwrap_or(900)
+}
+
+pub fn get_refresh_token_expires_in_days() -> i32 {
+    std::env::var("REFRESH_TOKEN_EXPIRES_IN_DAYS")
+        .unwrap_or("30".to_string())
+        .parse()
+        .unwrap_or(30)
+}
+
+// "log" only prints reset codes for local development, anything else sends them through
+// the SMS and email gateways
+pub fn get_password_reset_delivery() -> String {
+    std::env::var("PASSWORD_RESET_DELIVERY").unwrap_or("gateway".to_string())
+}
+
+pub fn get_sms_gateway_url() -> String {
+    std::env::var("SMS_GATEWAY_URL").unwrap_or("".to_string())
+}
+
+pub fn get_email_gateway_url() -> String {
+    std::env::var("EMAIL_GATEWAY_URL").unwrap_or("".to_string())
+}
+
+pub fn get_message_gateway_auth() -> String {
+    std::env::var("MESSAGE_GATEWAY_AUTH").unwrap_or("".to_string())
+}
+
+pub fn get_password_reset_code_expires_in_minutes() -> i32 {
+    std::env::var("PASSWORD_RESET_CODE_EXPIRES_IN_MINUTES")
+        .unwrap_or("10".to_string())
+        .parse()
+        .unwrap_or(10)
+}
+
+// Failed attempts allowed before every further attempt has to wait, the wait doubles
+// with each failure starting from LOGIN_BACKOFF_BASE_SECONDS
+pub fn get_login_backoff_after_failures() -> i32 {
+    std::env::var("LOGIN_BACKOFF_AFTER_FAILURES")
+        .unwrap_or("3".to_string())
+        .parse()
+        .unwrap_or(3)
+}
+
+pub fn get_login_backoff_base_seconds() -> i64 {
+    std::env::var("LOGIN_BACKOFF_BASE_SECONDS")
+        .unwrap_or("1".to_string())
+        .parse()
+        .unwrap_or(1)
+}
+
+pub fn get_account_lockout_after_failures() -> i32 {
+    std::env::var("ACCOUNT_LOCKOUT_AFTER_FAILURES")
+        .unwrap_or("10".to_string())
+        .parse()
+        .unwrap_or(10)
+}
+
+// Higher than the account limit because many users can share one address
+pub fn get_ip_lockout_after_failures() -> i32 {
+    std::env::var("IP_LOCKOUT_AFTER_FAILURES")
+        .unwrap_or("50".to_string())
+        .parse()
+        .unwrap_or(50)
+}
+
+// Also how long failures are remembered after the last one
+pub fn get_login_lockout_minutes() -> i64 {
+    std::env::var("LOGIN_LOCKOUT_MINUTES")
+        .unwrap_or("15".to_string())
+        .parse()
+        .unwrap_or(15)
+}
+
+// Only enable behind a reverse proxy that sets X-Forwarded-For, otherwise clients can
+// pick their own address
+pub fn get_trust_proxy_headers() -> bool {
+    std::env::var("TRUST_PROXY_HEADERS").unwrap_or("false".to_string()) == "true"
+}
+
+// OAuth client ids of the Android, iOS and web apps, comma separated
+pub fn get_google_client_ids() -> Vec<String> {
+    std::env::var("GOOGLE_CLIENT_IDS")
+        .unwrap_or("".to_string())
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+pub fn get_google_jwks_url() -> String {
+    std::env::var("GOOGLE_JWKS_URL")
+        .unwrap_or("https://www.googleapis.com/oauth2/v3/certs".to_string())
+}
+
+// A JWKS document used instead of fetching GOOGLE_JWKS_URL, for tests and offline setups
+pub fn get_google_jwks() -> String {
+    std::env::var("GOOGLE_JWKS").unwrap_or("".to_string())
+}
+
+// Bundle id of the iOS app and service id of the web client, comma separated
+pub fn get_apple_client_ids() -> Vec<String> {
+    std::env::var("APPLE_CLIENT_IDS")
+        .unwrap_or("".to_string())
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+pub fn get_apple_jwks_url() -> String {
+    std::env::var("APPLE_JWKS_URL").unwrap_or("https://appleid.apple.com/auth/keys".to_string())
+}
+
+// A JWKS document used instead of fetching APPLE_JWKS_URL, for tests and offline setups
+pub fn get_apple_jwks() -> String {
+    std::env::var("APPLE_JWKS").unwrap_or("".to_string())
+}
+
+// Used when the key endpoint sends no Cache-Control max-age
+pub fn get_jwks_cache_seconds() -> u64 {
+    std::env::var("JWKS_CACHE_SECONDS")
+        .unwrap_or("3600".to_string())
+        .parse()
+        .unwrap_or(3600)
+}
+
+pub fn get_facebook_app_id() -> String {
+    std::env::var("FACEBOOK_APP_ID").unwrap_or("".to_string())
+}
+
+pub fn get_facebook_app_secret() -> String {
+    std::env::var("FACEBOOK_APP_SECRET").unwrap_or("".to_string())
+}
+
+// How often monthly seller statements are generated and overdue shops suspended
+pub fn get_billing_interval() -> u64 {
+    std::env::var("BILLING_INTERVAL_SECONDS")
+        .unwrap_or("3600".to_string())
+        .parse()
+        .unwrap_or(3600)
+}
+
+// How often flash sales that started or ended are picked up and announced
+pub fn get_flash_sale_interval() -> u64 {
+    std::env::var("FLASH_SALE_INTERVAL_SECONDS")
+        .unwrap_or("60".to_string())
+        .parse()
+        .unwrap_or(60)
+}
+
+// Days a seller has to pay a statement after it is issued
+pub fn get_seller_statement_due_days() -> i32 {
+    std::env::var("SELLER_STATEMENT_DUE_DAYS")
+        .unwrap_or("7".to_string())
+        .parse()
+        .unwrap_or(7)
+}
+
+// Days past the due date a statement with a payslip waiting for review is not overdue
+pub fn get_seller_statement_review_days() -> i32 {
+    std::env::var("SELLER_STATEMENT_REVIEW_DAYS")
+        .unwrap_or("3".to_string())
+        .parse()
+        .unwrap_or(3)
+}